@@ -22,6 +22,12 @@ player2-join-controller-keyboard =
 
     Press Select On A Gamepad Or Keyboard
 
+player2-join-controller-split-keyboard =
+                        Player 2
+
+
+    Press Select On A Gamepad Or The Arrow Keys Side
+
 player2-join-controller =
                 Player 2
     
@@ -17,6 +17,7 @@ fn main() {
     game.install_plugin(DefaultGamePlugin);
     game.install_plugin(LocalInputGamePlugin);
     game.init_shared_resource::<AssetServer>();
+    game.init_shared_resource::<CpuBrains>();
 
     game.sessions
         .create_with("play", |builder: &mut SessionBuilder| {
@@ -24,12 +25,15 @@ fn main() {
                 .install_plugin(DefaultSessionPlugin)
                 .install_plugin(self::BehaviorsPlugin)
                 .install_plugin(self::ScenePlugin {
-                    mode: PlayMode::Offline(PlayersInfo {
-                        a1: PlayerInfo::CPU,
-                        a2: PlayerInfo::CPU,
-                        b1: PlayerInfo::CPU,
-                        b2: PlayerInfo::CPU,
-                    }),
+                    mode: PlayMode::Offline(
+                        PlayersInfo {
+                            a1: PlayerInfo::cpu(),
+                            a2: PlayerInfo::cpu(),
+                            b1: PlayerInfo::cpu(),
+                            b2: PlayerInfo::cpu(),
+                        },
+                        default(),
+                    ),
                 })
                 .add_startup_system(play::set_player_states_free);
         });
@@ -0,0 +1,41 @@
+//! Runs episodes of the headless environment as fast as possible with a random agent.
+use std::time::Instant;
+use striker_ball::{env::*, PlayerSlot};
+
+fn main() {
+    let mut env = Env::new("assets");
+    let start = Instant::now();
+    let mut frames = 0;
+
+    for seed in 0..10 {
+        env.reset(EnvConfig {
+            seed,
+            agents: vec![PlayerSlot::A1],
+            kick_speed: 2.0,
+            ..Default::default()
+        });
+        let mut total_reward = 0.0;
+        loop {
+            let t = frames as f32 / 30.0;
+            let action = EnvAction {
+                x: t.cos(),
+                y: t.sin(),
+                tackle: frames % 20 == 0,
+                ..Default::default()
+            };
+            let (_observation, rewards, done) = env.step(&[action]);
+            total_reward += rewards[0];
+            frames += 1;
+            if done {
+                break;
+            }
+        }
+        println!("episode {seed}: reward {total_reward}");
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "{frames} frames in {elapsed:.2}s ({:.0} fps)",
+        frames as f64 / elapsed
+    );
+}
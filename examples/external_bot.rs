@@ -0,0 +1,62 @@
+#![allow(clippy::too_many_arguments)]
+use bones_bevy_renderer::BonesBevyRenderer;
+use bones_framework::prelude::*;
+use striker_ball::*;
+
+const fn namespace() -> (&'static str, &'static str, &'static str) {
+    ("striker_ball", "example", "external_bot")
+}
+
+/// Usage: `cargo run --example external_bot -- [tcp:<host>:<port> | unix:<path>] [--lockstep]`
+///
+/// The bot drives `A1` against three default cpus, see `scripts/example_bot.py`.
+fn main() {
+    setup_logs!(namespace());
+
+    crate::register_schemas();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let lockstep = args.iter().any(|arg| arg == "--lockstep");
+    let address = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or("tcp:127.0.0.1:7777", String::as_str)
+        .parse::<BotAddress>()
+        .unwrap();
+
+    let mut game = Game::new();
+
+    game.install_plugin(DefaultGamePlugin);
+    game.install_plugin(LocalInputGamePlugin);
+    game.init_shared_resource::<AssetServer>();
+
+    let mut brains = CpuBrains::default();
+    brains.register("external", ExternalBrain::new(address, lockstep));
+    game.insert_shared_resource(brains);
+
+    game.sessions
+        .create_with("play", |builder: &mut SessionBuilder| {
+            builder
+                .install_plugin(DefaultSessionPlugin)
+                .install_plugin(self::BehaviorsPlugin)
+                .install_plugin(self::ScenePlugin {
+                    mode: PlayMode::Offline(
+                        PlayersInfo {
+                            a1: PlayerInfo::CPU {
+                                brain: ustr("external"),
+                            },
+                            a2: PlayerInfo::cpu(),
+                            b1: PlayerInfo::cpu(),
+                            b2: PlayerInfo::cpu(),
+                        },
+                        default(),
+                    ),
+                })
+                .add_startup_system(play::set_player_states_free);
+        });
+
+    BonesBevyRenderer::new(game)
+        .namespace(namespace())
+        .app()
+        .run();
+}
@@ -0,0 +1,34 @@
+//! Tunes the cpu parameters with headless self-play.
+//!
+//! Usage: `cargo run --release --example tune_cpu -- [generations] [output]`
+//!
+//! The best set is written over `assets/params.cpu.yaml`, the cpu asset the
+//! game loads, unless another output is given.
+use bones_framework::prelude::*;
+use striker_ball::{env::Env, tuner::*};
+
+const fn namespace() -> (&'static str, &'static str, &'static str) {
+    ("striker_ball", "example", "tune_cpu")
+}
+
+fn main() {
+    setup_logs!(namespace());
+
+    let mut args = std::env::args().skip(1);
+    let generations = args.next().map_or(20, |arg| arg.parse().unwrap());
+    let output = args.next().unwrap_or_else(|| PARAMS_ASSET.to_string());
+
+    let mut env = Env::new("assets");
+    let start = asset_params(&mut env);
+    let best = tune(
+        &mut env,
+        start,
+        &TunerConfig {
+            generations,
+            ..Default::default()
+        },
+    );
+
+    write_params(&best, &output).unwrap();
+    println!("wrote the best cpu params to {output}");
+}
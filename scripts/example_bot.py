@@ -0,0 +1,24 @@
+#!/usr/bin/env python3
+"""A minimal bot for the `external_bot` example that runs at the ball.
+
+Run this first, then `cargo run --example external_bot -- tcp:127.0.0.1:7777`.
+"""
+import json
+import socket
+
+HOST, PORT = "127.0.0.1", 7777
+
+with socket.create_server((HOST, PORT)) as server:
+    print(f"waiting for the game on {HOST}:{PORT}")
+    conn, _ = server.accept()
+    with conn, conn.makefile("rw") as stream:
+        for line in stream:
+            message = json.loads(line)
+            game = message["game"]
+            me = next(p for p in game["players"] if p["slot"] == message["slot"])
+            dx = game["ball"]["pos"][0] - me["pos"][0]
+            dy = game["ball"]["pos"][1] - me["pos"][1]
+            length = max((dx * dx + dy * dy) ** 0.5, 1e-6)
+            action = {"x": dx / length, "y": dy / length, "shoot": False, "pass": False, "tackle": False}
+            stream.write(json.dumps(action) + "\n")
+            stream.flush()
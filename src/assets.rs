@@ -8,6 +8,7 @@ pub struct Data {
     pub localization: Handle<LocalizationAsset>,
     pub screen_size: Vec2,
     pub constant: Constants,
+    pub cpu: Handle<CpuParams>,
     pub sprite: Sprites,
     pub sound: Sounds,
     pub font: Fonts,
@@ -61,6 +62,8 @@ pub struct Constants {
     pub pass_frames: u64,
     pub recieve_frames: u64,
     pub turn_frames: u64,
+    /// How many frames a shoot, pass or tackle press is kept for, see [`ActionBuffer`].
+    pub input_buffer_frames: u64,
     pub dribble_smoothing: f32,
     pub dribble_smoothing_threshold: f32,
 
@@ -72,6 +75,53 @@ pub struct Constants {
     pub pin_radius: f32,
 }
 
+/// The numbers the cpu players decide with, loaded from `assets/params.cpu.yaml`.
+///
+/// These can be tuned with self-play, see [`crate::tuner`].
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, serde::Serialize)]
+#[type_data(metadata_asset("cpu"))]
+#[repr(C)]
+pub struct CpuParams {
+    /// How close the cpu needs to be to tackle, in player radii.
+    pub tackle_distance: f32,
+    /// How close an enemy needs to be for the partner to be pressured, in tackle distances.
+    pub pressure_distance: f32,
+    /// How lined up with the ball a tackle needs to be, in ball radii.
+    pub tackle_alignment: f32,
+    /// How close the cpu needs to be aiming at a pin to shoot, in degrees.
+    pub aim_tolerance: f32,
+    /// How close a wall needs to be to stop fleeing towards it, in player radii.
+    pub wall_distance: f32,
+    /// How far out an enemy can be before it isn't followed anymore,
+    /// as a fraction of the player bounds.
+    pub enemy_wall_distance: f32,
+    /// How close to an enemy the cpu needs to be to stop following it, in player radii.
+    pub enemy_y_tolerance: f32,
+    /// How far ahead of the ball the support stays, as a fraction of the player bounds.
+    pub support_distance: f32,
+    /// How far out to the side the support stays, as a fraction of the player bounds.
+    pub support_spread: f32,
+    /// How far out to the side the sweeper goes, as a fraction of the player bounds.
+    pub sweeper_spread: f32,
+}
+impl CpuParams {
+    /// Every parameter, for tuning them all the same way.
+    pub fn values_mut(&mut self) -> [&mut f32; 10] {
+        [
+            &mut self.tackle_distance,
+            &mut self.pressure_distance,
+            &mut self.tackle_alignment,
+            &mut self.aim_tolerance,
+            &mut self.wall_distance,
+            &mut self.enemy_wall_distance,
+            &mut self.enemy_y_tolerance,
+            &mut self.support_distance,
+            &mut self.support_spread,
+            &mut self.sweeper_spread,
+        ]
+    }
+}
+
 #[derive(HasSchema, Clone, Default)]
 #[repr(C)]
 pub struct Sprites {
@@ -0,0 +1,501 @@
+//! A headless, gym-style environment over the play simulation for
+//! reinforcement learning.
+//!
+//! ```no_run
+//! use striker_ball::{env::*, PlayerSlot};
+//!
+//! let mut env = Env::new("assets");
+//! let mut observation = env.reset(EnvConfig {
+//!     agents: vec![PlayerSlot::A1],
+//!     ..Default::default()
+//! });
+//! while observation.frame < 600 {
+//!     let action = EnvAction { x: 1.0, ..Default::default() };
+//!     let (next, _rewards, done) = env.step(&[action]);
+//!     observation = next;
+//!     if done {
+//!         break;
+//!     }
+//! }
+//! ```
+//!
+//! Every player that isn't an agent is played by the [`DefaultBrain`]. There
+//! is no play flow (countdown, score display, etc.) so an episode ends once
+//! [`EnvConfig::target_score`] is reached or after [`EnvConfig::max_frames`].
+use super::*;
+use bones_framework::networking::RUNTIME;
+use std::path::Path;
+
+/// The action of one agent, the same as an external bot's.
+pub type EnvAction = BotAction;
+
+/// The player states in the order of their one-hot encoding in the features.
+pub fn player_states() -> [Ustr; 13] {
+    use player::state::*;
+    [
+        free(),
+        tackle(),
+        tackled(),
+        grab(),
+        dribble(),
+        shoot(),
+        pass(),
+        turn(),
+        recieve(),
+        kick(),
+        lose(),
+        win(),
+        wait(),
+    ]
+}
+
+/// The amount of features for each player.
+pub const PLAYER_FEATURES: usize = 7 + 13;
+/// The amount of features for the ball.
+pub const BALL_FEATURES: usize = 5;
+
+#[derive(Clone, Debug)]
+pub struct EnvConfig {
+    /// Seeds the starting kick of the ball.
+    pub seed: u64,
+    /// The players controlled through [`Env::step`], in the order of the actions and rewards.
+    pub agents: Vec<PlayerSlot>,
+    /// The speed of the ball at the start of an episode in a direction picked from the seed.
+    pub kick_speed: f32,
+    pub max_frames: u64,
+    /// The score of either team that ends the episode.
+    pub target_score: u8,
+    pub rewards: RewardConfig,
+    /// Overrides the cpu parameters of the game asset.
+    pub cpu_params: TeamCpuParams,
+}
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            agents: vec![PlayerSlot::A1],
+            kick_speed: 0.0,
+            max_frames: 60 * 60,
+            target_score: 1,
+            rewards: default(),
+            cpu_params: default(),
+        }
+    }
+}
+
+/// The reward of an agent for each event, from the perspective of the agent's team
+/// unless it says otherwise.
+#[derive(Clone, Debug)]
+pub struct RewardConfig {
+    /// An enemy pin is hit.
+    pub pin_hit: f32,
+    /// One of the team's pins is hit.
+    pub pin_lost: f32,
+    /// The agent itself gets the ball.
+    pub possession_gained: f32,
+    /// The agent itself loses the ball to the enemy team.
+    pub possession_lost: f32,
+    /// Given every frame, usually a small negative number to hurry the agent.
+    pub frame: f32,
+}
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            pin_hit: 1.0,
+            pin_lost: -1.0,
+            possession_gained: 0.1,
+            possession_lost: -0.1,
+            frame: 0.0,
+        }
+    }
+}
+
+/// A fixed-size feature vector of the match.
+///
+/// The layout is, with positions and velocities divided by the player bounds:
+/// - For each of `A1`, `A2`, `B1`, `B2`, [`PLAYER_FEATURES`] of
+///   `x, y, velocity x, velocity y, angle x, angle y, owns the ball`
+///   followed by a one-hot of the [`player_states`].
+/// - [`BALL_FEATURES`] of `x, y, velocity x, velocity y, is loose`.
+/// - For each of the pins at the start of the episode, team A first from
+///   bottom to top, `1.0` if it is still standing.
+/// - The score of team A and team B.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Observation {
+    pub frame: u64,
+    pub features: Vec<f32>,
+}
+
+/// Overrides the input of the agent players, indexed by [`PlayerSlot::index`].
+#[derive(HasSchema, Clone, Default)]
+pub struct EnvInputs {
+    pub inputs: [Option<PlayInput>; 4],
+}
+
+/// Runs exactly one frame for every [`SessionRunner::step`].
+pub struct EnvRunner {
+    pub collectors: [PlayTeamInputCollector; 2],
+}
+impl Default for EnvRunner {
+    fn default() -> Self {
+        use PlayerSlot::*;
+        Self {
+            collectors: [
+                PlayTeamInputCollector::new(SingleSource::CPU(A1), SingleSource::CPU(A2)),
+                PlayTeamInputCollector::new(SingleSource::CPU(B1), SingleSource::CPU(B2))
+                    .with_team(Team::B),
+            ],
+        }
+    }
+}
+impl SessionRunner for EnvRunner {
+    fn step(&mut self, _frame_start: Instant, world: &mut World, stages: &mut SystemStages) {
+        for collector in &mut self.collectors {
+            collector.apply_inputs(world);
+        }
+
+        world
+            .resource_mut::<Time>()
+            .advance_exact(std::time::Duration::from_secs_f64(TARGET_STEP));
+
+        let env_inputs = world.resource::<EnvInputs>().clone();
+        {
+            let mut play_inputs = world.resource_mut::<PlayTeamInputs>();
+            for (client, collector) in play_inputs.clients.iter_mut().zip(&self.collectors) {
+                client.update_from_dense(&collector.get_control().get_dense_input());
+            }
+            for slot in PlayerSlot::variants() {
+                let Some(input) = env_inputs.inputs[slot.index()] else {
+                    continue;
+                };
+                let control = play_inputs.get_character_control_mut(slot);
+                // Goes through the dense input so the agents play by the same
+                // precision as everyone else.
+                control.update_from_dense(&input.get_dense_input());
+            }
+        }
+
+        stages.run(world);
+    }
+
+    fn restart_session(&mut self) {
+        *self = EnvRunner::default();
+    }
+
+    fn disable_local_input(&mut self, _disable_input: bool) {}
+}
+
+/// The environment, see the [module docs](self).
+pub struct Env {
+    game: Game,
+    config: EnvConfig,
+    frame: u64,
+    /// The agent inputs kept between steps so that presses carry over.
+    inputs: Vec<PlayInput>,
+    /// The pins at the start of the episode in the feature order.
+    pins: Vec<Entity>,
+    previous_positions: [Vec2; 4],
+    previous_owner: Option<PlayerSlot>,
+    previous_score: PinScore,
+}
+impl Env {
+    /// Creates the environment, loading the game assets from `asset_dir`.
+    pub fn new(asset_dir: impl AsRef<Path>) -> Self {
+        crate::register_schemas();
+
+        let asset_dir = asset_dir.as_ref();
+        let mut asset_server = AssetServer::default();
+        asset_server.set_io(FileAssetIo::new(asset_dir, &asset_dir.join("packs")));
+        asset_server.register_default_assets();
+        RUNTIME
+            .block_on(asset_server.load_assets())
+            .expect("failed to load the game assets");
+
+        let mut game = Game::new();
+        game.insert_shared_resource(asset_server);
+        game.init_shared_resource::<CpuBrains>();
+        game.init_shared_resource::<InputSettings>();
+
+        Self {
+            game,
+            config: default(),
+            frame: 0,
+            inputs: Vec::new(),
+            pins: Vec::new(),
+            previous_positions: default(),
+            previous_owner: None,
+            previous_score: default(),
+        }
+    }
+
+    /// Starts a new episode.
+    pub fn reset(&mut self, config: EnvConfig) -> Observation {
+        self.inputs = vec![PlayInput::default(); config.agents.len()];
+        self.config = config;
+        self.frame = 0;
+        let cpu_params = self.config.cpu_params.clone();
+
+        self.game.sessions.delete(session::PLAY);
+        self.game
+            .sessions
+            .create_with(session::PLAY, move |builder: &mut SessionBuilder| {
+                builder
+                    .install_plugin(DefaultSessionPlugin)
+                    .install_plugin(BehaviorsPlugin)
+                    .insert_resource(PlayMode::Offline(
+                        PlayersInfo {
+                            a1: PlayerInfo::cpu(),
+                            a2: PlayerInfo::cpu(),
+                            b1: PlayerInfo::cpu(),
+                            b2: PlayerInfo::cpu(),
+                        },
+                        default(),
+                    ))
+                    .init_resource::<PlayTeamInputs>()
+                    .init_resource::<EnvInputs>()
+                    .insert_resource(cpu_params)
+                    .add_startup_system(spawn::scene)
+                    .add_startup_system(play::set_player_states_free)
+                    .add_system_to_stage(Last, |mut inputs: ResMut<PlayTeamInputs>| {
+                        inputs.advance_frame()
+                    });
+                builder.runner = Box::<EnvRunner>::default();
+            });
+
+        // Run the startup systems.
+        self.game.step(Instant::now());
+
+        let mut rng = SplitMix64(self.config.seed);
+        let kick =
+            Vec2::from_angle(rng.next_f32() * std::f32::consts::TAU) * self.config.kick_speed;
+        let world = self.world();
+        let pins = world.run_system(episode_pins, ());
+        world.run_system(kick_ball, kick);
+
+        self.pins = pins;
+        self.previous_positions = world.run_system(player_positions, ());
+        self.previous_owner = None;
+        self.previous_score = default();
+
+        self.observe()
+    }
+
+    /// Runs one frame with an action for each of the [`EnvConfig::agents`],
+    /// returning the observation, a reward for each agent, and whether or not
+    /// the episode is done.
+    pub fn step(&mut self, actions: &[EnvAction]) -> (Observation, Vec<f32>, bool) {
+        assert_eq!(
+            actions.len(),
+            self.config.agents.len(),
+            "there must be one action per agent"
+        );
+        let mut env_inputs = EnvInputs::default();
+        for ((slot, action), input) in self.config.agents.iter().zip(actions).zip(&mut self.inputs)
+        {
+            action.apply(input);
+            env_inputs.inputs[slot.index()] = Some(*input);
+        }
+        *self.world().resource_mut::<EnvInputs>() = env_inputs;
+
+        self.game.step(Instant::now());
+        self.frame += 1;
+
+        let observation = self.observe();
+        let rewards = self.rewards();
+        let score = *self.world().resource::<PinScore>();
+        let done = score.a.max(score.b) >= self.config.target_score
+            || observation.frame >= self.config.max_frames;
+
+        self.previous_positions = self.world().run_system(player_positions, ());
+        self.previous_owner = self.world().run_system(ball_owner, ());
+        self.previous_score = score;
+
+        (observation, rewards, done)
+    }
+
+    /// The play session world for anything the observation doesn't cover.
+    pub fn world(&mut self) -> &mut World {
+        &mut self
+            .game
+            .sessions
+            .get_mut(session::PLAY)
+            .expect("the environment needs to be reset first")
+            .world
+    }
+
+    fn observe(&mut self) -> Observation {
+        let previous_positions = self.previous_positions;
+        let pins = self.pins.clone();
+        let features = self
+            .world()
+            .run_system(observe_features, (previous_positions, pins));
+        Observation {
+            frame: self.frame,
+            features,
+        }
+    }
+
+    fn rewards(&mut self) -> Vec<f32> {
+        let RewardConfig {
+            pin_hit,
+            pin_lost,
+            possession_gained,
+            possession_lost,
+            frame,
+        } = self.config.rewards;
+        let score = *self.world().resource::<PinScore>();
+        let owner = self.world().run_system(ball_owner, ());
+        let scored_a = (score.a - self.previous_score.a) as f32;
+        let scored_b = (score.b - self.previous_score.b) as f32;
+
+        self.config
+            .agents
+            .iter()
+            .map(|slot| {
+                let (scored, conceded) = match slot.team() {
+                    Team::A => (scored_a, scored_b),
+                    Team::B => (scored_b, scored_a),
+                };
+                let mut reward = frame + scored * pin_hit + conceded * pin_lost;
+                if owner == Some(*slot) && self.previous_owner != Some(*slot) {
+                    reward += possession_gained;
+                }
+                let lost_to_enemy = owner.is_some_and(|owner| owner.team() != slot.team());
+                if self.previous_owner == Some(*slot) && lost_to_enemy {
+                    reward += possession_lost;
+                }
+                reward
+            })
+            .collect()
+    }
+}
+
+fn episode_pins(
+    entities: Res<Entities>,
+    pins: Comp<Pin>,
+    teams: Comp<Team>,
+    transforms: Comp<Transform>,
+) -> Vec<Entity> {
+    let mut pins = entities
+        .iter_with((&pins, &teams, &transforms))
+        .map(|(pin_e, (_pin, team, transform))| (*team as u8, transform.translation.y, pin_e))
+        .collect::<Vec<_>>();
+    pins.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    pins.into_iter().map(|(_, _, pin_e)| pin_e).collect()
+}
+
+fn kick_ball(In(velocity): In<Vec2>, entities: Res<Entities>, mut balls: CompMut<Ball>) {
+    for (_ball_e, ball) in entities.iter_with(&mut balls) {
+        ball.velocity = velocity;
+    }
+}
+
+fn player_positions(
+    player_ent_signs: Res<PlayerEntSigns>,
+    transforms: Comp<Transform>,
+) -> [Vec2; 4] {
+    player_ent_signs
+        .entities()
+        .map(|player_e| transforms.get(player_e).unwrap().translation.xy())
+}
+
+fn ball_owner(
+    entities: Res<Entities>,
+    balls: Comp<Ball>,
+    players: Comp<Player>,
+) -> Option<PlayerSlot> {
+    let (_ball_e, ball) = entities.iter_with(&balls).next()?;
+    ball.owner
+        .option()
+        .and_then(|owner| players.get(owner))
+        .map(|player| player.slot)
+}
+
+fn observe_features(
+    In((previous_positions, episode_pins)): In<([Vec2; 4], Vec<Entity>)>,
+    entities: Res<Entities>,
+    player_ent_signs: Res<PlayerEntSigns>,
+    players: Comp<Player>,
+    states: Comp<State>,
+    balls: Comp<Ball>,
+    pins: Comp<Pin>,
+    transforms: Comp<Transform>,
+    pin_score: Res<PinScore>,
+    root: Root<Data>,
+) -> Vec<f32> {
+    let bounds = root.constant.player_bounds;
+    let scale = |v: Vec2| [v.x / bounds.x, v.y / bounds.y];
+    let (ball_e, ball) = entities.iter_with(&balls).next().unwrap();
+
+    let mut features = Vec::new();
+
+    for (i, player_e) in player_ent_signs.entities().into_iter().enumerate() {
+        let pos = transforms.get(player_e).unwrap().translation.xy();
+        let player = players.get(player_e).unwrap();
+        let state = states.get(player_e).unwrap().current;
+
+        features.extend(scale(pos));
+        features.extend(scale(pos - previous_positions[i]));
+        features.extend([player.angle.x, player.angle.y]);
+        features.push((ball.owner == Maybe::Set(player_e)) as u8 as f32);
+        features.extend(player_states().map(|s| (s == state) as u8 as f32));
+    }
+
+    features.extend(scale(transforms.get(ball_e).unwrap().translation.xy()));
+    features.extend(scale(ball.velocity));
+    features.push(ball.owner.is_none() as u8 as f32);
+
+    for pin_e in episode_pins {
+        let standing = pins.get(pin_e).is_some_and(|pin| pin.standing);
+        features.push(standing as u8 as f32);
+    }
+
+    features.extend([pin_score.a as f32, pin_score.b as f32]);
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every observation, reward and done flag of an episode.
+    fn episode(seed: u64) -> Vec<(Observation, Vec<f32>, bool)> {
+        let mut env = Env::new(concat!(env!("CARGO_MANIFEST_DIR"), "/assets"));
+        let observation = env.reset(EnvConfig {
+            seed,
+            agents: vec![PlayerSlot::A1, PlayerSlot::B2],
+            kick_speed: 4.0,
+            max_frames: 600,
+            ..default()
+        });
+        let actions = [
+            EnvAction {
+                x: 1.0,
+                shoot: true,
+                ..default()
+            },
+            EnvAction {
+                y: -1.0,
+                tackle: true,
+                ..default()
+            },
+        ];
+
+        let mut steps = vec![(observation, Vec::new(), false)];
+        loop {
+            let step = env.step(&actions);
+            let done = step.2;
+            steps.push(step);
+            if done {
+                return steps;
+            }
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let first = episode(42);
+        assert_eq!(first, episode(42));
+        assert_ne!(first, episode(43));
+    }
+}
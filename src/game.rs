@@ -18,6 +18,7 @@ pub fn run() {
 
     game.install_plugin(DefaultGamePlugin);
     game.init_shared_resource::<AssetServer>();
+    game.init_shared_resource::<CpuBrains>();
 
     // By inserting `ClearColor` as a shared resource, every session
     // will by default read its own `ClearColor` as `BLACK` unless
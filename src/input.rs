@@ -1,11 +1,18 @@
 use super::*;
 
+pub mod bindings;
+pub use bindings::*;
+
+pub mod stick;
+pub use stick::*;
+
 /// Inserts the [`LocalInputs`] resource and updates and advances it on the
 /// before and after systems on the [`Game`].
 pub struct LocalInputGamePlugin;
 impl GamePlugin for LocalInputGamePlugin {
     fn install(self, game: &mut Game) {
         game.insert_shared_resource(LocalInputs::default());
+        game.init_shared_resource::<InputSettings>();
         game.systems.add_before_system(LocalInputs::update);
         game.systems.add_after_system(LocalInputs::advance);
 
@@ -25,8 +32,12 @@ pub struct LocalInput {
 
     pub pause: PressInput,
 
+    /// The sticks after the [`StickSettings`] are applied.
     pub left_stick: Vec2,
     pub right_stick: Vec2,
+    /// The sticks as the gamepad reports them.
+    pub raw_left_stick: Vec2,
+    pub raw_right_stick: Vec2,
     pub north: PressInput,
     pub south: PressInput,
     pub west: PressInput,
@@ -38,56 +49,60 @@ pub struct LocalInput {
     pub right_trigger: PressInput,
 }
 impl LocalInput {
-    pub fn apply_gamepad_input(&mut self, event: &GamepadEvent) {
-        /// The distance the stick has to move to press its equivalent 'button'.
-        const STROKE: f32 = 0.5;
+    pub fn apply_gamepad_input(
+        &mut self,
+        event: &GamepadEvent,
+        bindings: &InputBindings,
+        sticks: &StickSettings,
+    ) {
         match event {
             GamepadEvent::Axis(GamepadAxisEvent { axis, value, .. }) => match axis {
                 GamepadAxis::LeftStickX => {
-                    self.left_stick.x = *value;
-                    self.menu_right.apply_bool(*value > STROKE);
-                    self.menu_left.apply_bool(*value < -STROKE);
+                    self.raw_left_stick.x = *value;
+                    self.left_stick = sticks.apply(self.raw_left_stick);
+                    self.menu_right.apply_bool(self.left_stick.x > MENU_STROKE);
+                    self.menu_left.apply_bool(self.left_stick.x < -MENU_STROKE);
                 }
                 GamepadAxis::LeftStickY => {
-                    self.left_stick.y = *value;
-                    self.menu_up.apply_bool(*value > STROKE);
-                    self.menu_down.apply_bool(*value < -STROKE);
+                    self.raw_left_stick.y = *value;
+                    self.left_stick = sticks.apply(self.raw_left_stick);
+                    self.menu_up.apply_bool(self.left_stick.y > MENU_STROKE);
+                    self.menu_down.apply_bool(self.left_stick.y < -MENU_STROKE);
+                }
+                GamepadAxis::RightStickX => {
+                    self.raw_right_stick.x = *value;
+                    self.right_stick = sticks.apply(self.raw_right_stick);
+                }
+                GamepadAxis::RightStickY => {
+                    self.raw_right_stick.y = *value;
+                    self.right_stick = sticks.apply(self.raw_right_stick);
                 }
-                GamepadAxis::RightStickX => self.right_stick.x = *value,
-                GamepadAxis::RightStickY => self.right_stick.y = *value,
                 GamepadAxis::LeftZ => {}
                 GamepadAxis::RightZ => {}
                 GamepadAxis::Other(_) => {}
             },
-            GamepadEvent::Button(GamepadButtonEvent { button, value, .. }) => match button {
-                GamepadButton::DPadUp => self.menu_up.apply_value(*value),
-                GamepadButton::DPadDown => self.menu_down.apply_value(*value),
-                GamepadButton::DPadLeft => self.menu_left.apply_value(*value),
-                GamepadButton::DPadRight => self.menu_right.apply_value(*value),
-                GamepadButton::Start => {
-                    self.start.apply_value(*value);
-                    self.pause.apply_value(*value);
+            GamepadEvent::Button(GamepadButtonEvent { button, value, .. }) => {
+                match button {
+                    GamepadButton::North => self.north.apply_value(*value),
+                    GamepadButton::South => self.south.apply_value(*value),
+                    GamepadButton::West => self.west.apply_value(*value),
+                    GamepadButton::East => self.east.apply_value(*value),
+                    GamepadButton::LeftTrigger => self.left_bump.apply_value(*value),
+                    GamepadButton::RightTrigger => self.right_bump.apply_value(*value),
+                    GamepadButton::LeftTrigger2 => self.left_trigger.apply_value(*value),
+                    GamepadButton::RightTrigger2 => self.right_trigger.apply_value(*value),
+                    _ => {}
                 }
-                GamepadButton::North => self.north.apply_value(*value),
-                GamepadButton::South => {
-                    self.south.apply_value(*value);
-                    self.menu_select.apply_value(*value);
+                for action in bindings.button_actions(*button) {
+                    if let Some(input) = self.menu_action_mut(action) {
+                        input.apply_value(*value);
+                    }
                 }
-                GamepadButton::West => {
-                    self.west.apply_value(*value);
-                    self.menu_back.apply_value(*value);
-                }
-                GamepadButton::East => self.east.apply_value(*value),
-                GamepadButton::LeftTrigger => self.left_bump.apply_value(*value),
-                GamepadButton::RightTrigger => self.right_bump.apply_value(*value),
-                GamepadButton::LeftTrigger2 => self.left_trigger.apply_value(*value),
-                GamepadButton::RightTrigger2 => self.right_trigger.apply_value(*value),
-                _ => {}
-            },
+            }
             _ => {}
         }
     }
-    pub fn apply_keyboard_input(&mut self, event: &KeyboardEvent) {
+    pub fn apply_keyboard_input(&mut self, event: &KeyboardEvent, keys: &KeyBindings) {
         let KeyboardEvent {
             key_code: Maybe::Set(key),
             button_state,
@@ -96,18 +111,30 @@ impl LocalInput {
         else {
             return;
         };
-        match key {
-            KeyCode::W => self.menu_up.apply_bool(button_state.pressed()),
-            KeyCode::S => self.menu_down.apply_bool(button_state.pressed()),
-            KeyCode::A => self.menu_left.apply_bool(button_state.pressed()),
-            KeyCode::D => self.menu_right.apply_bool(button_state.pressed()),
-            KeyCode::Escape => {
-                self.menu_back.apply_bool(button_state.pressed());
-                self.pause.apply_bool(button_state.pressed());
+        for action in keys.key_actions(*key) {
+            if let Some(input) = self.menu_action_mut(action) {
+                input.apply_bool(button_state.pressed());
             }
-            KeyCode::Space => self.menu_select.apply_bool(button_state.pressed()),
-            KeyCode::Return => self.start.apply_bool(button_state.pressed()),
-            _ => {}
+        }
+    }
+    /// The menu input for the action, if it is one.
+    fn menu_action_mut(&mut self, action: BindAction) -> Option<&mut PressInput> {
+        match action {
+            BindAction::MenuUp => Some(&mut self.menu_up),
+            BindAction::MenuDown => Some(&mut self.menu_down),
+            BindAction::MenuLeft => Some(&mut self.menu_left),
+            BindAction::MenuRight => Some(&mut self.menu_right),
+            BindAction::MenuSelect => Some(&mut self.menu_select),
+            BindAction::MenuBack => Some(&mut self.menu_back),
+            BindAction::Pause => Some(&mut self.pause),
+            BindAction::Start => Some(&mut self.start),
+            BindAction::Up
+            | BindAction::Down
+            | BindAction::Left
+            | BindAction::Right
+            | BindAction::Shoot
+            | BindAction::Pass
+            | BindAction::Tackle => None,
         }
     }
     pub fn advance(&mut self) {
@@ -121,6 +148,8 @@ impl LocalInput {
             pause,
             left_stick: _,
             right_stick: _,
+            raw_left_stick: _,
+            raw_right_stick: _,
             north,
             south,
             west,
@@ -191,20 +220,38 @@ impl LocalInputs {
         let inputs = &mut *game.shared_resource_mut::<LocalInputs>();
         let gamepad_inputs = game.shared_resource::<GamepadInputs>();
         let keyboard_inputs = game.shared_resource::<KeyboardInputs>();
+        let storage = game.shared_resource::<Storage>();
+        let mut settings = game.shared_resource_mut::<InputSettings>();
+        settings.load(&storage);
+        let InputSettings {
+            bindings, sticks, ..
+        } = &*settings;
 
         for event in &gamepad_inputs.gamepad_events {
+            let gamepad_id = *event.gamepad_id();
+            if let GamepadEvent::Connection(GamepadConnectionEvent {
+                event: GamepadConnectionEventKind::Disconnected,
+                ..
+            }) = event
+            {
+                inputs.sources.remove(&SingleSource::Gamepad(gamepad_id));
+                continue;
+            }
             inputs
                 .sources
-                .entry(SingleSource::Gamepad(*event.gamepad_id()))
+                .entry(SingleSource::Gamepad(gamepad_id))
                 .or_default()
-                .apply_gamepad_input(event);
+                .apply_gamepad_input(event, bindings, &sticks.get(Some(gamepad_id)));
         }
-        for event in &keyboard_inputs.key_events {
-            inputs
-                .sources
-                .entry(SingleSource::KeyboardMouse)
-                .or_default()
-                .apply_keyboard_input(event);
+        for source in [SingleSource::KeyboardMouse, SingleSource::Keyboard2] {
+            let keys = bindings.keys(source).unwrap();
+            for event in &keyboard_inputs.key_events {
+                inputs
+                    .sources
+                    .entry(source)
+                    .or_default()
+                    .apply_keyboard_input(event, keys);
+            }
         }
         for input in inputs.sources.values() {
             if (input.menu_up
@@ -226,6 +273,42 @@ impl LocalInputs {
     }
 }
 
+/// The input settings saved in the [`Storage`], kept here so the inputs don't
+/// copy them out of the storage every frame.
+///
+/// They're loaded the first time the [`LocalInputs`] are updated, once the
+/// storage is, and the screens that change them save them through here.
+#[derive(HasSchema, Clone, Default)]
+pub struct InputSettings {
+    loaded: bool,
+    pub bindings: InputBindings,
+    pub sticks: StickSettingsMap,
+}
+impl InputSettings {
+    /// Loads the settings from the storage unless they already were.
+    pub fn load(&mut self, storage: &Storage) {
+        if !self.loaded {
+            self.bindings = InputBindings::load(storage);
+            self.sticks = StickSettingsMap::load(storage);
+            self.loaded = true;
+        }
+    }
+    /// Saves the bindings to the storage and uses them from now on.
+    pub fn save_bindings(world: &World, bindings: InputBindings) {
+        let mut storage = world.resource_mut::<Storage>();
+        storage.insert(bindings.clone());
+        storage.save();
+        world.resource_mut::<Self>().bindings = bindings;
+    }
+    /// Saves the stick settings to the storage and uses them from now on.
+    pub fn save_sticks(world: &World, sticks: StickSettingsMap) {
+        let mut storage = world.resource_mut::<Storage>();
+        storage.insert(sticks.clone());
+        storage.save();
+        world.resource_mut::<Self>().sticks = sticks;
+    }
+}
+
 /// Keeps track keyboard key state as opposed keyboard events. This is used
 /// primarily turn actual key state into axis or character movement values.
 #[derive(HasSchema, Clone, Default)]
@@ -0,0 +1,273 @@
+use super::*;
+
+/// An action that can be bound to keyboard keys and gamepad buttons.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum BindAction {
+    #[default]
+    Up,
+    Down,
+    Left,
+    Right,
+    Shoot,
+    Pass,
+    Tackle,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    MenuSelect,
+    MenuBack,
+    Pause,
+    Start,
+}
+impl BindAction {
+    pub const ALL: [Self; 15] = [
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Shoot,
+        Self::Pass,
+        Self::Tackle,
+        Self::MenuUp,
+        Self::MenuDown,
+        Self::MenuLeft,
+        Self::MenuRight,
+        Self::MenuSelect,
+        Self::MenuBack,
+        Self::Pause,
+        Self::Start,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Up => "Up",
+            Self::Down => "Down",
+            Self::Left => "Left",
+            Self::Right => "Right",
+            Self::Shoot => "Shoot",
+            Self::Pass => "Pass",
+            Self::Tackle => "Tackle",
+            Self::MenuUp => "Menu Up",
+            Self::MenuDown => "Menu Down",
+            Self::MenuLeft => "Menu Left",
+            Self::MenuRight => "Menu Right",
+            Self::MenuSelect => "Menu Select",
+            Self::MenuBack => "Menu Back",
+            Self::Pause => "Pause",
+            Self::Start => "Start",
+        }
+    }
+    /// Gamepads always move with a stick, so movement can't be bound to buttons.
+    pub fn gamepad_bindable(self) -> bool {
+        !matches!(self, Self::Up | Self::Down | Self::Left | Self::Right)
+    }
+}
+
+#[derive(HasSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[schema(no_default)]
+#[repr(C)]
+pub struct KeyBinding {
+    pub key: KeyCode,
+    pub action: BindAction,
+}
+
+#[derive(HasSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[schema(no_default)]
+#[repr(C)]
+pub struct ButtonBinding {
+    pub button: GamepadButton,
+    pub action: BindAction,
+}
+
+/// The keys bound to each [`BindAction`] on one keyboard source.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct KeyBindings {
+    pub keys: SVec<KeyBinding>,
+}
+impl FromIterator<(KeyCode, BindAction)> for KeyBindings {
+    fn from_iter<T: IntoIterator<Item = (KeyCode, BindAction)>>(iter: T) -> Self {
+        Self {
+            keys: iter
+                .into_iter()
+                .map(|(key, action)| KeyBinding { key, action })
+                .collect(),
+        }
+    }
+}
+impl KeyBindings {
+    pub fn key_actions(&self, key: KeyCode) -> impl Iterator<Item = BindAction> + '_ {
+        self.keys
+            .iter()
+            .filter(move |binding| binding.key == key)
+            .map(|binding| binding.action)
+    }
+    pub fn action_keys(&self, action: BindAction) -> impl Iterator<Item = KeyCode> + '_ {
+        self.keys
+            .iter()
+            .filter(move |binding| binding.action == action)
+            .map(|binding| binding.key)
+    }
+    /// Whether or not any of the keys bound to the action are held down.
+    pub fn action_pressed(&self, action: BindAction, keyboard_state: &KeyboardState) -> bool {
+        self.action_keys(action)
+            .any(|key| keyboard_state.is_pressed(&key))
+    }
+    /// Replaces the keys bound to the action with `key`.
+    pub fn bind(&mut self, action: BindAction, key: KeyCode) {
+        self.keys = self
+            .keys
+            .iter()
+            .copied()
+            .filter(|binding| binding.action != action)
+            .collect();
+        self.keys.push(KeyBinding { key, action });
+    }
+}
+
+/// The keys and buttons bound to each [`BindAction`], saved in [`Storage`].
+///
+/// [`SingleSource::KeyboardMouse`] and [`SingleSource::Keyboard2`] each have
+/// their own keys. The same buttons are used by every gamepad. When two
+/// players share a gamepad, the second player uses the mirror of the
+/// shoulder buttons, see [`mirror_button`].
+#[derive(HasSchema, Clone, Debug)]
+#[repr(C)]
+pub struct InputBindings {
+    pub keyboard: KeyBindings,
+    pub keyboard2: KeyBindings,
+    pub buttons: SVec<ButtonBinding>,
+    /// Whether or not the keyboard and mouse player aims shots at the cursor.
+    pub mouse_aim: bool,
+}
+impl Default for InputBindings {
+    fn default() -> Self {
+        use BindAction::*;
+
+        let keyboard = [
+            (KeyCode::W, Up),
+            (KeyCode::S, Down),
+            (KeyCode::A, Left),
+            (KeyCode::D, Right),
+            (KeyCode::J, Shoot),
+            (KeyCode::K, Pass),
+            (KeyCode::L, Tackle),
+            (KeyCode::W, MenuUp),
+            (KeyCode::S, MenuDown),
+            (KeyCode::A, MenuLeft),
+            (KeyCode::D, MenuRight),
+            (KeyCode::Space, MenuSelect),
+            (KeyCode::Escape, MenuBack),
+            (KeyCode::Escape, Pause),
+            (KeyCode::Return, Start),
+        ];
+        // The right hand side of the keyboard, with the numpad as an alternative.
+        let keyboard2 = [
+            (KeyCode::Up, Up),
+            (KeyCode::Down, Down),
+            (KeyCode::Left, Left),
+            (KeyCode::Right, Right),
+            (KeyCode::Period, Shoot),
+            (KeyCode::Numpad1, Shoot),
+            (KeyCode::Slash, Pass),
+            (KeyCode::Numpad2, Pass),
+            (KeyCode::RShift, Tackle),
+            (KeyCode::Numpad3, Tackle),
+            (KeyCode::Up, MenuUp),
+            (KeyCode::Down, MenuDown),
+            (KeyCode::Left, MenuLeft),
+            (KeyCode::Right, MenuRight),
+            (KeyCode::Period, MenuSelect),
+            (KeyCode::Numpad1, MenuSelect),
+            (KeyCode::RShift, MenuBack),
+            (KeyCode::Numpad3, MenuBack),
+            (KeyCode::Back, Pause),
+            (KeyCode::RControl, Start),
+            (KeyCode::NumpadEnter, Start),
+        ];
+        let buttons = [
+            (GamepadButton::South, Shoot),
+            (GamepadButton::RightTrigger2, Shoot),
+            (GamepadButton::LeftTrigger2, Shoot),
+            (GamepadButton::East, Pass),
+            (GamepadButton::LeftTrigger, Pass),
+            (GamepadButton::West, Tackle),
+            (GamepadButton::LeftTrigger, Tackle),
+            (GamepadButton::DPadUp, MenuUp),
+            (GamepadButton::DPadDown, MenuDown),
+            (GamepadButton::DPadLeft, MenuLeft),
+            (GamepadButton::DPadRight, MenuRight),
+            (GamepadButton::South, MenuSelect),
+            (GamepadButton::West, MenuBack),
+            (GamepadButton::Start, Pause),
+            (GamepadButton::Start, Start),
+        ];
+
+        Self {
+            keyboard: keyboard.into_iter().collect(),
+            keyboard2: keyboard2.into_iter().collect(),
+            buttons: buttons
+                .into_iter()
+                .map(|(button, action)| ButtonBinding { button, action })
+                .collect(),
+            mouse_aim: false,
+        }
+    }
+}
+impl InputBindings {
+    /// The bindings saved in the storage, or the defaults if nothing was saved yet.
+    pub fn load(storage: &Storage) -> Self {
+        storage.get::<Self>().cloned().unwrap_or_default()
+    }
+    /// The keys of a keyboard source.
+    pub fn keys(&self, source: SingleSource) -> Option<&KeyBindings> {
+        match source {
+            SingleSource::KeyboardMouse => Some(&self.keyboard),
+            SingleSource::Keyboard2 => Some(&self.keyboard2),
+            SingleSource::CPU(..) | SingleSource::Gamepad(..) => None,
+        }
+    }
+    pub fn keys_mut(&mut self, source: SingleSource) -> Option<&mut KeyBindings> {
+        match source {
+            SingleSource::KeyboardMouse => Some(&mut self.keyboard),
+            SingleSource::Keyboard2 => Some(&mut self.keyboard2),
+            SingleSource::CPU(..) | SingleSource::Gamepad(..) => None,
+        }
+    }
+    pub fn button_actions(&self, button: GamepadButton) -> impl Iterator<Item = BindAction> + '_ {
+        self.buttons
+            .iter()
+            .filter(move |binding| binding.button == button)
+            .map(|binding| binding.action)
+    }
+    pub fn action_buttons(&self, action: BindAction) -> impl Iterator<Item = GamepadButton> + '_ {
+        self.buttons
+            .iter()
+            .filter(move |binding| binding.action == action)
+            .map(|binding| binding.button)
+    }
+    /// Replaces the buttons bound to the action with `button`.
+    pub fn bind_button(&mut self, action: BindAction, button: GamepadButton) {
+        self.buttons = self
+            .buttons
+            .iter()
+            .copied()
+            .filter(|binding| binding.action != action)
+            .collect();
+        self.buttons.push(ButtonBinding { button, action });
+    }
+}
+
+/// Swaps the left and right shoulder buttons.
+///
+/// This is used for the second player on a shared gamepad.
+pub fn mirror_button(button: GamepadButton) -> GamepadButton {
+    match button {
+        GamepadButton::LeftTrigger => GamepadButton::RightTrigger,
+        GamepadButton::RightTrigger => GamepadButton::LeftTrigger,
+        GamepadButton::LeftTrigger2 => GamepadButton::RightTrigger2,
+        GamepadButton::RightTrigger2 => GamepadButton::LeftTrigger2,
+        button => button,
+    }
+}
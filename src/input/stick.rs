@@ -0,0 +1,184 @@
+use super::*;
+
+/// How far a processed stick has to move to press its equivalent menu 'button'.
+pub const MENU_STROKE: f32 = 0.5;
+
+/// Whether the dead zone is a circle around the center of the stick or a
+/// band along each axis.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DeadZoneShape {
+    #[default]
+    Radial,
+    Axial,
+}
+impl DeadZoneShape {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Radial => "Radial",
+            Self::Axial => "Axial",
+        }
+    }
+    pub fn cycle(&mut self) {
+        *self = match self {
+            Self::Radial => Self::Axial,
+            Self::Axial => Self::Radial,
+        }
+    }
+}
+
+/// How the tilt of the stick past the dead zone turns into movement.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    /// Slower near the center for finer control at low speeds.
+    Quadratic,
+    Cubic,
+}
+impl ResponseCurve {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::Quadratic => "Quadratic",
+            Self::Cubic => "Cubic",
+        }
+    }
+    pub fn cycle(&mut self) {
+        *self = match self {
+            Self::Linear => Self::Quadratic,
+            Self::Quadratic => Self::Cubic,
+            Self::Cubic => Self::Linear,
+        }
+    }
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Linear => value,
+            Self::Quadratic => value * value,
+            Self::Cubic => value * value * value,
+        }
+    }
+}
+
+/// The dead zones and response curve of a stick.
+#[derive(HasSchema, Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct StickSettings {
+    /// Tilts below this are ignored, which keeps drifting sticks still.
+    pub inner_dead_zone: f32,
+    /// Tilts above this are full tilt, for sticks that never quite reach the edge.
+    pub outer_dead_zone: f32,
+    pub shape: DeadZoneShape,
+    pub curve: ResponseCurve,
+}
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            inner_dead_zone: 0.2,
+            outer_dead_zone: 0.95,
+            shape: DeadZoneShape::Radial,
+            curve: ResponseCurve::Linear,
+        }
+    }
+}
+impl StickSettings {
+    /// The amount the dead zones change by in the settings.
+    pub const STEP: f32 = 0.05;
+    /// The smallest gap between the inner and outer dead zones.
+    pub const MIN_RANGE: f32 = 0.1;
+
+    /// Turns the raw position of the stick into a movement, which is zero
+    /// inside the inner dead zone and at most `1.0` long.
+    pub fn apply(&self, raw: Vec2) -> Vec2 {
+        let rescale = |value: f32| {
+            ((value - self.inner_dead_zone) / (self.outer_dead_zone - self.inner_dead_zone))
+                .clamp(0.0, 1.0)
+        };
+        let stick = match self.shape {
+            DeadZoneShape::Radial => raw.normalize_or_zero() * rescale(raw.length()),
+            DeadZoneShape::Axial => {
+                let stick = Vec2::new(
+                    rescale(raw.x.abs()).copysign(raw.x),
+                    rescale(raw.y.abs()).copysign(raw.y),
+                );
+                stick.clamp_length_max(1.0)
+            }
+        };
+        stick.normalize_or_zero() * self.curve.apply(stick.length())
+    }
+    pub fn set_inner_dead_zone(&mut self, value: f32) {
+        self.inner_dead_zone = value.clamp(0.0, self.outer_dead_zone - Self::MIN_RANGE);
+    }
+    pub fn set_outer_dead_zone(&mut self, value: f32) {
+        self.outer_dead_zone = value.clamp(self.inner_dead_zone + Self::MIN_RANGE, 1.0);
+    }
+}
+
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct GamepadStickSettings {
+    pub gamepad: u32,
+    pub settings: StickSettings,
+}
+
+/// The [`StickSettings`] of each gamepad, saved in [`Storage`].
+///
+/// Gamepads without their own settings use the `default` settings.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct StickSettingsMap {
+    pub default: StickSettings,
+    pub gamepads: SVec<GamepadStickSettings>,
+}
+impl StickSettingsMap {
+    /// The settings saved in the storage, or the defaults if nothing was saved yet.
+    pub fn load(storage: &Storage) -> Self {
+        storage.get::<Self>().cloned().unwrap_or_default()
+    }
+    /// The settings of the gamepad, `None` being the default settings.
+    pub fn get(&self, gamepad: Option<u32>) -> StickSettings {
+        gamepad
+            .and_then(|gamepad| {
+                self.gamepads
+                    .iter()
+                    .find(|entry| entry.gamepad == gamepad)
+                    .map(|entry| entry.settings)
+            })
+            .unwrap_or(self.default)
+    }
+    /// The settings of the gamepad, giving it its own copy of the defaults if
+    /// it doesn't have any yet.
+    pub fn get_mut(&mut self, gamepad: Option<u32>) -> &mut StickSettings {
+        let Some(gamepad) = gamepad else {
+            return &mut self.default;
+        };
+        if !self.gamepads.iter().any(|entry| entry.gamepad == gamepad) {
+            self.gamepads.push(GamepadStickSettings {
+                gamepad,
+                settings: self.default,
+            });
+        }
+        &mut self
+            .gamepads
+            .iter_mut()
+            .find(|entry| entry.gamepad == gamepad)
+            .unwrap()
+            .settings
+    }
+    /// Removes the gamepad's own settings so that it uses the defaults again,
+    /// or resets the defaults.
+    pub fn reset(&mut self, gamepad: Option<u32>) {
+        match gamepad {
+            Some(gamepad) => {
+                self.gamepads = self
+                    .gamepads
+                    .iter()
+                    .copied()
+                    .filter(|entry| entry.gamepad != gamepad)
+                    .collect();
+            }
+            None => self.default = default(),
+        }
+    }
+}
@@ -32,5 +32,11 @@ pub use schema::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod matchmaking;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod env;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tuner;
+
 #[cfg(not(target_arch = "wasm32"))]
 pub use matchmaking::*;
@@ -1,6 +1,86 @@
 use crate::*;
 use bones::*;
 use bones_framework::networking::*;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+pub mod attempt;
+pub use attempt::*;
+
+pub mod direct;
+pub use direct::*;
+
+pub mod lan_match;
+pub use lan_match::*;
+
+pub mod lobby;
+pub use lobby::*;
+
+pub mod online_match;
+pub use online_match::*;
+
+pub mod peers;
+pub use peers::*;
+
+pub mod round_trip;
+pub use round_trip::*;
+
+pub mod spectate;
+pub use spectate::*;
+
+/// Sends the message as a line of json, see [`read_message`].
+fn send_message<T: serde::Serialize>(stream: &mut TcpStream, message: &T) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    stream.write_all(&line)
+}
+
+/// Reads the next line of json from a nonblocking stream, which is `None`
+/// until all of it has arrived.
+fn read_message<T: serde::de::DeserializeOwned>(
+    reader: &mut BufReader<TcpStream>,
+    pending: &mut String,
+) -> std::io::Result<Option<T>> {
+    loop {
+        match reader.read_line(pending) {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(_) if !pending.ends_with('\n') => {}
+            Ok(_) => {
+                let message = serde_json::from_str(pending.trim());
+                pending.clear();
+                return Ok(Some(message?));
+            }
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Ok(None);
+            }
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// A connection that is made on another thread, so that an address that
+/// doesn't answer doesn't hold up the game until it times out.
+struct Connecting(mpsc::Receiver<std::io::Result<TcpStream>>);
+impl Connecting {
+    fn new(address: SocketAddr, timeout: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(TcpStream::connect_timeout(&address, timeout));
+        });
+        Self(receiver)
+    }
+    /// The blocking stream once it is connected.
+    fn poll(&self) -> std::io::Result<Option<TcpStream>> {
+        match self.0.try_recv() {
+            Ok(stream) => stream.map(Some),
+            Err(mpsc::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::TryRecvError::Disconnected) => Err(ErrorKind::BrokenPipe.into()),
+        }
+    }
+}
 
 impl SessionPlugin for Matchmaker {
     fn install(self, session: &mut SessionBuilder) {
@@ -22,8 +102,6 @@ pub struct Matchmaker {
     // Host
     pub host_name: String,
     player_count: u32,
-    server: Option<lan::ServerInfo>,
-    joined_players: usize,
 
     // Search
     pub search_enabled: bool,
@@ -31,23 +109,52 @@ pub struct Matchmaker {
     lan_servers: Vec<lan::ServerInfo>,
     lan_discovery: Option<lan::ServiceDiscoveryReceiver>,
 
-    // Join
-    joining: bool,
-    socket: Option<NetworkMatchSocket>,
+    // Host & Join
+    lan: LanMatchmaking<NetworkMatchSocket>,
+    direct_host: Option<Arc<Mutex<DirectHost>>>,
+    direct_join: Option<Arc<Mutex<DirectJoin>>>,
+    /// Whether the peers of the LAN match found it through the discovery,
+    /// see [`Matchmaker::is_lan_discovered`].
+    lan_discovered: bool,
+
+    // Online
+    online: OnlineMatchmaking<NetworkMatchSocket>,
+
+    // Lobby
+    lobby: Option<Lobby>,
+
+    // Spectate
+    spectator_server: Option<Arc<Mutex<SpectatorServer>>>,
+    spectator_discovery: Option<Arc<SpectatorDiscovery>>,
+    watchable: Vec<WatchableMatch>,
+    watching: Option<SpectatorStream>,
 }
 
 impl Matchmaker {
     /// This is whether or not the matchmaker is waiting to get a network socket for a match full of players
     /// either by hosting or by searching then joining.
     pub fn is_waiting(&self) -> bool {
-        self.joining
+        self.is_hosting() || self.is_joining()
     }
     /// Whether or not the matchmaker is waiting to get a network socket by way of hosting.
     pub fn is_hosting(&self) -> bool {
-        self.server.is_some() && self.is_waiting()
+        self.lan.is_hosting()
     }
     pub fn is_joining(&self) -> bool {
-        self.server.is_none() && self.is_waiting()
+        self.lan.is_joining() || self.direct_join.is_some()
+    }
+    /// Whether the players of the LAN match found it through the discovery
+    /// rather than by its address, which is how they find each other again
+    /// after one drops.
+    pub fn is_lan_discovered(&self) -> bool {
+        self.lan_discovered && self.lan.socket().is_some()
+    }
+    pub fn lan_status(&self) -> &LanStatus {
+        self.lan.status()
+    }
+    /// Takes the error that hosting or joining failed with, see [`LanError`].
+    pub fn take_lan_error(&mut self) -> Option<LanError> {
+        self.lan.take_error()
     }
     pub fn service_name(&self) -> &str {
         &self.service_name
@@ -55,22 +162,39 @@ impl Matchmaker {
     pub fn service_type(&self) -> String {
         format!("_{}._udp.local.", self.service_name)
     }
+    /// The service type that matches are streamed to spectators under.
+    pub fn spectator_service_type(&self) -> String {
+        format!("_{}._tcp.local.", self.service_name)
+    }
     pub fn lan_servers(&self) -> &Vec<lan::ServerInfo> {
         &self.lan_servers
     }
     pub fn joined_players(&self) -> Option<usize> {
-        self.is_hosting().then_some(self.joined_players)
+        self.lan.joined_players()
+    }
+    /// How many machines a hosted match is for.
+    pub fn player_count(&self) -> u32 {
+        self.player_count
+    }
+    /// Changes how many machines a hosted match is for, which can't be done while hosting.
+    pub fn set_player_count(&mut self, player_count: u32) {
+        if !self.is_hosting() {
+            self.player_count = player_count.clamp(2, MAX_PEERS as u32);
+        }
     }
     /// This is the network socket for a match full of players
     /// either by hosting or by searching then joining.
     pub fn network_match_socket(&self) -> Option<NetworkMatchSocket> {
-        self.socket.clone()
+        self.lan
+            .socket()
+            .cloned()
+            .or_else(|| self.online.socket().cloned())
     }
     /// If the matchmaker doesn't have a socket and isn't waiting for a socket
     /// and the search is enabled this will return true and the matchmaker
     /// will update its servers on its refresh timer.
     pub fn is_searching(&self) -> bool {
-        !self.is_waiting() && self.socket.is_none() && self.search_enabled
+        !self.is_waiting() && self.lan.socket().is_none() && self.search_enabled
     }
     pub fn disable_search(&mut self) {
         self.search_enabled = false;
@@ -86,32 +210,59 @@ impl Matchmaker {
     }
     pub fn lan_host(&mut self) {
         let service_type = self.service_type();
-        let (is_recreated, server) = RUNTIME.block_on(async {
-            lan::prepare_to_host(&mut self.server, &service_type, &self.host_name).await
-        });
-        tracing::debug!(?is_recreated, "preparing to host");
-
-        lan::start_server(server.clone(), self.player_count);
-
-        self.joining = true;
-        self.socket = lan::wait_players(&mut self.joined_players, server);
+        self.lan
+            .host(&service_type, &self.host_name, self.player_count);
+        self.direct_join = None;
+        self.lan_discovered = true;
+        self.direct_host = match DirectHost::new() {
+            Ok(host) => Some(Arc::new(Mutex::new(host))),
+            Err(err) => {
+                tracing::warn!(%err, "couldn't let players join by address");
+                None
+            }
+        };
     }
     pub fn lan_join(&mut self, server: &lan::ServerInfo) {
         self.lan_cancel();
-        lan::join_server(server).expect("failed to join lan server");
-        self.joining = true;
-        self.socket = lan::wait_game_start();
+        self.lan.join(server);
+        self.lan_discovered = true;
+    }
+    /// Joins a LAN server by the address and code its host shows, for
+    /// networks where it can't be discovered.
+    pub fn lan_join_direct(&mut self, address: DirectAddress) -> Result<(), String> {
+        let join = DirectJoin::connect(address.socket_addr()?, address.code());
+        self.lan_cancel();
+        self.direct_join = Some(Arc::new(Mutex::new(join)));
+        Ok(())
+    }
+    /// The address and code that the hosted server can be joined with directly.
+    pub fn host_address(&self) -> Option<DirectAddress> {
+        self.is_hosting()
+            .then(|| self.direct_host.as_ref()?.lock().unwrap().address())
+            .flatten()
     }
     pub fn lan_cancel(&mut self) {
-        if let Some(server) = self.server.take() {
-            lan::stop_server(&server);
-        } else {
-            lan::leave_server();
-        }
-        self.joining = false;
-        self.socket = None;
+        self.lan.cancel();
+        self.direct_host = None;
+        self.direct_join = None;
+        self.lobby = None;
         self.lan_discovery = None;
         self.lan_servers = Vec::new();
+        self.spectator_discovery = None;
+        self.watchable = Vec::new();
+    }
+    /// The discovered LAN server that was hosted under the name.
+    pub fn lan_server_named(&self, name: &str) -> Option<lan::ServerInfo> {
+        self.lan_servers
+            .iter()
+            .find(|server| {
+                server
+                    .service
+                    .get_hostname()
+                    .split_once('.')
+                    .is_some_and(|(host_name, _)| host_name == name)
+            })
+            .cloned()
     }
     pub fn lan_search(&mut self) {
         let service_type = self.service_type();
@@ -121,26 +272,146 @@ impl Matchmaker {
             &mut self.lan_discovery,
             &self.refresh,
         );
+        if self.spectator_discovery.is_none() {
+            match SpectatorDiscovery::new(&self.spectator_service_type()) {
+                Ok(discovery) => self.spectator_discovery = Some(Arc::new(discovery)),
+                Err(err) => tracing::warn!(%err, "couldn't look for matches to watch"),
+            }
+        }
+    }
+    /// Streams the hosted match to the spectators on the local network,
+    /// replacing the stream of the match before it.
+    pub fn host_spectators(&mut self, feed: SpectatorFeed, start: SpectatorStart) {
+        let service_type = self.spectator_service_type();
+        let server = SpectatorServer::new(feed, start).and_then(|mut server| {
+            server.advertise(&service_type, &self.host_name)?;
+            Ok(server)
+        });
+        self.spectator_server = match server {
+            Ok(server) => Some(Arc::new(Mutex::new(server))),
+            Err(err) => {
+                tracing::warn!(%err, "couldn't stream the match to spectators");
+                None
+            }
+        };
+    }
+    pub fn stop_spectators(&mut self) {
+        self.spectator_server = None;
+    }
+    /// The matches in progress on the local network that can be watched.
+    pub fn watchable_matches(&self) -> &[WatchableMatch] {
+        &self.watchable
+    }
+    /// Connects to the host of the match to watch it.
+    pub fn watch(&mut self, index: usize) -> Result<(), String> {
+        let watchable = self
+            .watchable
+            .get(index)
+            .ok_or_else(|| format!("there is no match {index} to watch"))?;
+        self.watching = Some(SpectatorStream::connect(watchable.address));
+        Ok(())
+    }
+    /// The stream of the match being watched.
+    pub fn spectator_stream(&self) -> Option<&SpectatorStream> {
+        self.watching.as_ref()
+    }
+    pub fn stop_watching(&mut self) {
+        self.watching = None;
+    }
+    pub fn online_status(&self) -> &OnlineStatus {
+        self.online.status()
+    }
+    /// Who the running online search is matched with.
+    pub fn online_searching(&self) -> Option<OnlineSearch> {
+        self.online.searching()
+    }
+    /// Searches for an online match on the matchmaking server, which is a node id.
+    pub fn online_search(&mut self, server: &str, search: OnlineSearch) {
+        self.lan_cancel();
+        self.online.search(server, search);
+    }
+    pub fn online_cancel(&mut self) {
+        self.online.cancel();
+        self.lobby = None;
+    }
+    /// Opens the lobby once there is a network socket, see [`Lobby`].
+    pub fn open_lobby(&mut self, local_players: u8) -> Option<&mut Lobby> {
+        let socket = self.network_match_socket()?;
+        let name = self.host_name.clone();
+        Some(self.lobby.get_or_insert_with(|| {
+            Lobby::new(
+                socket.player_idx(),
+                socket.player_count(),
+                name,
+                local_players,
+            )
+        }))
+    }
+    pub fn lobby(&self) -> Option<&Lobby> {
+        self.lobby.as_ref()
+    }
+    pub fn lobby_mut(&mut self) -> Option<&mut Lobby> {
+        self.lobby.as_mut()
+    }
+    /// Closes the lobby once the match starts, after which the peer
+    /// messages are no longer handled by it.
+    pub fn close_lobby(&mut self) {
+        self.lobby = None;
     }
     pub fn update(&mut self, delta: std::time::Duration) {
         self.refresh.tick(delta);
+        self.online.update(delta);
+        self.lan.update(delta);
 
-        if !self.is_waiting() {
-            if self.is_searching() && self.refresh.finished() {
-                tracing::debug!("matchmaker refresh...");
-                self.lan_search();
-                self.refresh.reset();
+        if let Some(host) = &self.direct_host {
+            match self.lan.hosted_server() {
+                Some(server) => {
+                    // The joiner may have the server from the discovery
+                    // too, but it can't be told apart.
+                    if host.lock().unwrap().update(&server) {
+                        self.lan_discovered = false;
+                    }
+                }
+                // The match started or hosting stopped.
+                None => self.direct_host = None,
             }
-        } else if self.socket.is_none() {
-            if let Some(server) = &self.server {
-                self.socket = lan::wait_players(&mut self.joined_players, server);
-            } else {
-                self.socket = lan::wait_game_start();
-            };
-            if self.socket.is_some() {
-                self.joining = false;
+        }
+        if let Some(join) = self.direct_join.clone() {
+            let result = join.lock().unwrap().poll(&self.service_type(), delta);
+            match result {
+                Ok(None) => {}
+                Ok(Some(server)) => {
+                    self.direct_join = None;
+                    self.lan.join(&server);
+                    self.lan_discovered = false;
+                }
+                Err(error) => {
+                    self.direct_join = None;
+                    self.lan.fail(error);
+                }
+            }
+        }
+
+        if let Some(socket) = self.network_match_socket() {
+            if let Some(lobby) = &mut self.lobby {
+                lobby.update(&socket);
             }
         }
+        if let Some(server) = &self.spectator_server {
+            server.lock().unwrap().update();
+        }
+        if let Some(discovery) = &self.spectator_discovery {
+            discovery.update(&mut self.watchable);
+        }
+        if let Some(stream) = &self.watching {
+            stream.lock().unwrap().receive();
+        }
+
+        if self.is_searching() && self.refresh.finished() {
+            tracing::debug!("matchmaker refresh...");
+            self.lan_search();
+            self.refresh.reset();
+        }
     }
 }
 
@@ -150,6 +421,8 @@ pub struct MatchmakerPlugin {
     pub player_count: u32,
     pub refresh: f32,
     pub start_searching: bool,
+    pub online_service: Arc<dyn OnlineService<NetworkMatchSocket>>,
+    pub lan_service: Arc<dyn LanService<NetworkMatchSocket>>,
 }
 impl MatchmakerPlugin {
     pub fn new(service_name: &str) -> Self {
@@ -159,6 +432,8 @@ impl MatchmakerPlugin {
             player_count: 2,
             refresh: 2.0,
             start_searching: false,
+            online_service: Arc::new(BonesOnline),
+            lan_service: Arc::new(BonesLan::default()),
         }
     }
     pub fn service_name(mut self, service_name: String) -> Self {
@@ -181,6 +456,14 @@ impl MatchmakerPlugin {
         self.start_searching = true;
         self
     }
+    pub fn online_service(mut self, service: Arc<dyn OnlineService<NetworkMatchSocket>>) -> Self {
+        self.online_service = service;
+        self
+    }
+    pub fn lan_service(mut self, service: Arc<dyn LanService<NetworkMatchSocket>>) -> Self {
+        self.lan_service = service;
+        self
+    }
 }
 impl From<MatchmakerPlugin> for Matchmaker {
     fn from(plugin: MatchmakerPlugin) -> Self {
@@ -190,12 +473,18 @@ impl From<MatchmakerPlugin> for Matchmaker {
             service_name: plugin.service_name,
             host_name: plugin.host_name,
             player_count: plugin.player_count,
-            server: None,
-            joined_players: 0,
             lan_servers: Vec::new(),
             lan_discovery: None,
-            socket: None,
-            joining: false,
+            lan: LanMatchmaking::new(plugin.lan_service),
+            direct_host: None,
+            direct_join: None,
+            lan_discovered: false,
+            online: OnlineMatchmaking::new(plugin.online_service, plugin.player_count),
+            lobby: None,
+            spectator_server: None,
+            spectator_discovery: None,
+            watchable: Vec::new(),
+            watching: None,
         }
     }
 }
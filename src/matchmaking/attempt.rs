@@ -0,0 +1,189 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// What a matchmaking service tells us about the current attempt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchmakingEvent<S, E> {
+    /// The players waiting for the match, including us.
+    PlayerCount(usize),
+    /// The match is full and starting with this socket.
+    Ready(S),
+    Error(E),
+}
+
+/// Something a [`Matchmaking`] tries to get the socket of a full match with,
+/// like hosting a LAN server or searching online.
+pub trait MatchmakingAttempt: Clone {
+    type Error: Clone + Debug;
+    /// How many times the attempt is tried again once it timed out.
+    const RETRIES: u32 = 0;
+
+    /// How long the attempt waits with the players before it is tried
+    /// again, or `None` to wait for as long as it takes.
+    fn timeout(&self, _players: usize) -> Option<Duration> {
+        None
+    }
+    /// The error of an attempt that timed out after every retry.
+    fn timed_out() -> Self::Error;
+}
+
+/// Something that carries out the attempts of a [`Matchmaking`], which is
+/// the bones matchmaker in the game and a stand-in in the tests.
+pub trait MatchmakingService<A: MatchmakingAttempt, S> {
+    fn start(&self, attempt: &A) -> Result<(), A::Error>;
+    fn cancel(&self, attempt: &A);
+    /// The next event about the attempt if there is one.
+    fn poll(&self) -> Option<MatchmakingEvent<S, A::Error>>;
+}
+
+/// The state of a [`Matchmaking`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MatchmakingStatus<E> {
+    #[default]
+    Idle,
+    /// Waiting for the match to fill up.
+    Waiting {
+        /// The players waiting for the match, which is zero until the
+        /// matchmaker has told us.
+        players: usize,
+    },
+    /// The match is ready and the socket can be taken.
+    Ready,
+    Failed(E),
+}
+
+/// Gets the socket of a full match through a [`MatchmakingService`], trying
+/// again when the attempt takes too long.
+///
+/// It holds the state the LAN and online matchmaking share, while they hold
+/// their services, which are passed in.
+#[derive(Clone)]
+pub struct Matchmaking<A: MatchmakingAttempt, S> {
+    attempt: Option<A>,
+    status: MatchmakingStatus<A::Error>,
+    socket: Option<S>,
+    /// How long the current try has been waiting.
+    waited: Duration,
+    retries: u32,
+}
+impl<A: MatchmakingAttempt, S> Default for Matchmaking<A, S> {
+    fn default() -> Self {
+        Self {
+            attempt: None,
+            status: MatchmakingStatus::Idle,
+            socket: None,
+            waited: Duration::ZERO,
+            retries: 0,
+        }
+    }
+}
+impl<A: MatchmakingAttempt, S> Matchmaking<A, S> {
+    pub fn status(&self) -> &MatchmakingStatus<A::Error> {
+        &self.status
+    }
+    /// The attempt that is waiting for the match to fill up.
+    pub fn waiting(&self) -> Option<&A> {
+        matches!(self.status, MatchmakingStatus::Waiting { .. })
+            .then_some(self.attempt.as_ref())
+            .flatten()
+    }
+    /// The network socket of the match once it is full of players.
+    pub fn socket(&self) -> Option<&S> {
+        self.socket.as_ref()
+    }
+    /// Starts the attempt, cancelling anything that was already going on.
+    pub fn start<Svc: MatchmakingService<A, S> + ?Sized>(&mut self, service: &Svc, attempt: A) {
+        self.cancel(service);
+        self.try_attempt(service, attempt);
+    }
+    fn try_attempt<Svc: MatchmakingService<A, S> + ?Sized>(&mut self, service: &Svc, attempt: A) {
+        self.waited = Duration::ZERO;
+        self.status = match service.start(&attempt) {
+            Ok(()) => MatchmakingStatus::Waiting { players: 0 },
+            Err(error) => {
+                tracing::warn!(?error, "matchmaking failed");
+                MatchmakingStatus::Failed(error)
+            }
+        };
+        self.attempt = Some(attempt);
+    }
+    /// Stops the attempt and forgets the socket of a match.
+    pub fn cancel<Svc: MatchmakingService<A, S> + ?Sized>(&mut self, service: &Svc) {
+        if let Some(attempt) = self.attempt.take() {
+            service.cancel(&attempt);
+        }
+        // Drain the events of the old attempt so they aren't mistaken for a new one.
+        while service.poll().is_some() {}
+        self.status = MatchmakingStatus::Idle;
+        self.socket = None;
+        self.retries = 0;
+    }
+    /// Fails the attempt, stopping it.
+    pub fn fail<Svc: MatchmakingService<A, S> + ?Sized>(&mut self, service: &Svc, error: A::Error) {
+        tracing::warn!(?error, "matchmaking failed");
+        self.cancel(service);
+        self.status = MatchmakingStatus::Failed(error);
+    }
+    /// Takes the error of a failed attempt, going back to idle.
+    pub fn take_error(&mut self) -> Option<A::Error> {
+        let MatchmakingStatus::Failed(error) = &self.status else {
+            return None;
+        };
+        let error = error.clone();
+        self.status = MatchmakingStatus::Idle;
+        Some(error)
+    }
+    /// Handles the events from the service and tries again once the timeout
+    /// of the attempt has passed.
+    pub fn update<Svc: MatchmakingService<A, S> + ?Sized>(
+        &mut self,
+        service: &Svc,
+        delta: Duration,
+    ) {
+        while let Some(event) = service.poll() {
+            let MatchmakingStatus::Waiting { players } = &mut self.status else {
+                continue;
+            };
+            match event {
+                MatchmakingEvent::PlayerCount(count) => {
+                    *players = count;
+                    // Someone joined, so the match is being found.
+                    self.waited = Duration::ZERO;
+                }
+                MatchmakingEvent::Ready(socket) => {
+                    tracing::info!("match ready");
+                    self.socket = Some(socket);
+                    self.status = MatchmakingStatus::Ready;
+                }
+                MatchmakingEvent::Error(error) => {
+                    self.fail(service, error);
+                    return;
+                }
+            }
+        }
+        let Some(attempt) = self.waiting().cloned() else {
+            return;
+        };
+        let MatchmakingStatus::Waiting { players } = self.status else {
+            return;
+        };
+        let Some(timeout) = attempt.timeout(players) else {
+            return;
+        };
+        self.waited += delta;
+        if self.waited < timeout {
+            return;
+        }
+        if self.retries >= A::RETRIES {
+            self.fail(service, A::timed_out());
+            return;
+        }
+        tracing::info!(
+            retries = self.retries,
+            "matchmaking timed out, trying again"
+        );
+        service.cancel(&attempt);
+        self.retries += 1;
+        self.try_attempt(service, attempt);
+    }
+}
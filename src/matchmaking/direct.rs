@@ -0,0 +1,425 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufReader, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener, TcpStream, UdpSocket};
+use std::time::Duration;
+
+/// How long joining by address waits for the host to answer.
+pub const DIRECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An IPv4 address and port followed by the code the host shows, entered a
+/// digit at a time so that it can be typed with a gamepad.
+///
+/// Every part is zero padded, like `192.168.001.020:07777 #0042`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DirectAddress(pub [u8; DirectAddress::LEN]);
+impl Default for DirectAddress {
+    fn default() -> Self {
+        Self::new(SocketAddrV4::new(Ipv4Addr::new(192, 168, 0, 1), 0), 0)
+    }
+}
+impl From<SocketAddrV4> for DirectAddress {
+    fn from(address: SocketAddrV4) -> Self {
+        Self::new(address, 0)
+    }
+}
+impl DirectAddress {
+    /// The digits of the four octets, then the ones of the port and of the code.
+    pub const LEN: usize = 21;
+
+    pub fn new(address: SocketAddrV4, code: u16) -> Self {
+        let [a, b, c, d] = address.ip().octets();
+        let text = format!(
+            "{a:03}{b:03}{c:03}{d:03}{:05}{:04}",
+            address.port(),
+            code % 10000
+        );
+        let mut digits = [0; Self::LEN];
+        for (digit, char) in digits.iter_mut().zip(text.bytes()) {
+            *digit = char - b'0';
+        }
+        Self(digits)
+    }
+    /// Steps the digit at the index between 0 and 9.
+    pub fn cycle(&mut self, index: usize, direction: i8) {
+        if let Some(digit) = self.0.get_mut(index) {
+            *digit = (*digit as i8 + direction).rem_euclid(10) as u8;
+        }
+    }
+    fn number(&self, range: std::ops::Range<usize>) -> u32 {
+        self.0[range]
+            .iter()
+            .fold(0, |number, digit| number * 10 + *digit as u32)
+    }
+    pub fn socket_addr(&self) -> Result<SocketAddr, String> {
+        let mut octets = [0; 4];
+        for (i, octet) in octets.iter_mut().enumerate() {
+            let number = self.number(i * 3..i * 3 + 3);
+            *octet = u8::try_from(number).map_err(|_| format!("{number} isn't an octet"))?;
+        }
+        let port = self.number(12..17);
+        let port = u16::try_from(port)
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(|| format!("{port} isn't a port"))?;
+        Ok(SocketAddr::new(IpAddr::from(octets), port))
+    }
+    /// The code shown by the host, see [`DirectHost`].
+    pub fn code(&self) -> u16 {
+        self.number(17..Self::LEN) as u16
+    }
+    /// The position of the digit at the index in the displayed text.
+    pub fn text_position(index: usize) -> usize {
+        index + (index / 3).min(3) + usize::from(index >= 12) + 2 * usize::from(index >= 17)
+    }
+}
+impl std::fmt::Display for DirectAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, digit) in self.0.iter().enumerate() {
+            match i {
+                3 | 6 | 9 => write!(f, ".")?,
+                12 => write!(f, ":")?,
+                17 => write!(f, " #")?,
+                _ => {}
+            }
+            write!(f, "{digit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The server info of a LAN server joined by its address instead of being
+/// discovered, which works the same for [`lan::join_server`] as long as the
+/// properties are the ones the host advertises.
+pub fn direct_server_info(
+    service_type: &str,
+    address: SocketAddr,
+    properties: HashMap<String, String>,
+) -> Result<lan::ServerInfo, String> {
+    let name = address.ip().to_string().replace(['.', ':'], "-");
+    let service = mdns_sd::ServiceInfo::new(
+        service_type,
+        &name,
+        &format!("{name}.local."),
+        address.ip(),
+        address.port(),
+        properties,
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(lan::ServerInfo {
+        service,
+        ping: None,
+    })
+}
+
+/// What a joiner sends the [`DirectHost`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectRequest {
+    pub code: u16,
+}
+
+/// What the [`DirectHost`] answers a joiner with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DirectAnswer {
+    /// The port and the advertised properties of the hosted server, which
+    /// hold the node address that it is joined at.
+    Server {
+        port: u16,
+        properties: HashMap<String, String>,
+    },
+    WrongCode,
+}
+
+/// The address of this machine on the local network, which is the one the
+/// mDNS multicast would go out of.
+fn local_ip() -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("224.0.0.251", 5353)).ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+        _ => None,
+    }
+}
+
+struct DirectJoiner {
+    reader: BufReader<TcpStream>,
+    pending: String,
+}
+
+/// Tells the players that join by address and enter the right code how to
+/// join the hosted server, since it isn't found without the mDNS discovery.
+pub struct DirectHost {
+    listener: TcpListener,
+    code: u16,
+    joiners: Vec<DirectJoiner>,
+}
+impl DirectHost {
+    /// Listens on any port with a code seeded from the clock.
+    pub fn new() -> Result<Self, String> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Self::with_code((SplitMix64(nanos as u64).next_u64() % 10000) as u16)
+    }
+    pub fn with_code(code: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).map_err(|err| err.to_string())?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            listener,
+            code,
+            joiners: Vec::new(),
+        })
+    }
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+    pub fn port(&self) -> Option<u16> {
+        self.listener
+            .local_addr()
+            .ok()
+            .map(|address| address.port())
+    }
+    /// The address and code the host shows to the players joining it.
+    pub fn address(&self) -> Option<DirectAddress> {
+        Some(DirectAddress::new(
+            SocketAddrV4::new(local_ip()?, self.port()?),
+            self.code,
+        ))
+    }
+    /// Lets the new joiners in and answers the ones that sent their code
+    /// with the hosted server, returning whether anyone was sent it.
+    pub fn update(&mut self, server: &lan::ServerInfo) -> bool {
+        let mut answered = false;
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => match stream.set_nonblocking(true) {
+                    Ok(()) => self.joiners.push(DirectJoiner {
+                        reader: BufReader::new(stream),
+                        pending: String::new(),
+                    }),
+                    Err(err) => tracing::warn!(%err, %address, "joiner couldn't connect"),
+                },
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    tracing::warn!(%err, "couldn't accept joiner");
+                    break;
+                }
+            }
+        }
+        self.joiners.retain_mut(|joiner| {
+            let request =
+                match read_message::<DirectRequest>(&mut joiner.reader, &mut joiner.pending) {
+                    Ok(Some(request)) => request,
+                    Ok(None) => return true,
+                    Err(err) => {
+                        tracing::info!(%err, "joiner left");
+                        return false;
+                    }
+                };
+            let answer = if request.code == self.code {
+                answered = true;
+                DirectAnswer::Server {
+                    port: server.service.get_port(),
+                    properties: server
+                        .service
+                        .get_properties()
+                        .iter()
+                        .map(|property| {
+                            (property.key().to_string(), property.val_str().to_string())
+                        })
+                        .collect(),
+                }
+            } else {
+                tracing::info!(code = request.code, "joiner sent the wrong code");
+                DirectAnswer::WrongCode
+            };
+            // The joiner is done with us either way.
+            let stream = joiner.reader.get_mut();
+            if let Err(err) = stream
+                .set_nonblocking(false)
+                .and_then(|_| stream.set_write_timeout(Some(SPECTATOR_WRITE_TIMEOUT)))
+                .and_then(|_| send_message(stream, &answer))
+            {
+                tracing::warn!(%err, "couldn't answer joiner");
+            }
+            false
+        });
+        answered
+    }
+}
+
+/// Asks a [`DirectHost`] for the server it hosts.
+pub struct DirectJoin {
+    address: SocketAddr,
+    code: u16,
+    /// The connection to the host until it is made.
+    connecting: Option<Connecting>,
+    reader: Option<BufReader<TcpStream>>,
+    pending: String,
+    /// How long the host has been waited on to answer.
+    waited: Duration,
+}
+impl DirectJoin {
+    /// Starts connecting to the host at the address, which is sent the code
+    /// once it is connected, see [`DirectJoin::poll`].
+    pub fn connect(address: SocketAddr, code: u16) -> Self {
+        Self {
+            address,
+            code,
+            connecting: Some(Connecting::new(address, DIRECT_TIMEOUT)),
+            reader: None,
+            pending: String::new(),
+            waited: Duration::ZERO,
+        }
+    }
+    /// The server to join once the host has answered.
+    pub fn poll(
+        &mut self,
+        service_type: &str,
+        delta: Duration,
+    ) -> Result<Option<lan::ServerInfo>, LanError> {
+        if let Some(connecting) = &self.connecting {
+            let connected = connecting.poll().and_then(|stream| {
+                let Some(mut stream) = stream else {
+                    return Ok(None);
+                };
+                send_message(&mut stream, &DirectRequest { code: self.code })?;
+                stream.set_nonblocking(true)?;
+                Ok(Some(stream))
+            });
+            let stream = connected.map_err(|err| {
+                tracing::warn!(%err, "couldn't reach the host");
+                LanError::Failed
+            })?;
+            let Some(stream) = stream else {
+                return Ok(None);
+            };
+            self.connecting = None;
+            self.reader = Some(BufReader::new(stream));
+        }
+        let Some(reader) = &mut self.reader else {
+            return Ok(None);
+        };
+        let answer = read_message(reader, &mut self.pending).map_err(|err| {
+            tracing::warn!(%err, "the host didn't answer");
+            LanError::Failed
+        })?;
+        match answer {
+            Some(DirectAnswer::Server { port, properties }) => {
+                let address = SocketAddr::new(self.address.ip(), port);
+                direct_server_info(service_type, address, properties)
+                    .map(Some)
+                    .map_err(|err| {
+                        tracing::warn!(%err, "the host answered with an invalid server");
+                        LanError::Failed
+                    })
+            }
+            Some(DirectAnswer::WrongCode) => Err(LanError::WrongCode),
+            None => {
+                self.waited += delta;
+                if self.waited >= DIRECT_TIMEOUT {
+                    return Err(LanError::TimedOut);
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_address() {
+        let mut address = DirectAddress::default();
+        assert_eq!(address.to_string(), "192.168.000.001:00000 #0000");
+        assert!(address.socket_addr().is_err());
+
+        address.cycle(16, -1);
+        address.cycle(15, 1);
+        assert_eq!(address.to_string(), "192.168.000.001:00019 #0000");
+        assert_eq!(address.socket_addr(), Ok("192.168.0.1:19".parse().unwrap()));
+
+        address.cycle(0, 2);
+        assert_eq!(address.to_string(), "392.168.000.001:00019 #0000");
+        assert!(address.socket_addr().is_err());
+
+        let address = DirectAddress::new("10.0.0.7:65535".parse().unwrap(), 42);
+        assert_eq!(address.socket_addr(), Ok("10.0.0.7:65535".parse().unwrap()));
+        assert_eq!(address.code(), 42);
+        assert_eq!(address.to_string(), "010.000.000.007:65535 #0042");
+    }
+
+    #[test]
+    fn text_position() {
+        let text = DirectAddress::default().to_string().into_bytes();
+        for index in 0..DirectAddress::LEN {
+            assert!(text[DirectAddress::text_position(index)].is_ascii_digit());
+        }
+        assert_eq!(DirectAddress::text_position(3), 4);
+        assert_eq!(DirectAddress::text_position(12), 16);
+        assert_eq!(DirectAddress::text_position(17), 23);
+    }
+
+    fn join(
+        host: &mut DirectHost,
+        server: &lan::ServerInfo,
+        code: u16,
+    ) -> Result<lan::ServerInfo, LanError> {
+        let address = SocketAddr::from(([127, 0, 0, 1], host.port().unwrap()));
+        let mut join = DirectJoin::connect(address, code);
+        loop {
+            host.update(server);
+            if let Some(server) = join.poll(SERVICE_TYPE, Duration::from_millis(1))? {
+                return Ok(server);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    const SERVICE_TYPE: &str = "_direct._udp.local.";
+
+    #[test]
+    fn join_by_address() {
+        let properties = HashMap::from([(NODE_ADDR_PROPERTY.to_string(), "00ff".to_string())]);
+        let hosted =
+            direct_server_info(SERVICE_TYPE, "10.0.0.7:7777".parse().unwrap(), properties).unwrap();
+        let mut host = DirectHost::with_code(1234).unwrap();
+
+        let server = join(&mut host, &hosted, 1234).unwrap();
+        assert_eq!(server.service.get_port(), 7777);
+        assert_eq!(node_addr(&server), Ok("00ff"));
+        // The server is joined at the address it was asked at.
+        assert!(server
+            .service
+            .get_addresses()
+            .contains(&IpAddr::from([127, 0, 0, 1])));
+
+        assert_eq!(
+            join(&mut host, &hosted, 4321).err(),
+            Some(LanError::WrongCode)
+        );
+    }
+
+    #[test]
+    fn unreachable_host() {
+        let address = {
+            let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+            listener.local_addr().unwrap()
+        };
+        // Connecting doesn't wait for the host, which isn't there.
+        let mut join = DirectJoin::connect(address, 1234);
+        let result = loop {
+            match join.poll(SERVICE_TYPE, Duration::ZERO) {
+                Ok(None) => std::thread::sleep(Duration::from_millis(1)),
+                result => break result,
+            }
+        };
+        assert_eq!(result.err(), Some(LanError::Failed));
+    }
+}
@@ -0,0 +1,469 @@
+use super::*;
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long joining a server waits for the match to start before trying again.
+pub const JOIN_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long hosting waits for anyone to join before advertising the server again.
+pub const HOST_TIMEOUT: Duration = Duration::from_secs(120);
+/// How many times hosting or joining is tried again before it times out.
+pub const LAN_RETRIES: u32 = 2;
+/// How long a joined server can be gone from the network before the host is
+/// taken to have left or the match to have filled up without us, since the
+/// host also stops advertising it once the match starts.
+pub const HOST_LEFT_DELAY: Duration = Duration::from_secs(3);
+
+/// Why hosting or joining a match on the local network failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LanError {
+    /// The server already has all of the players of its match, which it
+    /// advertises, see [`PLAYERS_PROPERTY`].
+    ServerFull,
+    /// The host stopped hosting before the match started, which the
+    /// [`BonesLan`] and the [`Lobby`] find out.
+    HostLeft,
+    /// Nothing happened after every retry.
+    TimedOut,
+    /// The other peers play another version of the game, which the [`Lobby`]
+    /// finds out.
+    VersionMismatch,
+    /// The code entered to join by address isn't the one the host shows.
+    WrongCode,
+    /// The matchmaker couldn't host or join at all.
+    Failed,
+}
+impl std::fmt::Display for LanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ServerFull => "The Server Is Full...",
+            Self::HostLeft => "The Host Left...",
+            Self::TimedOut => "Timed Out...",
+            Self::VersionMismatch => "The Versions Don't Match...",
+            Self::WrongCode => "Wrong Code...",
+            Self::Failed => "Couldn't Connect...",
+        })
+    }
+}
+
+/// What the LAN matchmaker tells us about hosting or joining.
+pub type LanEvent<S> = MatchmakingEvent<S, LanError>;
+
+/// The state of [`LanMatchmaking`].
+pub type LanStatus = MatchmakingStatus<LanError>;
+
+/// Something that can host and join matches on the local network, see
+/// [`MatchmakingService`].
+pub trait LanService<S>: Send + Sync {
+    /// Starts hosting a match for the player count under the host name.
+    fn host(&self, service_type: &str, host_name: &str, player_count: u32) -> Result<(), String>;
+    /// Starts joining the server.
+    fn join(&self, server: &lan::ServerInfo) -> Result<(), String>;
+    /// Stops hosting or leaves the joined server.
+    fn cancel(&self);
+    /// The next event about hosting or joining if there is one.
+    fn poll(&self) -> Option<LanEvent<S>>;
+    /// The server being hosted, as it is advertised.
+    fn hosted_server(&self) -> Option<lan::ServerInfo>;
+}
+impl<S> MatchmakingService<LanAttempt, S> for dyn LanService<S> {
+    fn start(&self, attempt: &LanAttempt) -> Result<(), LanError> {
+        match attempt {
+            LanAttempt::Host {
+                service_type,
+                host_name,
+                player_count,
+            } => self.host(service_type, host_name, *player_count),
+            LanAttempt::Join(server) => self.join(server),
+        }
+        .map_err(|err| {
+            tracing::warn!(?err, "lan matchmaking failed");
+            LanError::Failed
+        })
+    }
+    fn cancel(&self, _attempt: &LanAttempt) {
+        LanService::cancel(self);
+    }
+    fn poll(&self) -> Option<LanEvent<S>> {
+        LanService::poll(self)
+    }
+}
+
+/// The property of a LAN server that it is joined at, which is the hex of
+/// its iroh node address.
+pub const NODE_ADDR_PROPERTY: &str = "node-addr";
+
+/// The node address of the server, which every server advertised by bones has.
+pub fn node_addr(server: &lan::ServerInfo) -> Result<&str, String> {
+    let node_addr = server
+        .service
+        .get_property_val_str(NODE_ADDR_PROPERTY)
+        .ok_or("the server has no node address")?;
+    if node_addr.is_empty()
+        || node_addr.len() % 2 != 0
+        || !node_addr.bytes().all(|byte| byte.is_ascii_hexdigit())
+    {
+        return Err(format!("{node_addr:?} isn't a node address"));
+    }
+    Ok(node_addr)
+}
+
+/// The property of a LAN server with the players that joined it, including
+/// the host, and the players of its match, like `1/2`.
+pub const PLAYERS_PROPERTY: &str = "players";
+
+/// The players that joined the server and the players of its match, if it
+/// advertises them, see [`PLAYERS_PROPERTY`].
+pub fn server_players(server: &lan::ServerInfo) -> Option<(usize, usize)> {
+    let (joined, max) = server
+        .service
+        .get_property_val_str(PLAYERS_PROPERTY)?
+        .split_once('/')?;
+    Some((joined.parse().ok()?, max.parse().ok()?))
+}
+
+/// Whether the server advertises that it has all of the players of its match.
+pub fn is_server_full(server: &lan::ServerInfo) -> bool {
+    server_players(server).is_some_and(|(joined, max)| joined >= max)
+}
+
+/// The server as it is advertised with the players, see [`PLAYERS_PROPERTY`].
+pub fn with_players(
+    server: &lan::ServerInfo,
+    joined: usize,
+    max: usize,
+) -> Result<lan::ServerInfo, String> {
+    let service = &server.service;
+    let mut properties = service
+        .get_properties()
+        .iter()
+        .map(|property| (property.key().to_string(), property.val_str().to_string()))
+        .collect::<HashMap<_, _>>();
+    properties.insert(PLAYERS_PROPERTY.to_string(), format!("{joined}/{max}"));
+    let name = service
+        .get_fullname()
+        .strip_suffix(service.get_type())
+        .and_then(|name| name.strip_suffix('.'))
+        .unwrap_or(service.get_fullname());
+    let addresses = service.get_addresses().iter().copied().collect::<Vec<_>>();
+    let mut advertised = mdns_sd::ServiceInfo::new(
+        service.get_type(),
+        name,
+        service.get_hostname(),
+        &addresses[..],
+        service.get_port(),
+        properties,
+    )
+    .map_err(|err| err.to_string())?;
+    if service.is_addr_auto() {
+        advertised = advertised.enable_addr_auto();
+    }
+    Ok(lan::ServerInfo {
+        service: advertised,
+        ..server.clone()
+    })
+}
+
+/// Watches the network for the joined server going away, which is how a
+/// joiner finds out that the host left or that the match filled up without
+/// it.
+struct HostWatch {
+    daemon: ServiceDaemon,
+    receiver: mdns_sd::Receiver<ServiceEvent>,
+    fullname: String,
+    /// Whether the server was full when it was last advertised.
+    full: bool,
+    /// When the server stopped being advertised.
+    removed: Option<Instant>,
+}
+impl HostWatch {
+    fn new(server: &lan::ServerInfo) -> Result<Self, String> {
+        let daemon = ServiceDaemon::new().map_err(|err| err.to_string())?;
+        let receiver = daemon
+            .browse(server.service.get_type())
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            daemon,
+            receiver,
+            fullname: server.service.get_fullname().to_string(),
+            full: is_server_full(server),
+            removed: None,
+        })
+    }
+    /// Why the match can't be joined once the server has been gone for
+    /// longer than [`HOST_LEFT_DELAY`].
+    fn error(&mut self) -> Option<LanError> {
+        while let Ok(event) = self.receiver.try_recv() {
+            match event {
+                ServiceEvent::ServiceRemoved(_, fullname) if fullname == self.fullname => {
+                    self.removed.get_or_insert_with(Instant::now);
+                }
+                ServiceEvent::ServiceResolved(info) if info.get_fullname() == self.fullname => {
+                    self.full = server_players(&lan::ServerInfo {
+                        service: info,
+                        ping: None,
+                    })
+                    .is_some_and(|(joined, max)| joined >= max);
+                    self.removed = None;
+                }
+                _ => {}
+            }
+        }
+        let removed = self.removed?;
+        (removed.elapsed() >= HOST_LEFT_DELAY).then_some(if self.full {
+            LanError::ServerFull
+        } else {
+            LanError::HostLeft
+        })
+    }
+}
+impl Drop for HostWatch {
+    fn drop(&mut self) {
+        let _ = self.daemon.shutdown();
+    }
+}
+
+#[derive(Default)]
+struct BonesLanState {
+    /// The hosted server, as it is advertised.
+    server: Option<lan::ServerInfo>,
+    player_count: u32,
+    joined_players: usize,
+    reported_players: usize,
+    joining: bool,
+    /// The watch on the joined server, unless the network couldn't be browsed.
+    host_watch: Option<HostWatch>,
+}
+
+/// The [`LanService`] of the bones LAN matchmaker.
+///
+/// The hosted server advertises its players, which is kept up to date through
+/// the bones mDNS daemon, so that a full server isn't joined.
+#[derive(Clone, Default)]
+pub struct BonesLan(Arc<Mutex<BonesLanState>>);
+impl LanService<NetworkMatchSocket> for BonesLan {
+    fn host(&self, service_type: &str, host_name: &str, player_count: u32) -> Result<(), String> {
+        let mut state = self.0.lock().unwrap();
+        let (is_recreated, server) = RUNTIME.block_on(async {
+            lan::prepare_to_host(&mut state.server, service_type, host_name).await
+        });
+        tracing::debug!(?is_recreated, "preparing to host");
+
+        let server = with_players(server, 1, player_count as usize)?;
+        lan::start_server(server.clone(), player_count);
+        state.server = Some(server);
+        state.player_count = player_count;
+        state.joined_players = 0;
+        state.reported_players = 0;
+        Ok(())
+    }
+    fn join(&self, server: &lan::ServerInfo) -> Result<(), String> {
+        // Joining panics on a server without a node address.
+        node_addr(server)?;
+        lan::join_server(server).map_err(|err| err.to_string())?;
+
+        let mut state = self.0.lock().unwrap();
+        state.joining = true;
+        state.host_watch = HostWatch::new(server)
+            .inspect_err(|err| tracing::warn!(?err, "can't watch for the host leaving"))
+            .ok();
+        Ok(())
+    }
+    fn cancel(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.joining = false;
+        state.host_watch = None;
+        if let Some(server) = state.server.take() {
+            lan::stop_server(&server);
+        } else {
+            lan::leave_server();
+        }
+    }
+    fn poll(&self) -> Option<LanEvent<NetworkMatchSocket>> {
+        let state = &mut *self.0.lock().unwrap();
+        let Some(server) = &state.server else {
+            if !state.joining {
+                return None;
+            }
+            if let Some(socket) = lan::wait_game_start() {
+                state.joining = false;
+                state.host_watch = None;
+                return Some(LanEvent::Ready(socket));
+            }
+            let error = state.host_watch.as_mut().and_then(HostWatch::error)?;
+            state.joining = false;
+            state.host_watch = None;
+            return Some(LanEvent::Error(error));
+        };
+        if let Some(socket) = lan::wait_players(&mut state.joined_players, server) {
+            return Some(LanEvent::Ready(socket));
+        }
+        if state.reported_players == state.joined_players {
+            return None;
+        }
+        state.reported_players = state.joined_players;
+        match with_players(server, state.joined_players, state.player_count as usize) {
+            Ok(server) => {
+                if let Err(err) = lan::MDNS.register(server.service.clone()) {
+                    tracing::warn!(?err, "couldn't advertise the players of the server");
+                }
+                state.server = Some(server);
+            }
+            Err(err) => tracing::warn!(?err, "couldn't advertise the players of the server"),
+        }
+        Some(LanEvent::PlayerCount(state.joined_players))
+    }
+    fn hosted_server(&self) -> Option<lan::ServerInfo> {
+        self.0.lock().unwrap().server.clone()
+    }
+}
+
+/// What is tried again when hosting or joining times out.
+#[derive(Clone)]
+pub enum LanAttempt {
+    Host {
+        service_type: String,
+        host_name: String,
+        player_count: u32,
+    },
+    Join(lan::ServerInfo),
+}
+impl MatchmakingAttempt for LanAttempt {
+    type Error = LanError;
+    const RETRIES: u32 = LAN_RETRIES;
+
+    fn timeout(&self, players: usize) -> Option<Duration> {
+        match self {
+            // Nobody would find the server again if it's advertised again
+            // while others have joined it.
+            Self::Host { .. } => (players <= 1).then_some(HOST_TIMEOUT),
+            Self::Join(..) => Some(JOIN_TIMEOUT),
+        }
+    }
+    fn timed_out() -> LanError {
+        LanError::TimedOut
+    }
+}
+
+/// Hosts and joins matches on the local network through a [`LanService`],
+/// see [`Matchmaking`].
+#[derive(Clone)]
+pub struct LanMatchmaking<S> {
+    service: Arc<dyn LanService<S>>,
+    matchmaking: Matchmaking<LanAttempt, S>,
+}
+impl<S> LanMatchmaking<S> {
+    pub fn new(service: Arc<dyn LanService<S>>) -> Self {
+        Self {
+            service,
+            matchmaking: Matchmaking::default(),
+        }
+    }
+    pub fn status(&self) -> &LanStatus {
+        self.matchmaking.status()
+    }
+    pub fn is_hosting(&self) -> bool {
+        matches!(self.matchmaking.waiting(), Some(LanAttempt::Host { .. }))
+    }
+    pub fn is_joining(&self) -> bool {
+        matches!(self.matchmaking.waiting(), Some(LanAttempt::Join(..)))
+    }
+    /// The players that joined the hosted match, including us.
+    pub fn joined_players(&self) -> Option<usize> {
+        match self.status() {
+            LanStatus::Waiting { players } if self.is_hosting() => Some(*players),
+            _ => None,
+        }
+    }
+    /// The network socket of the match once it is full of players.
+    pub fn socket(&self) -> Option<&S> {
+        self.matchmaking.socket()
+    }
+    /// The server being hosted, see [`LanService::hosted_server`].
+    pub fn hosted_server(&self) -> Option<lan::ServerInfo> {
+        self.is_hosting()
+            .then(|| self.service.hosted_server())
+            .flatten()
+    }
+    /// Starts hosting, cancelling anything that was already going on.
+    pub fn host(&mut self, service_type: &str, host_name: &str, player_count: u32) {
+        let attempt = LanAttempt::Host {
+            service_type: service_type.to_string(),
+            host_name: host_name.to_string(),
+            player_count,
+        };
+        self.matchmaking.start(&*self.service, attempt);
+    }
+    /// Starts joining the server, cancelling anything that was already going on.
+    ///
+    /// A server that advertises that it is full fails with
+    /// [`LanError::ServerFull`] without being joined.
+    pub fn join(&mut self, server: &lan::ServerInfo) {
+        if is_server_full(server) {
+            self.matchmaking.fail(&*self.service, LanError::ServerFull);
+            return;
+        }
+        self.matchmaking
+            .start(&*self.service, LanAttempt::Join(server.clone()));
+    }
+    /// Stops hosting or joining and forgets the socket of a match.
+    pub fn cancel(&mut self) {
+        self.matchmaking.cancel(&*self.service);
+    }
+    /// Fails the hosting or joining, stopping it.
+    pub fn fail(&mut self, error: LanError) {
+        self.matchmaking.fail(&*self.service, error);
+    }
+    /// Takes the error of a failed attempt, going back to idle.
+    pub fn take_error(&mut self) -> Option<LanError> {
+        self.matchmaking.take_error()
+    }
+    /// Handles the events from the matchmaker, see [`Matchmaking::update`].
+    pub fn update(&mut self, delta: Duration) {
+        self.matchmaking.update(&*self.service, delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    #[test]
+    fn advertised_players() {
+        let address = SocketAddrV4::new(Ipv4Addr::LOCALHOST, 4000).into();
+        let properties = HashMap::from([(NODE_ADDR_PROPERTY.to_string(), "0a".to_string())]);
+        let server = direct_server_info("_direct._udp.local.", address, properties).unwrap();
+        assert_eq!(server_players(&server), None);
+        assert!(!is_server_full(&server));
+
+        let advertised = with_players(&server, 1, 2).unwrap();
+        assert_eq!(server_players(&advertised), Some((1, 2)));
+        assert!(!is_server_full(&advertised));
+        assert_eq!(node_addr(&advertised), Ok("0a"));
+        assert_eq!(
+            advertised.service.get_fullname(),
+            server.service.get_fullname()
+        );
+        assert_eq!(advertised.service.get_port(), 4000);
+
+        let full = with_players(&advertised, 2, 2).unwrap();
+        assert_eq!(server_players(&full), Some((2, 2)));
+        assert!(is_server_full(&full));
+    }
+
+    #[test]
+    fn bones_join_error() {
+        let address = SocketAddrV4::new(Ipv4Addr::LOCALHOST, 4000).into();
+        let server = direct_server_info("_direct._udp.local.", address, HashMap::new()).unwrap();
+        assert!(node_addr(&server).is_err());
+
+        let mut matchmaking = LanMatchmaking::new(Arc::new(BonesLan::default()));
+        matchmaking.join(&server);
+        assert_eq!(*matchmaking.status(), LanStatus::Failed(LanError::Failed));
+        assert!(!matchmaking.is_joining());
+        assert_eq!(matchmaking.take_error(), Some(LanError::Failed));
+        assert_eq!(*matchmaking.status(), LanStatus::Idle);
+    }
+}
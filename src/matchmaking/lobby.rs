@@ -0,0 +1,407 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// The version of the game, which every peer of a match has to play.
+pub const GAME_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// How long the lobby waits to hear from every peer.
+pub const LOBBY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The team a peer wants its players on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LobbySide {
+    #[default]
+    Any,
+    A,
+    B,
+}
+impl LobbySide {
+    pub fn team(&self) -> Option<Team> {
+        match self {
+            Self::Any => None,
+            Self::A => Some(Team::A),
+            Self::B => Some(Team::B),
+        }
+    }
+    pub fn cycle(&mut self, direction: i8) {
+        let sides = [Self::A, Self::Any, Self::B];
+        let position = sides.iter().position(|side| side == self).unwrap() as isize;
+        *self = sides[(position + direction as isize).rem_euclid(sides.len() as isize) as usize];
+    }
+}
+
+/// The rules of a network match, which the host picks in the lobby.
+///
+/// Only the score target, the length of the countdown and the formations of
+/// the cpus can be picked. Everything else about the match, like the pitch and
+/// the speeds, is set by the game and its assets, which the peers already share by playing
+/// the same [`GAME_VERSION`]. A new rule has to be read by the play session
+/// the same way on every peer, and is sent to spectators along with the
+/// others in the [`SpectatorStart`].
+#[derive(HasSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchRules {
+    /// The score that wins the match.
+    pub score_target: u8,
+    /// The number the countdown before every round counts down from.
+    pub countdown: u8,
+    /// The formations of the cpus that fill the empty slots.
+    pub tactics: CpuTactics,
+}
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            score_target: 7,
+            countdown: 3,
+            tactics: default(),
+        }
+    }
+}
+impl MatchRules {
+    pub const MAX_SCORE_TARGET: u8 = 15;
+    /// The highest number the countdown has a sprite for.
+    pub const MAX_COUNTDOWN: u8 = 9;
+
+    pub fn change_score_target(&mut self, change: i8) {
+        self.score_target = self
+            .score_target
+            .saturating_add_signed(change)
+            .clamp(1, Self::MAX_SCORE_TARGET);
+    }
+    pub fn change_countdown(&mut self, change: i8) {
+        self.countdown = self
+            .countdown
+            .saturating_add_signed(change)
+            .clamp(1, Self::MAX_COUNTDOWN);
+    }
+    pub fn change_formation(&mut self, team: Team, direction: i8) {
+        self.tactics.formation_mut(team).cycle(direction);
+    }
+}
+
+/// What a peer shows the others in the lobby.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbyPeer {
+    /// Counts up with every change, since the messages can arrive out of order.
+    pub version: u32,
+    /// The [`GAME_VERSION`] of the peer, empty for versions from before it was sent.
+    #[serde(default)]
+    pub game_version: String,
+    pub name: String,
+    /// How many local players the peer has.
+    pub players: u8,
+    pub side: LobbySide,
+    pub ready: bool,
+    /// The rules the peer agrees to, which follow the ones of the host.
+    pub rules: MatchRules,
+}
+
+/// The lobby the peers of a network match wait in once they're connected.
+///
+/// Every peer picks a side and readies up, while the host, the peer with the
+/// first player index, picks the [`MatchRules`]. The match starts once every
+/// peer is ready with the rules of the host, after which nothing can change
+/// so all of the peers start it the same way.
+#[derive(Clone, Debug)]
+pub struct Lobby {
+    local: usize,
+    /// The lobby state of every peer by player index, this one included.
+    peers: Vec<Option<LobbyPeer>>,
+    /// Whether the local state changed since it was last sent.
+    send: bool,
+    /// Whether a peer left, which ends the lobby for everyone.
+    pub peer_left: bool,
+    /// Why the lobby can't go on, which ends it for this peer.
+    pub error: Option<LanError>,
+    opened: Instant,
+    round_trips: RoundTrips,
+}
+impl Lobby {
+    pub fn new(local: u32, peer_count: u32, name: String, players: u8) -> Self {
+        let mut peers = vec![None; peer_count as usize];
+        if let Some(peer) = peers.get_mut(local as usize) {
+            *peer = Some(LobbyPeer {
+                game_version: GAME_VERSION.to_string(),
+                name,
+                players,
+                ..default()
+            });
+        }
+        Self {
+            local: local as usize,
+            peers,
+            send: true,
+            peer_left: false,
+            error: None,
+            opened: Instant::now(),
+            round_trips: RoundTrips::new(peer_count),
+        }
+    }
+    pub fn is_host(&self) -> bool {
+        self.local == 0
+    }
+    pub fn local_idx(&self) -> usize {
+        self.local
+    }
+    pub fn local(&self) -> &LobbyPeer {
+        self.peers[self.local].as_ref().unwrap()
+    }
+    /// The peers by player index, `None` until they're heard from.
+    pub fn peers(&self) -> &[Option<LobbyPeer>] {
+        &self.peers
+    }
+    pub fn rules(&self) -> &MatchRules {
+        &self.local().rules
+    }
+    /// The round trip time to the slowest peer, once it's been measured.
+    pub fn rtt(&self) -> Option<std::time::Duration> {
+        self.round_trips.rtt(self.local)
+    }
+    fn change(&mut self, change: impl FnOnce(&mut LobbyPeer)) {
+        let local = self.peers[self.local].as_mut().unwrap();
+        change(local);
+        local.version += 1;
+        self.send = true;
+    }
+    /// Picks the side, which can't be done once ready.
+    pub fn set_side(&mut self, side: LobbySide) {
+        if !self.local().ready {
+            self.change(|local| local.side = side);
+        }
+    }
+    /// Readies up, which is final unless the host changes the rules.
+    pub fn ready(&mut self) {
+        if !self.local().ready && matches!(self.assignment(), Some(Ok(..))) {
+            self.change(|local| local.ready = true);
+        }
+    }
+    /// Changes the rules if this is the host and it isn't ready yet.
+    pub fn set_rules(&mut self, rules: MatchRules) {
+        if self.is_host() && !self.local().ready {
+            self.change(|local| local.rules = rules);
+        }
+    }
+    /// Handles a message from another peer.
+    pub fn receive(&mut self, peer: u32, message: PeerMessage) {
+        match message {
+            PeerMessage::Lobby(state) => {
+                let peer = peer as usize;
+                if peer == self.local {
+                    return;
+                }
+                let Some(entry) = self.peers.get_mut(peer) else {
+                    return;
+                };
+                if entry
+                    .as_ref()
+                    .is_some_and(|old| old.version >= state.version)
+                {
+                    return;
+                }
+                if state.game_version != self.local().game_version {
+                    tracing::warn!(
+                        peer,
+                        version = state.game_version,
+                        "peer plays another version"
+                    );
+                    self.error = Some(LanError::VersionMismatch);
+                }
+                // The rules changed, so the readiness was for other rules.
+                if peer == 0 && state.rules != *self.rules() {
+                    let rules = state.rules.clone();
+                    self.change(|local| {
+                        local.rules = rules;
+                        local.ready = false;
+                    });
+                }
+                self.peers[peer] = Some(state);
+            }
+            PeerMessage::Leave => {
+                tracing::info!(peer, "peer left the lobby");
+                self.peer_left = true;
+                if peer == 0 {
+                    self.error = Some(LanError::HostLeft);
+                }
+            }
+            PeerMessage::Rematch
+            | PeerMessage::Resume(..)
+            | PeerMessage::Ping(..)
+            | PeerMessage::Pong(..) => {}
+        }
+    }
+    /// The message telling the other peers about the local state, if it changed.
+    pub fn outgoing(&mut self) -> Option<PeerMessage> {
+        std::mem::take(&mut self.send).then(|| PeerMessage::Lobby(self.local().clone()))
+    }
+    /// Gives up on the peers that weren't heard from in time, which happens
+    /// when they can't read our messages.
+    pub fn check_timeout(&mut self, now: Instant) {
+        if self.error.is_none()
+            && self.peers.iter().any(Option::is_none)
+            && now.duration_since(self.opened) >= LOBBY_TIMEOUT
+        {
+            tracing::warn!("didn't hear from every peer in the lobby");
+            self.error = Some(LanError::TimedOut);
+        }
+    }
+    /// Which peer each slot would go to, once every peer has been heard from.
+    pub fn assignment(&self) -> Option<Result<SlotAssignment, String>> {
+        let peers = self.peers.iter().cloned().collect::<Option<Vec<_>>>()?;
+        let players = peers.iter().map(|peer| peer.players).collect::<Vec<_>>();
+        let sides = peers
+            .iter()
+            .map(|peer| peer.side.team())
+            .collect::<Vec<_>>();
+        Some(SlotAssignment::assign_sides(&players, &sides))
+    }
+    /// The slots and rules to play with once every peer is ready.
+    pub fn start(&self) -> Option<(SlotAssignment, MatchRules)> {
+        let peers = self
+            .peers
+            .iter()
+            .map(Option::as_ref)
+            .collect::<Option<Vec<_>>>()?;
+        let rules = &peers.first()?.rules;
+        if !peers.iter().all(|peer| peer.ready && peer.rules == *rules) {
+            return None;
+        }
+        let slots = self.assignment()?.ok()?;
+        Some((slots, rules.clone()))
+    }
+    /// Sends and receives the lobby messages over the socket, pinging the
+    /// other peers while they wait.
+    pub fn update(&mut self, socket: &NetworkMatchSocket) {
+        let now = std::time::Instant::now();
+        for (peer, message) in PeerMessage::receive(socket) {
+            if let Some(answer) = self.round_trips.receive(peer, &message, now) {
+                answer.send_to(socket, peer);
+            }
+            self.receive(peer, message);
+        }
+        if let Some(message) = self.outgoing() {
+            message.send(socket);
+        }
+        if let Some(ping) = self.round_trips.ping(now) {
+            ping.send(socket);
+        }
+        self.check_timeout(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Passes the pending messages of every lobby to the others.
+    fn exchange(lobbies: &mut [Lobby]) {
+        for from in 0..lobbies.len() {
+            if let Some(message) = lobbies[from].outgoing() {
+                for (to, lobby) in lobbies.iter_mut().enumerate() {
+                    if to != from {
+                        lobby.receive(from as u32, message.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ready_up() {
+        let mut lobbies = [
+            Lobby::new(0, 2, "host".to_string(), 1),
+            Lobby::new(1, 2, "guest".to_string(), 1),
+        ];
+        // Nobody can ready up before every peer is known.
+        lobbies[1].ready();
+        assert!(!lobbies[1].local().ready);
+        exchange(&mut lobbies);
+
+        lobbies[1].set_side(LobbySide::A);
+        lobbies[1].ready();
+        exchange(&mut lobbies);
+        assert_eq!(lobbies[0].peers()[1].as_ref().unwrap().side, LobbySide::A);
+        assert_eq!(lobbies[0].start(), None);
+
+        // Changing the rules takes back the readiness of the others.
+        let mut rules = MatchRules::default();
+        rules.change_score_target(3);
+        rules.change_countdown(-1);
+        rules.change_formation(Team::B, -1);
+        lobbies[0].set_rules(rules.clone());
+        exchange(&mut lobbies);
+        assert!(!lobbies[1].local().ready);
+        assert_eq!(lobbies[1].rules(), &rules);
+
+        // The guest can't change the rules.
+        lobbies[1].set_rules(MatchRules::default());
+        assert_eq!(lobbies[1].rules(), &rules);
+
+        lobbies[0].ready();
+        lobbies[1].ready();
+        exchange(&mut lobbies);
+        let start = lobbies[0].start().unwrap();
+        assert_eq!(lobbies[1].start(), Some(start.clone()));
+        assert_eq!(start.1.score_target, 10);
+        assert_eq!(start.1.countdown, 2);
+        assert_eq!(start.1.tactics.team_b, Formation::Aggressive);
+        assert_eq!(start.0.slot(1, false), Some(PlayerSlot::A1));
+    }
+
+    #[test]
+    fn stale_messages() {
+        let mut lobby = Lobby::new(0, 2, "host".to_string(), 2);
+        let peer = LobbyPeer {
+            version: 2,
+            ready: true,
+            ..default()
+        };
+        lobby.receive(1, PeerMessage::Lobby(peer.clone()));
+        lobby.receive(
+            1,
+            PeerMessage::Lobby(LobbyPeer {
+                version: 1,
+                ..default()
+            }),
+        );
+        assert_eq!(lobby.peers()[1], Some(peer));
+
+        lobby.receive(1, PeerMessage::Leave);
+        assert!(lobby.peer_left);
+    }
+
+    #[test]
+    fn lobby_errors() {
+        let mut lobby = Lobby::new(1, 3, "guest".to_string(), 1);
+        lobby.receive(
+            2,
+            PeerMessage::Lobby(LobbyPeer {
+                version: 1,
+                game_version: GAME_VERSION.to_string(),
+                ..default()
+            }),
+        );
+        assert_eq!(lobby.error, None);
+
+        // Peers from before the version was sent don't have one.
+        let old = serde_json::json!({ "Lobby": {
+            "version": 1,
+            "name": "host",
+            "players": 1,
+            "side": "Any",
+            "ready": false,
+            "rules": { "score_target": 7 },
+        }});
+        lobby.receive(0, serde_json::from_value(old).unwrap());
+        assert_eq!(lobby.error, Some(LanError::VersionMismatch));
+
+        let mut lobby = Lobby::new(1, 3, "guest".to_string(), 1);
+        lobby.check_timeout(lobby.opened + LOBBY_TIMEOUT / 2);
+        assert_eq!(lobby.error, None);
+        lobby.check_timeout(lobby.opened + LOBBY_TIMEOUT);
+        assert_eq!(lobby.error, Some(LanError::TimedOut));
+
+        let mut lobby = Lobby::new(1, 2, "guest".to_string(), 1);
+        lobby.receive(0, PeerMessage::Leave);
+        assert_eq!(lobby.error, Some(LanError::HostLeft));
+    }
+}
@@ -0,0 +1,264 @@
+use super::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The game id the online matchmaker uses to keep players of other games apart.
+pub const ONLINE_GAME_ID: &str = "striker_ball";
+
+/// A short code that friends enter to be matched with each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchCode(pub [u8; MatchCode::LEN]);
+impl Default for MatchCode {
+    fn default() -> Self {
+        Self([Self::ALPHABET[0]; Self::LEN])
+    }
+}
+impl MatchCode {
+    pub const LEN: usize = 4;
+    /// The characters a code is made of, leaving out the ones that are easy to mix up.
+    pub const ALPHABET: &'static [u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+    /// A code made from the seed.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = SplitMix64(seed);
+        Self(std::array::from_fn(|_| {
+            Self::ALPHABET[rng.next_u64() as usize % Self::ALPHABET.len()]
+        }))
+    }
+    /// A code seeded from the clock, so that it is unlikely to be the same as anyone else's.
+    pub fn random() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Self::from_seed(nanos as u64)
+    }
+    /// Steps the character at the index through the [`MatchCode::ALPHABET`].
+    pub fn cycle(&mut self, index: usize, direction: i8) {
+        let Some(char) = self.0.get_mut(index) else {
+            return;
+        };
+        let position = Self::ALPHABET
+            .iter()
+            .position(|c| c == char)
+            .unwrap_or_default() as isize;
+        let position =
+            (position + direction as isize).rem_euclid(Self::ALPHABET.len() as isize) as usize;
+        *char = Self::ALPHABET[position];
+    }
+}
+impl std::fmt::Display for MatchCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.0))
+    }
+}
+
+/// Who an online search is matched with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnlineSearch {
+    /// Any other player doing a quick match.
+    QuickMatch,
+    /// Only players searching with the same code.
+    Private(MatchCode),
+}
+impl OnlineSearch {
+    /// The match data sent to the matchmaker, which only matches players
+    /// whose match data is the same.
+    pub fn match_data(&self) -> Vec<u8> {
+        match self {
+            Self::QuickMatch => b"quick".to_vec(),
+            Self::Private(code) => format!("private:{code}").into_bytes(),
+        }
+    }
+}
+
+/// What the online matchmaker tells us about a search.
+pub type OnlineEvent<S> = MatchmakingEvent<S, String>;
+
+/// Something that can match players over the internet, see
+/// [`MatchmakingService`].
+pub trait OnlineService<S>: Send + Sync {
+    /// Starts searching for a match on the matchmaking server.
+    fn search(&self, server: &str, player_count: u32, match_data: Vec<u8>) -> Result<(), String>;
+    /// Stops searching on the matchmaking server.
+    fn cancel(&self, server: &str);
+    /// The next event from the matchmaking server if there is one.
+    fn poll(&self) -> Option<OnlineEvent<S>>;
+}
+impl<S> MatchmakingService<OnlineAttempt, S> for dyn OnlineService<S> {
+    fn start(&self, attempt: &OnlineAttempt) -> Result<(), String> {
+        self.search(
+            &attempt.server,
+            attempt.player_count,
+            attempt.search.match_data(),
+        )
+    }
+    fn cancel(&self, attempt: &OnlineAttempt) {
+        OnlineService::cancel(self, &attempt.server);
+    }
+    fn poll(&self) -> Option<OnlineEvent<S>> {
+        OnlineService::poll(self)
+    }
+}
+
+/// The [`OnlineService`] of the bones online matchmaker.
+#[derive(Clone, Copy, Default)]
+pub struct BonesOnline;
+impl BonesOnline {
+    /// What the matchmaker is asked to find.
+    fn match_info(player_count: u32, match_data: Vec<u8>) -> online::MatchInfo {
+        online::MatchInfo {
+            max_players: player_count,
+            match_data,
+            game_id: ONLINE_GAME_ID.into(),
+            player_idx_assignment: online::PlayerIdxAssignment::Ordered,
+        }
+    }
+    /// The event of a response from the matchmaker, if it's one the game uses.
+    fn event(
+        response: online::OnlineMatchmakerResponse,
+    ) -> Option<OnlineEvent<NetworkMatchSocket>> {
+        use online::OnlineMatchmakerResponse;
+
+        Some(match response {
+            OnlineMatchmakerResponse::PlayerCount(count) => OnlineEvent::PlayerCount(count),
+            OnlineMatchmakerResponse::GameStarting { socket, .. } => OnlineEvent::Ready(socket),
+            OnlineMatchmakerResponse::Error(err) => OnlineEvent::Error(err),
+            // Searching is told by the status already and the lobby
+            // responses aren't used by the game.
+            _ => return None,
+        })
+    }
+}
+impl OnlineService<NetworkMatchSocket> for BonesOnline {
+    fn search(&self, server: &str, player_count: u32, match_data: Vec<u8>) -> Result<(), String> {
+        let server = server
+            .parse()
+            .map_err(|err| format!("invalid matchmaking server id: {err}"))?;
+        online::start_search_for_game(server, Self::match_info(player_count, match_data));
+        Ok(())
+    }
+    fn cancel(&self, server: &str) {
+        if let Ok(server) = server.parse() {
+            if let Err(err) = online::stop_search_for_game(server) {
+                tracing::warn!(?err, "failed to stop the online search");
+            }
+        }
+    }
+    fn poll(&self) -> Option<OnlineEvent<NetworkMatchSocket>> {
+        while let Ok(response) = online::read_matchmaker_response() {
+            if let Some(event) = Self::event(response) {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+/// The state of an [`OnlineMatchmaking`] search.
+pub type OnlineStatus = MatchmakingStatus<String>;
+
+/// A search on a matchmaking server.
+#[derive(Clone, Debug)]
+pub struct OnlineAttempt {
+    /// The matchmaking server of the search.
+    server: String,
+    search: OnlineSearch,
+    player_count: u32,
+}
+impl MatchmakingAttempt for OnlineAttempt {
+    type Error = String;
+
+    fn timed_out() -> String {
+        "timed out".to_string()
+    }
+}
+
+/// Searches for matches over the internet through an [`OnlineService`], see
+/// [`Matchmaking`].
+#[derive(Clone)]
+pub struct OnlineMatchmaking<S> {
+    service: Arc<dyn OnlineService<S>>,
+    player_count: u32,
+    matchmaking: Matchmaking<OnlineAttempt, S>,
+}
+impl<S> OnlineMatchmaking<S> {
+    pub fn new(service: Arc<dyn OnlineService<S>>, player_count: u32) -> Self {
+        Self {
+            service,
+            player_count,
+            matchmaking: Matchmaking::default(),
+        }
+    }
+    pub fn status(&self) -> &OnlineStatus {
+        self.matchmaking.status()
+    }
+    pub fn is_searching(&self) -> bool {
+        self.matchmaking.waiting().is_some()
+    }
+    /// Who the running search is matched with.
+    pub fn searching(&self) -> Option<OnlineSearch> {
+        self.matchmaking.waiting().map(|attempt| attempt.search)
+    }
+    /// The network socket of the match once it is full of players.
+    pub fn socket(&self) -> Option<&S> {
+        self.matchmaking.socket()
+    }
+    /// Starts searching on the matchmaking server, cancelling any search
+    /// that was already running.
+    pub fn search(&mut self, server: &str, search: OnlineSearch) {
+        let attempt = OnlineAttempt {
+            server: server.to_string(),
+            search,
+            player_count: self.player_count,
+        };
+        self.matchmaking.start(&*self.service, attempt);
+        tracing::info!(?search, status = ?self.status(), "online search");
+    }
+    /// Stops the search and forgets the socket of a finished one.
+    pub fn cancel(&mut self) {
+        self.matchmaking.cancel(&*self.service);
+    }
+    /// Handles the events from the matchmaking server, see
+    /// [`Matchmaking::update`].
+    pub fn update(&mut self, delta: Duration) {
+        self.matchmaking.update(&*self.service, delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use online::OnlineMatchmakerResponse;
+
+    #[test]
+    fn bones_search() {
+        let err = BonesOnline
+            .search("not a server id", 2, b"quick".to_vec())
+            .unwrap_err();
+        assert!(err.starts_with("invalid matchmaking server id"), "{err}");
+
+        let search = OnlineSearch::Private(MatchCode::from_seed(1));
+        let info = BonesOnline::match_info(2, search.match_data());
+        assert_eq!(info.max_players, 2);
+        assert_eq!(info.match_data, search.match_data());
+        assert_eq!(info.game_id, ONLINE_GAME_ID);
+        assert!(matches!(
+            info.player_idx_assignment,
+            online::PlayerIdxAssignment::Ordered
+        ));
+    }
+
+    #[test]
+    fn bones_events() {
+        assert!(BonesOnline::event(OnlineMatchmakerResponse::Searching).is_none());
+        assert!(matches!(
+            BonesOnline::event(OnlineMatchmakerResponse::PlayerCount(1)),
+            Some(OnlineEvent::PlayerCount(1))
+        ));
+        assert!(matches!(
+            BonesOnline::event(OnlineMatchmakerResponse::Error("full".to_string())),
+            Some(OnlineEvent::Error(err)) if err == "full"
+        ));
+    }
+}
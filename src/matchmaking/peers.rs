@@ -0,0 +1,46 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+/// The messages the peers of a network match send each other outside of
+/// their inputs, over the reliable channel of the [`NetworkMatchSocket`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PeerMessage {
+    /// The state of the peer in the [`Lobby`].
+    Lobby(LobbyPeer),
+    /// The peer voted for a rematch once the match was done.
+    Rematch,
+    /// The peer left the match, so there won't be a rematch.
+    Leave,
+    /// Where to pick the match up from, sent by the host once the peers
+    /// found each other again after one dropped.
+    Resume(MatchResume),
+    /// Asks the peer to answer with a [`PeerMessage::Pong`] of the same id,
+    /// see [`RoundTrips`].
+    Ping(u32),
+    Pong(u32),
+}
+impl PeerMessage {
+    pub fn send(&self, socket: &NetworkMatchSocket) {
+        let message = serde_json::to_vec(self).expect("peer messages serialize");
+        socket.send_reliable(SocketTarget::All, &message);
+    }
+    /// Sends the message to a single peer by its player index.
+    pub fn send_to(&self, socket: &NetworkMatchSocket, peer: u32) {
+        let message = serde_json::to_vec(self).expect("peer messages serialize");
+        socket.send_reliable(SocketTarget::Player(peer as usize), &message);
+    }
+    /// The messages that arrived from the other peers along with their player index.
+    pub fn receive(socket: &NetworkMatchSocket) -> Vec<(u32, Self)> {
+        socket
+            .recv_reliable()
+            .into_iter()
+            .filter_map(|(peer, message)| match serde_json::from_slice(&message) {
+                Ok(message) => Some((peer, message)),
+                Err(err) => {
+                    tracing::warn!(?err, peer, "unknown peer message");
+                    None
+                }
+            })
+            .collect()
+    }
+}
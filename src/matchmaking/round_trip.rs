@@ -0,0 +1,138 @@
+use super::*;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How often the peers are pinged while they wait in the [`Lobby`].
+pub const PING_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Measures the round trip time to the other peers of a network match by
+/// pinging them, which the automatic [`InputDelaySetting`] is picked from.
+#[derive(Clone, Debug)]
+pub struct RoundTrips {
+    /// The pings that haven't been answered yet, by id.
+    pending: VecDeque<(u32, Instant)>,
+    next_id: u32,
+    last_ping: Option<Instant>,
+    /// The smoothed round trip time to each peer by player index.
+    rtts: Vec<Option<Duration>>,
+}
+impl RoundTrips {
+    /// The most pings waiting for an answer, older ones count as lost.
+    const MAX_PENDING: usize = 8;
+
+    pub fn new(peer_count: u32) -> Self {
+        Self {
+            pending: VecDeque::new(),
+            next_id: 0,
+            last_ping: None,
+            rtts: vec![None; peer_count as usize],
+        }
+    }
+    /// The ping to send to the other peers, if it's time for another one.
+    pub fn ping(&mut self, now: Instant) -> Option<PeerMessage> {
+        if self
+            .last_ping
+            .is_some_and(|last| now.duration_since(last) < PING_INTERVAL)
+        {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.last_ping = Some(now);
+        if self.pending.len() == Self::MAX_PENDING {
+            self.pending.pop_front();
+        }
+        self.pending.push_back((id, now));
+        Some(PeerMessage::Ping(id))
+    }
+    /// Handles a message from another peer, returning the answer to send
+    /// back to it if it pinged.
+    pub fn receive(
+        &mut self,
+        peer: u32,
+        message: &PeerMessage,
+        now: Instant,
+    ) -> Option<PeerMessage> {
+        match *message {
+            PeerMessage::Ping(id) => Some(PeerMessage::Pong(id)),
+            PeerMessage::Pong(id) => {
+                let (_, sent) = self.pending.iter().find(|(pending, _)| *pending == id)?;
+                let sample = now.duration_since(*sent);
+                let rtt = self.rtts.get_mut(peer as usize)?;
+                *rtt = Some(rtt.map_or(sample, |rtt| (rtt * 3 + sample) / 4));
+                None
+            }
+            _ => None,
+        }
+    }
+    /// The round trip time to the slowest peer, once every peer answered.
+    pub fn rtt(&self, local: usize) -> Option<Duration> {
+        self.rtts
+            .iter()
+            .enumerate()
+            .filter(|(peer, _)| *peer != local)
+            .map(|(_, rtt)| *rtt)
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max()
+    }
+}
+
+/// The input delay and prediction window a network match is played with,
+/// picked from the [`Settings`] once the peers leave the [`Lobby`].
+#[derive(HasSchema, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetworkDelay {
+    pub input_delay: u8,
+    pub max_prediction: u8,
+}
+impl Default for NetworkDelay {
+    fn default() -> Self {
+        Self::new(&default(), None)
+    }
+}
+impl NetworkDelay {
+    pub fn new(settings: &Settings, rtt: Option<Duration>) -> Self {
+        Self {
+            input_delay: settings.input_delay.frames(rtt),
+            max_prediction: *settings.max_prediction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtt() {
+        let start = Instant::now();
+        let mut host = RoundTrips::new(3);
+        let mut guests = [RoundTrips::new(3), RoundTrips::new(3)];
+
+        let ping = host.ping(start).unwrap();
+        assert_eq!(host.ping(start + PING_INTERVAL / 2), None);
+
+        let answers = guests
+            .each_mut()
+            .map(|guest| guest.receive(0, &ping, start).unwrap());
+        host.receive(1, &answers[0], start + Duration::from_millis(20));
+        assert_eq!(host.rtt(0), None);
+        host.receive(2, &answers[1], start + Duration::from_millis(60));
+        assert_eq!(host.rtt(0), Some(Duration::from_millis(60)));
+        assert_eq!(InputDelaySetting::from_rtt(host.rtt(0).unwrap()), 2);
+
+        // Later samples are smoothed into the earlier ones.
+        let ping = host.ping(start + PING_INTERVAL).unwrap();
+        let answer = guests[1].receive(0, &ping, start).unwrap();
+        host.receive(
+            2,
+            &answer,
+            start + PING_INTERVAL + Duration::from_millis(20),
+        );
+        assert_eq!(host.rtt(0), Some(Duration::from_millis(50)));
+
+        // Answers to pings that were never sent are ignored.
+        host.receive(1, &PeerMessage::Pong(42), start + Duration::from_secs(10));
+        assert_eq!(host.rtt(0), Some(Duration::from_millis(50)));
+    }
+}
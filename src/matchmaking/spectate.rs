@@ -0,0 +1,556 @@
+use super::*;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufReader, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How many frames spectators stay behind the stream, so that it plays
+/// smoothly through the hiccups of the network.
+pub const SPECTATOR_DELAY: usize = 90;
+/// The most frames a spectator plays in one step while it catches up, like
+/// after joining a match in progress.
+pub const SPECTATOR_CATCH_UP: usize = 20;
+/// How long the host waits on a spectator before dropping it.
+pub const SPECTATOR_WRITE_TIMEOUT: Duration = Duration::from_millis(50);
+/// How long joining a match to watch waits for the host to answer.
+pub const SPECTATOR_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// The most frames sent to a spectator in one message.
+const FRAMES_PER_MESSAGE: usize = 300;
+
+/// The dense input of every peer for one frame.
+pub type FrameInputs = [u64; MAX_PEERS];
+
+/// What a spectator needs to set a match up the same way the peers did.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpectatorStart {
+    pub slots: SlotAssignment,
+    pub rules: MatchRules,
+    /// Where the match picked up from after a peer dropped.
+    pub resume: Option<MatchResume>,
+}
+
+/// What the host streams to spectators, one json message per line.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpectatorMessage {
+    /// Sent first, before any of the frames.
+    Start(SpectatorStart),
+    /// The inputs of the next confirmed frames.
+    Frames(Vec<FrameInputs>),
+}
+
+/// Counts the frames of a network match, which is rolled back along with it
+/// so that the [`SpectatorFeed`] knows which frame the inputs belong to.
+#[derive(HasSchema, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct MatchFrame(pub usize);
+
+#[derive(Default)]
+pub struct SpectatorFeedState {
+    /// The inputs of every frame so far, the last ones of which may still
+    /// be rolled back.
+    frames: Vec<FrameInputs>,
+    max_prediction: usize,
+}
+impl SpectatorFeedState {
+    /// Records the inputs of the frame, dropping the predicted frames after
+    /// it when it was rolled back.
+    pub fn record(&mut self, frame: usize, inputs: FrameInputs) {
+        self.frames.truncate(frame);
+        if self.frames.len() == frame {
+            self.frames.push(inputs);
+        }
+    }
+    /// The frames that can't be rolled back anymore, since every peer sent
+    /// its input for them.
+    pub fn confirmed(&self) -> &[FrameInputs] {
+        let confirmed = self.frames.len().saturating_sub(self.max_prediction + 1);
+        &self.frames[..confirmed]
+    }
+}
+
+/// The inputs of a network match hosted by this peer, shared between the
+/// play session that records them and the [`Matchmaker`] that streams them to
+/// the spectators.
+#[derive(HasSchema, Clone, Default, Deref)]
+pub struct SpectatorFeed(pub Arc<Mutex<SpectatorFeedState>>);
+impl SpectatorFeed {
+    pub fn new(max_prediction: usize) -> Self {
+        Self(Arc::new(Mutex::new(SpectatorFeedState {
+            frames: Vec::new(),
+            max_prediction,
+        })))
+    }
+}
+impl SessionPlugin for SpectatorFeed {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+        session.init_resource::<MatchFrame>();
+        session.add_system_to_stage(First, record_spectator_inputs);
+    }
+}
+
+/// Records the inputs of the frame, which the network runner has already
+/// applied by the time the stages run.
+pub fn record_spectator_inputs(
+    feed: Res<SpectatorFeed>,
+    inputs: Res<PlayTeamInputs>,
+    mut frame: ResMut<MatchFrame>,
+) {
+    let frame_inputs = inputs
+        .clients
+        .map(|client| u64::from(client.get_dense_input()));
+    feed.lock().unwrap().record(**frame, frame_inputs);
+    **frame += 1;
+}
+
+struct Spectator {
+    stream: TcpStream,
+    /// How many of the confirmed frames were sent.
+    sent: usize,
+}
+
+/// The match advertised on the local network, until it is dropped.
+struct SpectatorAdvert {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+impl Drop for SpectatorAdvert {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Streams a hosted match to the spectators that connect.
+pub struct SpectatorServer {
+    listener: TcpListener,
+    advert: Option<SpectatorAdvert>,
+    feed: SpectatorFeed,
+    start: SpectatorStart,
+    spectators: Vec<Spectator>,
+}
+impl SpectatorServer {
+    pub fn new(feed: SpectatorFeed, start: SpectatorStart) -> Result<Self, String> {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).map_err(|err| err.to_string())?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            listener,
+            advert: None,
+            feed,
+            start,
+            spectators: Vec::new(),
+        })
+    }
+    pub fn port(&self) -> Option<u16> {
+        self.listener
+            .local_addr()
+            .ok()
+            .map(|address| address.port())
+    }
+    /// Advertises the match under the host name, so that spectators find it
+    /// with a [`SpectatorDiscovery`].
+    pub fn advertise(&mut self, service_type: &str, host_name: &str) -> Result<(), String> {
+        let port = self.port().ok_or("the spectator server has no port")?;
+        let daemon = ServiceDaemon::new().map_err(|err| err.to_string())?;
+        let service = ServiceInfo::new(
+            service_type,
+            host_name,
+            &format!("{host_name}.local."),
+            (),
+            port,
+            std::collections::HashMap::<String, String>::new(),
+        )
+        .map_err(|err| err.to_string())?
+        .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+        daemon.register(service).map_err(|err| err.to_string())?;
+        tracing::info!(port, "streaming the match to spectators");
+        self.advert = Some(SpectatorAdvert { daemon, fullname });
+        Ok(())
+    }
+    /// Lets the new spectators in and sends everyone the frames they haven't
+    /// seen yet, dropping the ones that can't keep up or left.
+    pub fn update(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => {
+                    let mut stream = stream;
+                    let message = SpectatorMessage::Start(self.start.clone());
+                    let result = stream
+                        .set_nodelay(true)
+                        .and_then(|_| stream.set_write_timeout(Some(SPECTATOR_WRITE_TIMEOUT)))
+                        .and_then(|_| send_message(&mut stream, &message));
+                    match result {
+                        Ok(()) => {
+                            tracing::info!(%address, "spectator joined");
+                            self.spectators.push(Spectator { stream, sent: 0 });
+                        }
+                        Err(err) => tracing::warn!(%err, %address, "spectator couldn't join"),
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    tracing::warn!(%err, "couldn't accept spectator");
+                    break;
+                }
+            }
+        }
+        let feed = self.feed.lock().unwrap();
+        let confirmed = feed.confirmed();
+        self.spectators.retain_mut(|spectator| {
+            while spectator.sent < confirmed.len() {
+                let end = confirmed.len().min(spectator.sent + FRAMES_PER_MESSAGE);
+                let message = SpectatorMessage::Frames(confirmed[spectator.sent..end].to_vec());
+                if let Err(err) = send_message(&mut spectator.stream, &message) {
+                    tracing::info!(%err, "spectator left");
+                    return false;
+                }
+                spectator.sent = end;
+            }
+            true
+        });
+    }
+}
+/// A match on the local network that can be watched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchableMatch {
+    pub host_name: String,
+    pub address: SocketAddr,
+    fullname: String,
+}
+
+/// Finds the matches that are being streamed on the local network.
+pub struct SpectatorDiscovery {
+    daemon: ServiceDaemon,
+    receiver: mdns_sd::Receiver<ServiceEvent>,
+}
+impl SpectatorDiscovery {
+    pub fn new(service_type: &str) -> Result<Self, String> {
+        let daemon = ServiceDaemon::new().map_err(|err| err.to_string())?;
+        let receiver = daemon.browse(service_type).map_err(|err| err.to_string())?;
+        Ok(Self { daemon, receiver })
+    }
+    /// Adds the matches that showed up and removes the ones that ended.
+    pub fn update(&self, matches: &mut Vec<WatchableMatch>) {
+        while let Ok(event) = self.receiver.try_recv() {
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    let Some(ip) = info.get_addresses().iter().next() else {
+                        continue;
+                    };
+                    let found = WatchableMatch {
+                        host_name: info
+                            .get_hostname()
+                            .split_once('.')
+                            .map_or(info.get_hostname(), |(name, _)| name)
+                            .to_string(),
+                        address: SocketAddr::new(*ip, info.get_port()),
+                        fullname: info.get_fullname().to_string(),
+                    };
+                    matches.retain(|watchable| watchable.fullname != found.fullname);
+                    matches.push(found);
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    matches.retain(|watchable| watchable.fullname != fullname);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+impl Drop for SpectatorDiscovery {
+    fn drop(&mut self) {
+        let _ = self.daemon.shutdown();
+    }
+}
+
+pub struct SpectatorStreamState {
+    /// The connection to the host until it is made.
+    connecting: Option<Connecting>,
+    reader: Option<BufReader<TcpStream>>,
+    /// A partially received line.
+    pending: String,
+    start: Option<SpectatorStart>,
+    frames: VecDeque<FrameInputs>,
+    /// Whether the host stopped streaming.
+    closed: bool,
+    /// Whether enough frames arrived to play, see [`SPECTATOR_DELAY`].
+    playing: bool,
+}
+impl SpectatorStreamState {
+    fn new(address: SocketAddr) -> Self {
+        Self {
+            connecting: Some(Connecting::new(address, SPECTATOR_CONNECT_TIMEOUT)),
+            reader: None,
+            pending: String::new(),
+            start: None,
+            frames: VecDeque::new(),
+            closed: false,
+            playing: false,
+        }
+    }
+    /// Reads every complete message that has arrived, once connected.
+    pub fn receive(&mut self) {
+        if let Some(connecting) = &self.connecting {
+            let connected = connecting.poll().and_then(|stream| {
+                let Some(stream) = stream else {
+                    return Ok(None);
+                };
+                stream.set_nonblocking(true)?;
+                Ok(Some(stream))
+            });
+            match connected {
+                Ok(None) => return,
+                Ok(Some(stream)) => self.reader = Some(BufReader::new(stream)),
+                Err(err) => {
+                    tracing::info!(%err, "couldn't connect to the spectator stream");
+                    self.closed = true;
+                }
+            }
+            self.connecting = None;
+        }
+        let Some(reader) = &mut self.reader else {
+            return;
+        };
+        while !self.closed {
+            match read_message(reader, &mut self.pending) {
+                Ok(Some(SpectatorMessage::Start(start))) => self.start = Some(start),
+                Ok(Some(SpectatorMessage::Frames(frames))) => self.frames.extend(frames),
+                Ok(None) => return,
+                Err(err) => {
+                    tracing::info!(%err, "spectator stream closed");
+                    self.closed = true;
+                }
+            }
+        }
+    }
+    pub fn start(&self) -> Option<&SpectatorStart> {
+        self.start.as_ref()
+    }
+    /// Whether the host stopped streaming and every frame was played.
+    pub fn is_finished(&self) -> bool {
+        self.closed && self.frames.is_empty()
+    }
+    /// Whether the host stopped streaming before the match could be set up.
+    pub fn failed(&self) -> bool {
+        self.closed && self.start.is_none()
+    }
+    /// The frames to play in one step.
+    ///
+    /// Nothing is played until [`SPECTATOR_DELAY`] frames arrived, and again
+    /// after running out, so that the stream doesn't stutter. When too many
+    /// frames are waiting, a few are played at once to catch up.
+    pub fn take_frames(&mut self) -> Vec<FrameInputs> {
+        if !self.playing {
+            self.playing = self.frames.len() >= SPECTATOR_DELAY || self.closed;
+            if !self.playing {
+                return Vec::new();
+            }
+        }
+        let count = if self.frames.len() > SPECTATOR_DELAY * 2 {
+            (self.frames.len() - SPECTATOR_DELAY).min(SPECTATOR_CATCH_UP)
+        } else {
+            1
+        };
+        let frames = self.frames.drain(..count.min(self.frames.len())).collect();
+        if self.frames.is_empty() && !self.closed {
+            self.playing = false;
+        }
+        frames
+    }
+}
+
+/// The connection of a spectator to the host of the match it watches.
+#[derive(HasSchema, Clone, Deref)]
+#[schema(no_default)]
+pub struct SpectatorStream(pub Arc<Mutex<SpectatorStreamState>>);
+impl SpectatorStream {
+    /// Starts connecting to the host at the address, which fails the stream
+    /// if it can't be reached, see [`SpectatorStreamState::failed`].
+    pub fn connect(address: SocketAddr) -> Self {
+        Self(Arc::new(Mutex::new(SpectatorStreamState::new(address))))
+    }
+}
+
+/// Plays a match from the inputs streamed by its host, see [`SpectatorStream`].
+pub struct SpectatorRunner {
+    pub stream: SpectatorStream,
+    pub accumulator: f64,
+    pub last_run: Option<Instant>,
+}
+impl SpectatorRunner {
+    pub fn new(stream: SpectatorStream) -> Self {
+        Self {
+            stream,
+            accumulator: 0.0,
+            last_run: None,
+        }
+    }
+}
+impl SessionRunner for SpectatorRunner {
+    fn step(&mut self, frame_start: Instant, world: &mut World, stages: &mut SystemStages) {
+        let last_run = self.last_run.unwrap_or(frame_start);
+        self.last_run = Some(frame_start);
+        // A long hitch isn't made up for, the stream catches up on its own.
+        self.accumulator =
+            (self.accumulator + (frame_start - last_run).as_secs_f64()).min(TARGET_STEP * 4.0);
+
+        self.stream.lock().unwrap().receive();
+        while self.accumulator >= TARGET_STEP {
+            self.accumulator -= TARGET_STEP;
+            let frames = self.stream.lock().unwrap().take_frames();
+            for inputs in frames {
+                for (client, input) in world
+                    .resource_mut::<PlayTeamInputs>()
+                    .clients
+                    .iter_mut()
+                    .zip(inputs)
+                {
+                    client.update_from_dense(&input.into());
+                }
+                world
+                    .resource_mut::<Time>()
+                    .advance_exact(Duration::from_secs_f64(TARGET_STEP));
+                stages.run(world);
+            }
+        }
+    }
+    fn restart_session(&mut self) {
+        self.accumulator = 0.0;
+        self.last_run = None;
+    }
+    fn disable_local_input(&mut self, _disable_input: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(frame: u64) -> FrameInputs {
+        [frame, 0, 0, 0]
+    }
+
+    #[test]
+    fn confirmed_frames() {
+        let feed = SpectatorFeed::new(2);
+        let mut feed = feed.lock().unwrap();
+        for frame in 0..5 {
+            feed.record(frame, inputs(frame as u64));
+        }
+        assert_eq!(feed.confirmed(), &[inputs(0), inputs(1)]);
+
+        // A rollback replaces the predicted frames.
+        feed.record(3, inputs(30));
+        feed.record(4, inputs(40));
+        feed.record(5, inputs(50));
+        assert_eq!(feed.confirmed(), &[inputs(0), inputs(1), inputs(2)]);
+        assert_eq!(feed.frames[3..], [inputs(30), inputs(40), inputs(50)]);
+    }
+
+    #[test]
+    fn stream_to_spectator() {
+        let feed = SpectatorFeed::new(0);
+        let start = SpectatorStart {
+            slots: SlotAssignment::assign(&[1, 1, 1]).unwrap(),
+            rules: MatchRules::default(),
+            resume: Some(MatchResume {
+                score: [1, 2],
+                ..default()
+            }),
+        };
+        let mut server = SpectatorServer::new(feed.clone(), start.clone()).unwrap();
+        let address = SocketAddr::from(([127, 0, 0, 1], server.port().unwrap()));
+
+        // The frames from before the spectator joined are sent too.
+        for frame in 0..SPECTATOR_DELAY * 3 {
+            feed.lock().unwrap().record(frame, inputs(frame as u64));
+        }
+        let stream = SpectatorStream::connect(address);
+        while server.spectators.is_empty() {
+            server.update();
+        }
+        // The last frame isn't confirmed yet.
+        let frames = SPECTATOR_DELAY * 3 + FRAMES_PER_MESSAGE;
+        for frame in SPECTATOR_DELAY * 3..=frames {
+            feed.lock().unwrap().record(frame, inputs(frame as u64));
+        }
+        server.update();
+        assert_eq!(server.spectators[0].sent, frames);
+        drop(server);
+
+        let mut state = stream.lock().unwrap();
+        while !state.closed {
+            state.receive();
+        }
+        assert_eq!(state.start(), Some(&start));
+
+        // It catches up to the delay, then plays one frame at a time.
+        let mut played = Vec::new();
+        while !state.is_finished() {
+            let waiting = state.frames.len();
+            let taken = state.take_frames();
+            if waiting > SPECTATOR_DELAY * 2 + SPECTATOR_CATCH_UP {
+                assert_eq!(taken.len(), SPECTATOR_CATCH_UP);
+            } else if waiting <= SPECTATOR_DELAY * 2 {
+                assert_eq!(taken.len(), 1);
+            }
+            played.extend(taken);
+        }
+        assert_eq!(played, (0..frames as u64).map(inputs).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn render_delay() {
+        let (listener, address) = {
+            let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+            let address = listener.local_addr().unwrap();
+            (listener, address)
+        };
+        let stream = SpectatorStream::connect(address);
+        let (mut host, _) = listener.accept().unwrap();
+        let mut state = stream.lock().unwrap();
+
+        let frames = (0..SPECTATOR_DELAY as u64 - 1).map(inputs).collect();
+        send_message(&mut host, &SpectatorMessage::Frames(frames)).unwrap();
+        while state.frames.len() < SPECTATOR_DELAY - 1 {
+            state.receive();
+        }
+        assert!(state.take_frames().is_empty());
+
+        send_message(&mut host, &SpectatorMessage::Frames(vec![inputs(99)])).unwrap();
+        while state.frames.len() < SPECTATOR_DELAY {
+            state.receive();
+        }
+        assert_eq!(state.take_frames(), vec![inputs(0)]);
+
+        // Once the frames run out it waits for the delay again.
+        for _ in 1..SPECTATOR_DELAY {
+            assert_eq!(state.take_frames().len(), 1);
+        }
+        send_message(&mut host, &SpectatorMessage::Frames(vec![inputs(100)])).unwrap();
+        while state.frames.is_empty() {
+            state.receive();
+        }
+        assert!(state.take_frames().is_empty());
+        assert!(!state.is_finished());
+    }
+
+    #[test]
+    fn unreachable_host() {
+        let address = {
+            let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+            listener.local_addr().unwrap()
+        };
+        // Connecting doesn't wait for the host, which isn't there.
+        let stream = SpectatorStream::connect(address);
+        let mut state = stream.lock().unwrap();
+        while !state.closed {
+            state.receive();
+        }
+        assert!(state.failed());
+    }
+}
@@ -15,7 +15,13 @@ pub enum MenuState {
     #[cfg(not(target_arch = "wasm32"))]
     Lan,
     #[cfg(not(target_arch = "wasm32"))]
+    Online,
+    #[cfg(not(target_arch = "wasm32"))]
+    Lobby,
+    #[cfg(not(target_arch = "wasm32"))]
     InNetworkGame,
+    #[cfg(not(target_arch = "wasm32"))]
+    NetworkMatchDone,
 }
 
 pub fn update_menu(world: &World) {
@@ -26,7 +32,15 @@ pub fn update_menu(world: &World) {
     #[cfg(not(target_arch = "wasm32"))]
     let lan_ui = world.resource_mut::<LanUI>().process_ui(world);
     #[cfg(not(target_arch = "wasm32"))]
+    let online_ui = world.resource_mut::<OnlineUI>().process_ui(world);
+    #[cfg(not(target_arch = "wasm32"))]
+    let lobby_ui = world.resource_mut::<LobbyUI>().process_ui(world);
+    #[cfg(not(target_arch = "wasm32"))]
     let network_quit = world.resource_mut::<NetworkQuit>().process_ui(world);
+    #[cfg(not(target_arch = "wasm32"))]
+    let network_match_done = world.resource_mut::<NetworkMatchDone>().process_ui(world);
+    #[cfg(not(target_arch = "wasm32"))]
+    let network_grace = world.resource_mut::<NetworkGraceUI>().process_ui(world);
 
     let splash_output = world.resource_mut::<Splash>().process_ui(world);
     let howtoplay_output = world.resource_mut::<HowToPlay>().process_ui(world);
@@ -34,6 +48,7 @@ pub fn update_menu(world: &World) {
     let credits_output = world.resource_mut::<CreditsUi>().process_ui(world);
     let team_select_output = world.resource_mut::<TeamSelect>().process_ui(world);
     let pause_ouptut = world.resource_mut::<Pause>().process_ui(world);
+    world.resource_mut::<ReconnectPrompt>().process_ui(world);
 
     let menu_state = *world.resource::<MenuState>();
     match menu_state {
@@ -84,7 +99,12 @@ pub fn update_menu(world: &World) {
             }
         }
         MenuState::InGame => {
-            if let Some(output) = world
+            let reconnect_output = world.resource_mut::<ReconnectPrompt>().process_input(world);
+            if let Some(output) = reconnect_output {
+                reconnect_transition(world, output)
+            } else if world.resource::<ReconnectPrompt>().visible() {
+                // The match stays paused until the gamepads are back.
+            } else if let Some(output) = world
                 .resource_mut::<Pause>()
                 .process_input(world)
                 .or(pause_ouptut)
@@ -94,15 +114,31 @@ pub fn update_menu(world: &World) {
         }
         #[cfg(not(target_arch = "wasm32"))]
         MenuState::InNetworkGame => {
-            if let Some(output) = world
+            if world.resource::<NetworkGraceUI>().visible {
+                let output = world
+                    .resource_mut::<NetworkGraceUI>()
+                    .process_input(world)
+                    .or(network_grace);
+                network::network_grace_transition(world, output)
+            } else if let Some(output) = world
                 .resource_mut::<NetworkQuit>()
                 .process_input(world)
                 .or(network_quit)
             {
                 network::network_quit_transition(world, output)
+            } else {
+                network::spectate_transition(world)
             }
         }
         #[cfg(not(target_arch = "wasm32"))]
+        MenuState::NetworkMatchDone => {
+            let output = world
+                .resource_mut::<NetworkMatchDone>()
+                .process_input(world)
+                .or(network_match_done);
+            network::network_match_done_transition(world, output)
+        }
+        #[cfg(not(target_arch = "wasm32"))]
         MenuState::LanSelect => {
             if let Some(output) = world
                 .resource_mut::<LanSelect>()
@@ -120,6 +156,22 @@ pub fn update_menu(world: &World) {
                 .or(lan_ui);
             network::lan_ui_transition(world, output)
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        MenuState::Online => {
+            let output = world
+                .resource_mut::<OnlineUI>()
+                .process_input(world)
+                .or(online_ui);
+            network::online_ui_transition(world, output)
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        MenuState::Lobby => {
+            let output = world
+                .resource_mut::<LobbyUI>()
+                .process_input(world)
+                .or(lobby_ui);
+            network::lobby_transition(world, output)
+        }
     }
 }
 
@@ -171,11 +223,18 @@ pub fn start_fade(world: &World, transition: FadeTransition) {
 
 pub fn play_leave(ui: &World) {
     *ui.resource_mut() = Pause::Disabled;
+    *ui.resource_mut() = ReconnectPrompt::default();
 
     #[cfg(not(target_arch = "wasm32"))]
     {
         ui.resource_mut::<Matchmaker>().lan_cancel();
+        ui.resource_mut::<Matchmaker>().online_cancel();
+        ui.resource_mut::<Matchmaker>().stop_spectators();
+        ui.resource_mut::<Matchmaker>().stop_watching();
         ui.resource_mut::<NetworkQuit>().visible = false;
+        ui.resource_mut::<NetworkMatchDone>().visible = false;
+        ui.resource_mut::<LobbyUI>().visible = false;
+        ui.resource_mut::<NetworkGraceUI>().visible = false;
     }
     let mut sessions = ui.resource_mut::<Sessions>();
     sessions.delete_play();
@@ -198,7 +257,7 @@ pub fn play_reset(ui: &World) {
 
 pub fn play_offline_prep(ui: &World) {
     let mut sessions = ui.resource_mut::<Sessions>();
-    let player_signs = ui
+    let mut player_signs = ui
         .resource::<TeamSelect>()
         .get_player_signs()
         .unwrap_or_else(|| {
@@ -206,9 +265,14 @@ pub fn play_offline_prep(ui: &World) {
             default()
         });
 
+    for GamepadRemap { from, to } in ui.resource::<ReconnectPrompt>().remaps.iter().copied() {
+        player_signs.reassign_gamepad(from, to);
+    }
+
     tracing::debug!(?player_signs, "creating offline PLAY session");
 
-    sessions.create_play(PlayMode::Offline(player_signs));
+    let tactics = ui.resource::<TeamSelect>().tactics;
+    sessions.create_play(PlayMode::Offline(player_signs, tactics));
     *ui.resource_mut() = MenuState::InGame;
     *ui.resource_mut() = Pause::Hidden;
 }
@@ -383,6 +447,36 @@ pub fn pause_transition(world: &World, output: PauseOutput) {
     }
 }
 
+pub fn reconnect_transition(world: &World, output: ReconnectOutput) {
+    match output {
+        ReconnectOutput::Show => {
+            let pause = std::mem::replace(&mut *world.resource_mut(), Pause::Disabled);
+            world.resource_mut::<ReconnectPrompt>().pause = pause;
+            // An open pause menu already hides the play UI and pauses the game.
+            if pause == Pause::Hidden {
+                pause_transition(world, PauseOutput::Show);
+            }
+        }
+        ReconnectOutput::Reassign(GamepadRemap { from, to }) => {
+            if let Some(mut mode) = world
+                .resource_mut::<Sessions>()
+                .get_session_resource_mut::<PlayMode>(PLAY)
+            {
+                if let PlayMode::Offline(players_info, _) = &mut *mode {
+                    players_info.reassign_gamepad(from, to);
+                }
+            }
+            let prompt = world.resource::<ReconnectPrompt>();
+            if !prompt.visible() {
+                *world.resource_mut() = prompt.pause;
+                if prompt.pause == Pause::Hidden {
+                    pause_transition(world, PauseOutput::Hide);
+                }
+            }
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod network {
     use super::*;
@@ -393,6 +487,7 @@ pub mod network {
     }
     pub fn lan_ui_leave(world: &World) {
         world.resource_mut::<Matchmaker>().lan_cancel();
+        world.resource_mut::<Matchmaker>().stop_watching();
         world.resource_mut::<Matchmaker>().disable_search();
         world.resource_mut::<LanUI>().visible = false;
     }
@@ -400,17 +495,108 @@ pub mod network {
         world.resource_mut::<Matchmaker>().enable_search();
         world.resource_mut::<LanUI>().visible = true;
     }
+    /// Fades back to the screen that the network match was found on.
+    pub fn leave_network_match(ui: &World) {
+        let online = *ui.resource::<Matchmaker>().online_status() == OnlineStatus::Ready;
+        let transition = if online {
+            FadeTransition {
+                hide: play_leave,
+                prep: OnlineUI::show_resource,
+                finish: MenuState::Online,
+            }
+        } else {
+            FadeTransition {
+                hide: play_leave,
+                prep: lan_ui_prep,
+                finish: MenuState::Lan,
+            }
+        };
+        start_fade(ui, transition);
+    }
+    pub fn online_ui_hide(world: &World) {
+        world.resource_mut::<OnlineUI>().visible = false;
+    }
+    pub fn online_ui_leave(world: &World) {
+        world.resource_mut::<Matchmaker>().online_cancel();
+        world.resource_mut::<OnlineUI>().visible = false;
+    }
     pub fn play_online_prep(ui: &World) {
         let socket = ui.resource::<Matchmaker>().network_match_socket().unwrap();
         let service_type = *ui.resource::<ServiceType>();
+        let slots = *ui.resource::<SlotAssignment>();
+        let rules = ui.resource::<MatchRules>().clone();
+        let delay = *ui.resource::<NetworkDelay>();
+        let resume = ui.resource_mut::<NetworkGraceUI>().resume.take();
+        // The host of a LAN match streams it to the spectators.
+        let mut matchmaker = ui.resource_mut::<Matchmaker>();
+        let spectators = (socket.player_idx() == 0
+            && *matchmaker.online_status() != OnlineStatus::Ready)
+            .then(|| {
+                let feed = SpectatorFeed::new(delay.max_prediction as usize);
+                let start = SpectatorStart {
+                    slots,
+                    rules: rules.clone(),
+                    resume: resume.clone(),
+                };
+                matchmaker.host_spectators(feed.clone(), start);
+                feed
+            });
         let mut sessions = ui.resource_mut::<Sessions>();
 
         sessions.create_play(PlayMode::Online {
             socket,
             service_type,
+            slots,
+            rules,
+            resume,
+            delay,
+            spectators,
+        });
+        *ui.resource_mut() = MenuState::InNetworkGame;
+    }
+    pub fn play_spectate_prep(ui: &World) {
+        let stream = ui
+            .resource::<Matchmaker>()
+            .spectator_stream()
+            .cloned()
+            .unwrap();
+        let start = stream.lock().unwrap().start().cloned().unwrap();
+        let mut sessions = ui.resource_mut::<Sessions>();
+
+        sessions.create_play(PlayMode::Spectate {
+            stream,
+            slots: start.slots,
+            rules: start.rules,
+            resume: start.resume,
         });
         *ui.resource_mut() = MenuState::InNetworkGame;
     }
+    /// Goes back to the LAN screen when the host stops streaming the match
+    /// being watched, once every frame it sent was played.
+    pub fn spectate_transition(ui: &World) {
+        let finished = ui
+            .resource::<Matchmaker>()
+            .spectator_stream()
+            .is_some_and(|stream| stream.lock().unwrap().is_finished());
+        if finished {
+            ui.resource_mut::<LanUI>().state = LanUIState::Disconnected;
+            leave_network_match(ui);
+        }
+    }
+    /// Restarts the play session on the same socket, keeping the slots the
+    /// peers agreed on.
+    pub fn network_rematch_hide(ui: &World) {
+        ui.resource_mut::<NetworkMatchDone>().visible = false;
+        ui.resource_mut::<NetworkQuit>().visible = false;
+        ui.resource_mut::<Sessions>().delete_play();
+    }
+    /// Restarts the play session on the socket the peers found each other
+    /// again on, see [`NetworkGraceUI`].
+    pub fn network_resume_hide(ui: &World) {
+        ui.resource_mut::<NetworkGraceUI>().visible = false;
+        ui.resource_mut::<NetworkQuit>().visible = false;
+        ui.resource_mut::<Sessions>().delete_play();
+    }
     pub fn lan_select_transition(world: &World, output: LanSelectOutput) {
         match output {
             LanSelectOutput::Exit => {
@@ -434,24 +620,74 @@ pub mod network {
                     },
                 );
             }
+            LanSelectOutput::Online(service) => {
+                world.resource_mut::<OnlineUI>().service = service;
+                start_fade(
+                    world,
+                    FadeTransition {
+                        hide: LanSelect::hide_resource,
+                        prep: OnlineUI::show_resource,
+                        finish: MenuState::Online,
+                    },
+                );
+            }
         }
     }
+    /// Fades into the lobby once the peers are connected.
+    fn enter_lobby(world: &World, service: ServiceType, local_players: u8, hide: fn(&World)) {
+        world.insert_resource(service);
+        world.resource_mut::<Matchmaker>().open_lobby(local_players);
+        start_fade(
+            world,
+            FadeTransition {
+                hide,
+                prep: LobbyUI::show_resource,
+                finish: MenuState::Lobby,
+            },
+        );
+    }
     pub fn lan_ui_transition(world: &World, output: Option<LanUIOutput>) {
-        let lan_ui = world.resource::<LanUI>();
-        let mut matchmaker = world.resource_mut::<Matchmaker>();
+        let mut lan_ui = world.resource_mut::<LanUI>();
 
-        if matchmaker.network_match_socket().is_some() {
-            world.insert_resource(lan_ui.service);
+        if world
+            .resource::<Matchmaker>()
+            .network_match_socket()
+            .is_some()
+        {
+            enter_lobby(
+                world,
+                lan_ui.service,
+                lan_ui.service.local_players(),
+                lan_ui_hide,
+            );
+            return;
+        }
+        let mut matchmaker = world.resource_mut::<Matchmaker>();
+        // The match to watch is set up once its host sent how it started.
+        let (started, failed) = matchmaker
+            .spectator_stream()
+            .map_or((false, false), |stream| {
+                let stream = stream.lock().unwrap();
+                (stream.start().is_some(), stream.failed())
+            });
+        if started {
             start_fade(
                 world,
                 FadeTransition {
                     hide: lan_ui_hide,
-                    prep: play_online_prep,
+                    prep: play_spectate_prep,
                     finish: MenuState::InNetworkGame,
                 },
             );
             return;
         }
+        if failed {
+            matchmaker.stop_watching();
+            lan_ui.state = LanUIState::Error(LanError::Failed);
+        }
+        if let Some(error) = matchmaker.take_lan_error() {
+            lan_ui.state = LanUIState::Error(error);
+        }
         let Some(output) = output else { return };
         match output {
             LanUIOutput::HostCancel => {
@@ -461,11 +697,39 @@ pub mod network {
                     matchmaker.lan_host();
                 }
             }
+            LanUIOutput::Machines(change) => {
+                let count = matchmaker.player_count() as i32 + change;
+                matchmaker.set_player_count(count.max(0) as u32);
+            }
+            LanUIOutput::Delay(direction) => {
+                let mut storage = world.resource_mut::<Storage>();
+                let settings = storage.get_or_insert_default_mut::<Settings>();
+                settings.input_delay.cycle(direction);
+                storage.save();
+            }
+            LanUIOutput::Prediction(change) => {
+                let mut storage = world.resource_mut::<Storage>();
+                let settings = storage.get_or_insert_default_mut::<Settings>();
+                settings.max_prediction.change(change);
+                storage.save();
+            }
+            LanUIOutput::Direct(address) => {
+                if let Err(err) = matchmaker.lan_join_direct(address) {
+                    tracing::warn!(%err, "failed to connect directly");
+                    lan_ui.state = LanUIState::BadAddress;
+                }
+            }
             LanUIOutput::Server(i) => {
                 if let Some(server) = matchmaker.lan_servers().get(i).cloned() {
                     matchmaker.lan_join(&server);
                 }
             }
+            LanUIOutput::Watch(i) => {
+                if let Err(err) = matchmaker.watch(i) {
+                    tracing::warn!(%err, "failed to watch the match");
+                    lan_ui.state = LanUIState::Error(LanError::Failed);
+                }
+            }
             LanUIOutput::Exit => start_fade(
                 world,
                 FadeTransition {
@@ -476,6 +740,246 @@ pub mod network {
             ),
         }
     }
+    pub fn online_ui_transition(world: &World, output: Option<OnlineUIOutput>) {
+        let online_ui = world.resource::<OnlineUI>();
+
+        if world
+            .resource::<Matchmaker>()
+            .network_match_socket()
+            .is_some()
+        {
+            enter_lobby(
+                world,
+                online_ui.service,
+                online_ui.service.local_players(),
+                online_ui_hide,
+            );
+            return;
+        }
+        let mut matchmaker = world.resource_mut::<Matchmaker>();
+        let Some(output) = output else { return };
+        match output {
+            OnlineUIOutput::Search(search) => {
+                let asset_server = world.resource::<AssetServer>();
+                let root = asset_server.root::<Data>();
+                matchmaker.online_search(&root.matchmaking_server, search);
+            }
+            OnlineUIOutput::Cancel => matchmaker.online_cancel(),
+            OnlineUIOutput::Exit => start_fade(
+                world,
+                FadeTransition {
+                    hide: online_ui_leave,
+                    prep: Splash::show_resource,
+                    finish: MenuState::Splash,
+                },
+            ),
+        }
+    }
+    pub fn lobby_transition(world: &World, output: Option<LobbyUIOutput>) {
+        let Some(socket) = world.resource::<Matchmaker>().network_match_socket() else {
+            return;
+        };
+        let (peer_left, error, start, host_name, rtt) = {
+            let mut matchmaker = world.resource_mut::<Matchmaker>();
+            let Some(lobby) = matchmaker.lobby_mut() else {
+                return;
+            };
+            match output {
+                Some(LobbyUIOutput::Side(direction)) => {
+                    let mut side = lobby.local().side;
+                    side.cycle(direction);
+                    lobby.set_side(side);
+                }
+                Some(LobbyUIOutput::ScoreTarget(change)) => {
+                    let mut rules = lobby.rules().clone();
+                    rules.change_score_target(change);
+                    lobby.set_rules(rules);
+                }
+                Some(LobbyUIOutput::Countdown(change)) => {
+                    let mut rules = lobby.rules().clone();
+                    rules.change_countdown(change);
+                    lobby.set_rules(rules);
+                }
+                Some(LobbyUIOutput::Formation(team, direction)) => {
+                    let mut rules = lobby.rules().clone();
+                    rules.change_formation(team, direction);
+                    lobby.set_rules(rules);
+                }
+                Some(LobbyUIOutput::Ready) => lobby.ready(),
+                Some(LobbyUIOutput::Leave) | None => {}
+            }
+            let host_name = lobby.peers()[0].as_ref().map(|host| host.name.clone());
+            let error = lobby.error;
+            (
+                lobby.peer_left,
+                error,
+                lobby.start(),
+                host_name,
+                lobby.rtt(),
+            )
+        };
+        if output == Some(LobbyUIOutput::Leave) {
+            PeerMessage::Leave.send(&socket);
+            leave_network_match(world);
+        } else if let Some(error) = error {
+            PeerMessage::Leave.send(&socket);
+            world.resource_mut::<LanUI>().state = LanUIState::Error(error);
+            leave_network_match(world);
+        } else if peer_left {
+            world.resource_mut::<LanUI>().state = LanUIState::Disconnected;
+            leave_network_match(world);
+        } else if let Some((slots, rules)) = start {
+            // Every peer starts with the same slots and rules once everyone is ready.
+            tracing::info!(?slots, ?rules, "every peer is ready");
+            world.resource_mut::<Matchmaker>().close_lobby();
+            world.resource_mut::<NetworkGraceUI>().host_name = host_name.unwrap_or_default();
+            let settings = world
+                .resource_mut::<Storage>()
+                .get_or_insert_default::<Settings>()
+                .clone();
+            let delay = NetworkDelay::new(&settings, rtt);
+            tracing::info!(?rtt, ?delay, "picked the input delay");
+            world.insert_resource(slots);
+            world.insert_resource(rules);
+            world.insert_resource(delay);
+            start_fade(
+                world,
+                FadeTransition {
+                    hide: LobbyUI::hide_resource,
+                    prep: play_online_prep,
+                    finish: MenuState::InNetworkGame,
+                },
+            );
+        }
+    }
+    pub fn network_match_done_transition(world: &World, output: Option<NetworkMatchDoneOutput>) {
+        let socket = world.resource::<Matchmaker>().network_match_socket();
+        // The socket is gone when the peers tried to find each other again
+        // after one dropped, see `NetworkGraceUI`.
+        if output == Some(NetworkMatchDoneOutput::Leave) {
+            if let Some(socket) = &socket {
+                PeerMessage::Leave.send(socket);
+            }
+            leave_network_match(world);
+            return;
+        }
+        let Some(socket) = socket else {
+            return;
+        };
+        let mut match_done = world.resource_mut::<NetworkMatchDone>();
+
+        for (peer, message) in PeerMessage::receive(&socket) {
+            match message {
+                PeerMessage::Rematch => match_done.vote(peer),
+                PeerMessage::Leave => {
+                    tracing::info!(peer, "peer left after the match");
+                    match_done.peer_left = true;
+                }
+                PeerMessage::Lobby(..)
+                | PeerMessage::Resume(..)
+                | PeerMessage::Ping(..)
+                | PeerMessage::Pong(..) => {}
+            }
+        }
+        match output {
+            Some(NetworkMatchDoneOutput::Rematch) => {
+                PeerMessage::Rematch.send(&socket);
+                match_done.vote(socket.player_idx());
+            }
+            Some(NetworkMatchDoneOutput::Leave) | None => {}
+        }
+        if match_done.all_voted(socket.player_count() as usize) {
+            tracing::info!("every peer voted for a rematch");
+            start_fade(
+                world,
+                FadeTransition {
+                    hide: network_rematch_hide,
+                    prep: play_online_prep,
+                    finish: MenuState::InNetworkGame,
+                },
+            );
+        }
+    }
+    pub fn network_grace_transition(world: &World, output: Option<NetworkGraceOutput>) {
+        let Some(grace) = NetworkGraceUI::grace(world) else {
+            return;
+        };
+        let decision = match output {
+            Some(NetworkGraceOutput::Leave) => {
+                leave_network_match(world);
+                return;
+            }
+            Some(NetworkGraceOutput::TakeWin) => Some(GraceDecision::TakeWin),
+            Some(NetworkGraceOutput::Cpus) => Some(GraceDecision::Cpus),
+            None => None,
+        };
+        let can_rejoin = world.resource::<NetworkGraceUI>().can_rejoin(world);
+        let mut grace_ui = world.resource_mut::<NetworkGraceUI>();
+        let mut matchmaker = world.resource_mut::<Matchmaker>();
+
+        if let Some(decision) = decision {
+            tracing::info!(?decision, "going on without the dropped peer");
+            grace.0.lock().unwrap().decision = Some(decision);
+            grace_ui.visible = false;
+            if grace_ui.regrouping {
+                grace_ui.regrouping = false;
+                matchmaker.lan_cancel();
+            }
+            return;
+        }
+        if !can_rejoin || grace.0.lock().unwrap().timed_out() {
+            return;
+        }
+        // The peers find each other again the same way they did the first
+        // time, with the host hosting under the same name.
+        if !grace_ui.regrouping {
+            grace_ui.regrouping = true;
+            matchmaker.lan_cancel();
+            if grace_ui.host {
+                matchmaker.set_player_count(2);
+                matchmaker.lan_host();
+            } else {
+                matchmaker.enable_search();
+            }
+            return;
+        }
+        let Some(socket) = matchmaker.network_match_socket() else {
+            if !grace_ui.host && !matchmaker.is_waiting() {
+                if let Some(server) = matchmaker.lan_server_named(&grace_ui.host_name) {
+                    matchmaker.lan_join(&server);
+                }
+            }
+            return;
+        };
+        // The host has the last say on the match, the other peer waits for it.
+        let resume = if grace_ui.host {
+            let mut sessions = world.resource_mut::<Sessions>();
+            let play = sessions.get_world(PLAY).unwrap();
+            let resume = play.run_system(capture_match, ());
+            PeerMessage::Resume(resume.clone()).send(&socket);
+            resume
+        } else {
+            let resume = PeerMessage::receive(&socket)
+                .into_iter()
+                .find_map(|(_, message)| match message {
+                    PeerMessage::Resume(resume) => Some(resume),
+                    _ => None,
+                });
+            let Some(resume) = resume else {
+                return;
+            };
+            resume
+        };
+        grace_ui.resume = Some(resume);
+        start_fade(
+            world,
+            FadeTransition {
+                hide: network_resume_hide,
+                prep: play_online_prep,
+                finish: MenuState::InNetworkGame,
+            },
+        );
+    }
     pub fn network_quit_transition(world: &World, output: NetworkQuitOutput) {
         match output {
             NetworkQuitOutput::Quit => {
@@ -15,6 +15,7 @@ impl SessionPlugin for MenuPlugin {
         session.install_plugin(HowToPlay::default());
         session.install_plugin(TeamSelect::default());
         session.install_plugin(Pause::default());
+        session.install_plugin(ReconnectPrompt::default());
         session.install_plugin(Splash::shown());
         session.install_plugin(Fade::new(
             0.5,
@@ -33,9 +34,15 @@ impl SessionPlugin for MenuPlugin {
             );
             session.install_plugin(LanSelect::default());
             session.install_plugin(LanUI::default());
+            session.install_plugin(LobbyUI::default());
+            session.install_plugin(OnlineUI::default());
             session.install_plugin(NetworkQuit::default());
+            session.install_plugin(NetworkMatchDone::default());
+            session.install_plugin(NetworkGraceUI::default());
+            session.add_system_to_stage(Update, toggle_network_diagnostics);
         }
 
+        session.install_plugin(InputDisplay::default());
         session.install_plugin(EguiSizePlugin::default());
         session.add_startup_system(ui::setup_egui);
         session.add_startup_system(set_volume_scales);
@@ -18,7 +18,23 @@ pub mod spawn;
 pub use spawn::prelude::*;
 pub mod scene;
 pub use scene::*;
+pub mod resume;
+pub use resume::prelude::*;
 pub mod cpu_player;
 pub use cpu_player::*;
+pub mod cpu_brain;
+pub use cpu_brain::prelude::*;
+pub mod observation;
+pub use observation::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bot;
+#[cfg(not(target_arch = "wasm32"))]
+pub use bot::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod grace;
+#[cfg(not(target_arch = "wasm32"))]
+pub use grace::*;
+pub mod tactics;
+pub use tactics::prelude::*;
 pub mod plugin;
 pub use plugin::*;
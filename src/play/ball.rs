@@ -1,7 +1,10 @@
 use super::*;
 
 pub mod prelude {
-    pub use super::{Ball, BallPlugin};
+    pub use super::{
+        Ball, BallBody, BallContact, BallIntercept, BallPlugin, BallPrediction, BallTrajectory,
+        BallTrajectoryPath, BALL_PREDICTION_FRAMES,
+    };
 }
 
 #[derive(HasSchema, Clone)]
@@ -24,6 +27,24 @@ impl Default for Ball {
         }
     }
 }
+impl Ball {
+    /// Moves the ball at `pos` by `delta_multiplier` frames through its [`BallBody`].
+    pub fn advance(
+        &mut self,
+        pos: &mut Vec3,
+        constants: &Constants,
+        delta_multiplier: f32,
+    ) -> BallContact {
+        let mut body = BallBody::new(pos.xy(), self);
+        let contact = body.advance(constants, delta_multiplier, self.owner.is_none());
+
+        self.velocity = body.velocity;
+        self.bounced = body.bounced;
+        pos.x = body.pos.x;
+        pos.y = body.pos.y;
+        contact
+    }
+}
 
 pub fn sprite() -> AnimatedSprite {
     AnimatedSprite {
@@ -37,7 +58,10 @@ pub fn sprite() -> AnimatedSprite {
 pub struct BallPlugin;
 impl SessionPlugin for BallPlugin {
     fn install(self, session: &mut SessionBuilder) {
-        session.add_system_to_stage(Update, update_ball);
+        session
+            .init_resource::<BallPrediction>()
+            .add_system_to_stage(Update, update_ball)
+            .add_system_to_stage(PostUpdate, update_ball_trajectory_path);
     }
 }
 
@@ -53,12 +77,8 @@ pub fn update_ball(
     mut transforms: CompMut<Transform>,
 ) {
     let Constants {
-        ball_bounds,
         player_radius,
         ball_radius,
-        ball_friction,
-        ball_etransfer,
-        ball_border_slide,
         dribble_smoothing,
         dribble_smoothing_threshold,
         ..
@@ -93,21 +113,21 @@ pub fn update_ball(
 
             // ball.velocity = movement; TODO: Maybe use this to spin ball while dribbling
             ball.bounced = false;
-        } else {
-            let pos = &mut transforms.get_mut(ball_entity).unwrap().translation;
+        }
 
-            // Friction
-            if ball.bounced {
-                ball.velocity *= ball_friction.powf(time.delta_multiplier());
-            }
-            if ball.velocity.length() < 0.01 {
-                ball.velocity = Vec2::ZERO;
-            }
+        let pos = &mut transforms.get_mut(ball_entity).unwrap().translation;
+        let contact = ball.advance(pos, &root.constant, time.delta_multiplier());
 
-            // Movement
-            pos.x += ball.velocity.x * time.delta_multiplier();
-            pos.y += ball.velocity.y * time.delta_multiplier();
+        if contact.wall && ball.owner.is_none() {
+            audio.play_sound(*ball_bounced, ball_bounced.volume());
         }
+        // Drift to make sure the ball doesn't get stuck on the side.
+        path_colors.get_mut(ball_entity).unwrap().color = if contact.border_slide {
+            Color::ORANGE
+        } else {
+            Color::GREEN
+        };
+
         // Animation Speed
         animation.fps = 10.0 * ball.velocity.length();
 
@@ -118,64 +138,403 @@ pub fn update_ball(
         if ball.sound_timer.just_finished() {
             audio.play_sound(*ball_spin, ball_spin.volume());
         }
+    }
+}
 
-        let pos = &mut transforms.get_mut(ball_entity).unwrap().translation;
+/// What the ball ran into during a [`BallBody::advance`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BallContact {
+    /// Whether it bounced off of a wall.
+    pub wall: bool,
+    /// Whether it is sliding along a side, see [`BallBody::apply_border_slide`].
+    pub border_slide: bool,
+}
 
-        // Bounds
-        let bounds = ball_bounds;
+/// The physical properties of a ball without the ECS.
+///
+/// [`update_ball`] moves the ball with [`Ball::advance`] so that anything
+/// predicting the ball with it, like [`BallTrajectory`], matches the real thing.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BallBody {
+    pub pos: Vec2,
+    pub velocity: Vec2,
+    /// Whether or not the ball has bounced since it was last kicked.
+    /// Friction only applies after the first bounce.
+    pub bounced: bool,
+}
+impl BallBody {
+    pub fn new(pos: Vec2, ball: &Ball) -> Self {
+        Self {
+            pos,
+            velocity: ball.velocity,
+            bounced: ball.bounced,
+        }
+    }
+    /// Applies friction and moves the ball by its velocity.
+    pub fn apply_movement(&mut self, constants: &Constants, delta_multiplier: f32) {
+        if self.bounced {
+            self.velocity *= constants.ball_friction.powf(delta_multiplier);
+        }
+        if self.velocity.length() < 0.01 {
+            self.velocity = Vec2::ZERO;
+        }
+        self.pos += self.velocity * delta_multiplier;
+    }
+    /// Bounces the ball off of the walls, returning whether or not
+    /// it hit one. `loose` is whether or not the ball is unowned,
+    /// which is the only time a wall counts as a bounce.
+    pub fn apply_bounds(&mut self, constants: &Constants, loose: bool) -> bool {
+        let Constants {
+            ball_bounds: bounds,
+            ball_radius,
+            ball_etransfer,
+            ..
+        } = *constants;
 
-        if (bounds.y - pos.y - ball_radius) < 0.0 {
-            pos.y = bounds.y - ball_radius;
-            ball.velocity.y = -ball.velocity.y;
-            ball.velocity.y *= ball_etransfer;
-            if ball.owner.is_none() {
-                ball.bounced = true;
-                audio.play_sound(*ball_bounced, ball_bounced.volume());
-            }
+        let mut hit = false;
+
+        if (bounds.y - self.pos.y - ball_radius) < 0.0 {
+            self.pos.y = bounds.y - ball_radius;
+            self.velocity.y = -self.velocity.y * ball_etransfer;
+            hit = true;
         }
-        if (-bounds.y - pos.y + ball_radius) > 0.0 {
-            pos.y = -bounds.y + ball_radius;
-            ball.velocity.y = -ball.velocity.y;
-            ball.velocity.y *= ball_etransfer;
-            ball.bounced = ball.owner.is_none();
-            if ball.owner.is_none() {
-                ball.bounced = true;
-                audio.play_sound(*ball_bounced, ball_bounced.volume());
-            }
+        if (-bounds.y - self.pos.y + ball_radius) > 0.0 {
+            self.pos.y = -bounds.y + ball_radius;
+            self.velocity.y = -self.velocity.y * ball_etransfer;
+            hit = true;
         }
-        if (bounds.x - pos.x - ball_radius) < 0.0 {
-            pos.x = bounds.x - ball_radius;
-            ball.velocity.x = -ball.velocity.x;
-            ball.velocity.x *= ball_etransfer;
-            ball.bounced = ball.owner.is_none();
-            if ball.owner.is_none() {
-                ball.bounced = true;
-                audio.play_sound(*ball_bounced, ball_bounced.volume());
-            }
+        if (bounds.x - self.pos.x - ball_radius) < 0.0 {
+            self.pos.x = bounds.x - ball_radius;
+            self.velocity.x = -self.velocity.x * ball_etransfer;
+            hit = true;
         }
-        if (-bounds.x - pos.x + ball_radius) > 0.0 {
-            pos.x = -bounds.x + ball_radius;
-            ball.velocity.x = -ball.velocity.x;
-            ball.velocity.x *= ball_etransfer;
-            ball.bounced = ball.owner.is_none();
-            if ball.owner.is_none() {
-                ball.bounced = true;
-                audio.play_sound(*ball_bounced, ball_bounced.volume());
-            }
+        if (-bounds.x - self.pos.x + ball_radius) > 0.0 {
+            self.pos.x = -bounds.x + ball_radius;
+            self.velocity.x = -self.velocity.x * ball_etransfer;
+            hit = true;
+        }
+        if hit && loose {
+            self.bounced = true;
         }
+        hit
+    }
+    /// Keeps the ball sliding along the sides so it can't get stuck there,
+    /// returning whether or not it is sliding.
+    pub fn apply_border_slide(&mut self, constants: &Constants) -> bool {
+        let Constants {
+            ball_bounds: bounds,
+            ball_radius,
+            ball_border_slide,
+            ..
+        } = *constants;
 
-        // Drift to make sure the ball doesn't get stuck on the side.
-        if pos.x + ball_radius + ball_radius > bounds.x
-            || pos.x - ball_radius - ball_radius < -bounds.x
+        if self.pos.x + ball_radius + ball_radius > bounds.x
+            || self.pos.x - ball_radius - ball_radius < -bounds.x
         {
-            let signum = ball.velocity.x.signum();
-            let abs = ball.velocity.x.abs();
+            let signum = self.velocity.x.signum();
+            let abs = self.velocity.x.abs();
             let mag = abs.max(ball_border_slide);
-            let value = mag * signum;
-            ball.velocity.x = value;
-            path_colors.get_mut(ball_entity).unwrap().color = Color::ORANGE;
+            self.velocity.x = mag * signum;
+            true
+        } else {
+            false
+        }
+    }
+    /// Moves the ball by `delta_multiplier` frames and keeps it in bounds.
+    ///
+    /// `loose` is whether or not the ball is unowned, a dribbled ball is only
+    /// kept in bounds since its owner moves it.
+    pub fn advance(
+        &mut self,
+        constants: &Constants,
+        delta_multiplier: f32,
+        loose: bool,
+    ) -> BallContact {
+        if loose {
+            self.apply_movement(constants, delta_multiplier);
+        }
+        BallContact {
+            wall: self.apply_bounds(constants, loose),
+            border_slide: self.apply_border_slide(constants),
+        }
+    }
+    /// Steps the loose ball forward one frame at the target frame rate,
+    /// the same way [`update_ball`] does.
+    pub fn step(&mut self, constants: &Constants) {
+        self.advance(constants, 1.0, true);
+    }
+}
+
+/// The amount of frames a [`BallTrajectory`] looks ahead by default.
+pub const BALL_PREDICTION_FRAMES: usize = 120;
+
+/// The predicted positions of a loose ball, one for each frame ahead.
+#[derive(Clone, Debug, Default)]
+pub struct BallTrajectory {
+    /// The position of the ball after `index + 1` frames.
+    pub points: Vec<Vec2>,
+}
+
+/// A point on a [`BallTrajectory`] that can be reached in time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BallIntercept {
+    /// The amount of frames until the ball is at [`Self::pos`].
+    pub frame: usize,
+    pub pos: Vec2,
+}
+
+impl BallTrajectory {
+    /// Simulates the loose ball `frames` frames forward with the same physics as [`update_ball`],
+    /// including the bounces off the walls.
+    pub fn predict(mut body: BallBody, constants: &Constants, frames: usize) -> Self {
+        let mut points = Vec::with_capacity(frames);
+        for _ in 0..frames {
+            body.step(constants);
+            points.push(body.pos);
+            if body.velocity == Vec2::ZERO {
+                break;
+            }
+        }
+        Self { points }
+    }
+    /// Where the ball comes to rest or the last predicted position.
+    pub fn end(&self) -> Option<Vec2> {
+        self.points.last().copied()
+    }
+    /// The first point on the trajectory that something at `pos` moving
+    /// `speed` units per frame can get within `reach` of before the ball does.
+    pub fn intercept(&self, pos: Vec2, speed: f32, reach: f32) -> Option<BallIntercept> {
+        self.points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| BallIntercept {
+                frame: i + 1,
+                pos: *point,
+            })
+            .find(|intercept| intercept.pos.distance(pos) - reach <= speed * intercept.frame as f32)
+    }
+    /// The first point where the ball crosses the vertical line at `x`.
+    pub fn crossing_x(&self, x: f32) -> Option<BallIntercept> {
+        self.points
+            .windows(2)
+            .enumerate()
+            .find(|(_, pair)| (pair[0].x - x).signum() != (pair[1].x - x).signum())
+            .map(|(i, pair)| BallIntercept {
+                frame: i + 2,
+                pos: pair[1],
+            })
+    }
+}
+
+/// The last [`BallTrajectory`] predicted for the ball.
+///
+/// The cpus and the debug line all predict the same ball, so the trajectory
+/// is only simulated again once the ball has moved, which is once a frame.
+#[derive(HasSchema, Clone, Default)]
+pub struct BallPrediction {
+    body: Option<BallBody>,
+    trajectory: BallTrajectory,
+}
+impl BallPrediction {
+    /// The trajectory of the loose ball from `body`, [`BALL_PREDICTION_FRAMES`] long.
+    pub fn trajectory(&mut self, body: BallBody, constants: &Constants) -> &BallTrajectory {
+        if self.body != Some(body) {
+            self.trajectory = BallTrajectory::predict(body, constants, BALL_PREDICTION_FRAMES);
+            self.body = Some(body);
+        }
+        &self.trajectory
+    }
+}
+
+/// Marks the debug line that shows the [`BallTrajectory`] of the ball.
+#[derive(HasSchema, Clone, Default)]
+pub struct BallTrajectoryPath;
+
+pub fn update_ball_trajectory_path(
+    entities: Res<Entities>,
+    root: Root<Data>,
+    balls: Comp<Ball>,
+    transforms: Comp<Transform>,
+    markers: Comp<BallTrajectoryPath>,
+    mut path2ds: CompMut<Path2d>,
+    mut prediction: ResMut<BallPrediction>,
+) {
+    let Some((ball_e, ball)) = entities.iter_with(&balls).next() else {
+        return;
+    };
+    let ball_pos = transforms.get(ball_e).unwrap().translation.xy();
+
+    for (_e, (_marker, path2d)) in entities.iter_with((&markers, &mut path2ds)) {
+        path2d.points = if ball.owner.is_none() && ball.velocity != Vec2::ZERO {
+            let trajectory = prediction.trajectory(BallBody::new(ball_pos, ball), &root.constant);
+            [&[ball_pos][..], &trajectory.points].concat()
         } else {
-            path_colors.get_mut(ball_entity).unwrap().color = Color::GREEN;
+            Vec::new()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ball constants from `game.yaml`.
+    fn constants() -> Constants {
+        Constants {
+            ball_bounds: Vec2::new(179.5, 96.5),
+            ball_radius: 8.0,
+            ball_friction: 0.98,
+            ball_etransfer: 0.8,
+            ball_border_slide: 0.1,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn predict_matches_update_ball() {
+        let constants = constants();
+        let mut ball = Ball {
+            velocity: Vec2::new(5.0, 3.5),
+            ..default()
+        };
+        let mut pos = Vec3::new(-20.0, 10.0, 0.0);
+        let trajectory = BallTrajectory::predict(
+            BallBody::new(pos.xy(), &ball),
+            &constants,
+            BALL_PREDICTION_FRAMES,
+        );
+        assert_eq!(trajectory.points.len(), BALL_PREDICTION_FRAMES);
+
+        // Moved the way `update_ball` moves it, bounces and all.
+        for point in &trajectory.points {
+            ball.advance(&mut pos, &constants, 1.0);
+            assert_eq!(*point, pos.xy());
+        }
+        assert!(ball.bounced);
+    }
+
+    #[test]
+    fn prediction_is_shared() {
+        let constants = constants();
+        let start = BallBody {
+            pos: Vec2::ZERO,
+            velocity: Vec2::new(3.0, 0.0),
+            bounced: false,
+        };
+        let mut prediction = BallPrediction::default();
+        let first = prediction.trajectory(start, &constants).clone();
+        assert_eq!(
+            first.points,
+            BallTrajectory::predict(start, &constants, BALL_PREDICTION_FRAMES).points
+        );
+
+        // The same ball isn't simulated again...
+        prediction.trajectory.points.clear();
+        assert!(prediction.trajectory(start, &constants).points.is_empty());
+        // ...until it moves.
+        let moved = BallBody {
+            pos: first.points[0],
+            ..start
+        };
+        assert_eq!(
+            prediction.trajectory(moved, &constants).points[0],
+            first.points[1]
+        );
+    }
+
+    #[test]
+    fn bounce() {
+        let constants = constants();
+        let wall = constants.ball_bounds.x - constants.ball_radius;
+        let start = BallBody {
+            pos: Vec2::new(150.0, 0.0),
+            velocity: Vec2::new(4.0, 0.0),
+            bounced: false,
+        };
+        let trajectory = BallTrajectory::predict(start, &constants, BALL_PREDICTION_FRAMES);
+
+        // The ball stops at the wall and comes back slower.
+        let hit = trajectory.points.iter().position(|point| point.x == wall);
+        let hit = hit.unwrap();
+        assert!(trajectory.points[..hit].iter().all(|point| point.x < wall));
+        let back = trajectory.points[hit] - trajectory.points[hit + 1];
+        assert!(back.x > 0.0 && back.x < 4.0 * constants.ball_etransfer);
+
+        // It crosses on the way there first, then on the way back.
+        let there = trajectory.crossing_x(160.0).unwrap();
+        assert_eq!(there.frame, 3);
+        assert_eq!(there.pos, Vec2::new(162.0, 0.0));
+        let past_the_wall = trajectory.points.split_at(hit + 1).1;
+        let back = BallTrajectory {
+            points: past_the_wall.to_vec(),
+        };
+        assert!(back.crossing_x(160.0).unwrap().pos.x < 160.0);
+
+        // Friction only starts after the bounce, so the ball comes to rest.
+        let unbounced = BallBody {
+            pos: Vec2::new(0.0, 0.0),
+            velocity: Vec2::new(0.0, 1.0),
+            bounced: false,
+        };
+        let to_rest = BallTrajectory::predict(start, &constants, 1000);
+        assert!(to_rest.points.len() < 1000);
+        assert!(BallTrajectory::predict(unbounced, &constants, 80)
+            .points
+            .windows(2)
+            .all(|pair| pair[1].y - pair[0].y == 1.0));
+    }
+
+    #[test]
+    fn border_slide() {
+        let constants = constants();
+        let bounds = constants.ball_bounds;
+        let radius = constants.ball_radius;
+        // Barely moving, right next to the side.
+        let start = BallBody {
+            pos: Vec2::new(bounds.x - radius * 2.0 + 1.5, 20.0),
+            velocity: Vec2::new(0.02, 0.0),
+            bounced: false,
+        };
+        let trajectory = BallTrajectory::predict(start, &constants, 1000);
+
+        // The slide keeps it moving into the wall, off of it and away from the side.
+        let wall = bounds.x - radius;
+        assert!(trajectory.points.iter().any(|point| point.x == wall));
+        let end = trajectory.end().unwrap();
+        assert!(end.x + radius * 2.0 < bounds.x);
+        assert!(trajectory.points.len() < 1000);
+
+        let mut body = start;
+        for point in &trajectory.points {
+            body.step(&constants);
+            assert_eq!(*point, body.pos);
         }
     }
+
+    #[test]
+    fn intercept() {
+        let constants = constants();
+        let start = BallBody {
+            pos: Vec2::ZERO,
+            velocity: Vec2::new(3.0, 0.0),
+            bounced: false,
+        };
+        let trajectory = BallTrajectory::predict(start, &constants, BALL_PREDICTION_FRAMES);
+
+        // Standing on the path reaches the ball as soon as it arrives.
+        let intercept = trajectory
+            .intercept(Vec2::new(30.0, 0.0), 1.0, 0.0)
+            .unwrap();
+        assert!(intercept.pos.x <= 30.0);
+        let reachable =
+            |point: &Vec2, frame: usize| point.distance(Vec2::new(30.0, 0.0)) <= frame as f32;
+        assert!(reachable(&intercept.pos, intercept.frame));
+        assert!(trajectory.points[..intercept.frame - 1]
+            .iter()
+            .enumerate()
+            .all(|(i, point)| !reachable(point, i + 1)));
+
+        // Too slow and too far never gets there.
+        assert_eq!(trajectory.intercept(Vec2::new(0.0, 90.0), 0.1, 0.0), None);
+    }
 }
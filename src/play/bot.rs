@@ -0,0 +1,291 @@
+//! Lets an external process drive a cpu player over a local socket.
+//!
+//! The game connects to a bot listening on a TCP or Unix socket. Every frame
+//! it sends one JSON line with the slot being driven and the whole
+//! [`GameObservation`]:
+//!
+//! ```json
+//! {"slot":"A1","game":{"frame":1,"players":[...],"ball":{...},"pins":[...],"score_a":0,"score_b":0}}
+//! ```
+//!
+//! The bot answers with one JSON line per observation, any missing field
+//! being `0`/`false`:
+//!
+//! ```json
+//! {"x":1.0,"y":0.0,"shoot":false,"pass":false,"tackle":false}
+//! ```
+//!
+//! In lockstep mode the game waits for the answer before the frame goes on,
+//! otherwise the latest answer that has arrived is used.
+//!
+//! Bots are [`CpuBrain`]s, so they are registered in [`CpuBrains`] and
+//! selected with [`PlayerInfo::CPU`].
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub mod prelude {
+    pub use super::{BotAction, BotAddress, ExternalBrain};
+}
+
+/// Where the bot is listening.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BotAddress {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+impl FromStr for BotAddress {
+    type Err = String;
+
+    /// Parses `tcp:<host>:<port>`, `unix:<path>`, or just `<host>:<port>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            #[cfg(unix)]
+            return Ok(Self::Unix(path.into()));
+            #[cfg(not(unix))]
+            return Err(format!("unix sockets are not supported here: {path}"));
+        }
+        let address = s.strip_prefix("tcp:").unwrap_or(s);
+        if address.contains(':') {
+            Ok(Self::Tcp(address.to_string()))
+        } else {
+            Err(format!(
+                "expected `tcp:<host>:<port>` or `unix:<path>`, got `{s}`"
+            ))
+        }
+    }
+}
+impl std::fmt::Display for BotAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotAddress::Tcp(address) => write!(f, "tcp:{address}"),
+            #[cfg(unix)]
+            BotAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// The input sent back by the bot.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotAction {
+    pub x: f32,
+    pub y: f32,
+    pub shoot: bool,
+    pub pass: bool,
+    pub tackle: bool,
+}
+impl BotAction {
+    pub fn apply(&self, input: &mut PlayInput) {
+        input.x = self.x.clamp(-1.0, 1.0);
+        input.y = self.y.clamp(-1.0, 1.0);
+        input.shoot.apply_bool(self.shoot);
+        input.pass.apply_bool(self.pass);
+        input.tackle.apply_bool(self.tackle);
+    }
+}
+
+#[derive(Serialize)]
+struct BotMessage<'a> {
+    slot: String,
+    game: &'a GameObservation,
+}
+
+enum BotStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+impl BotStream {
+    fn connect(address: &BotAddress) -> std::io::Result<Self> {
+        match address {
+            BotAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_nodelay(true)?;
+                Ok(Self::Tcp(stream))
+            }
+            #[cfg(unix)]
+            BotAddress::Unix(path) => {
+                Ok(Self::Unix(std::os::unix::net::UnixStream::connect(path)?))
+            }
+        }
+    }
+    fn try_clone(&self) -> std::io::Result<Self> {
+        match self {
+            BotStream::Tcp(stream) => stream.try_clone().map(Self::Tcp),
+            #[cfg(unix)]
+            BotStream::Unix(stream) => stream.try_clone().map(Self::Unix),
+        }
+    }
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        match self {
+            BotStream::Tcp(stream) => stream.set_read_timeout(timeout),
+            #[cfg(unix)]
+            BotStream::Unix(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+}
+impl Read for BotStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            BotStream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            BotStream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+impl Write for BotStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            BotStream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            BotStream::Unix(stream) => stream.write(buf),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            BotStream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            BotStream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+struct BotConnection {
+    writer: BotStream,
+    reader: BufReader<BotStream>,
+    /// A partially received line.
+    pending: String,
+}
+impl BotConnection {
+    fn connect(address: &BotAddress, lockstep: bool) -> std::io::Result<Self> {
+        let writer = BotStream::connect(address)?;
+        let reader = writer.try_clone()?;
+        // Without lockstep the read only waits for as long as it takes to
+        // check if something arrived.
+        reader.set_read_timeout((!lockstep).then_some(Duration::from_millis(1)))?;
+        Ok(Self {
+            writer,
+            reader: BufReader::new(reader),
+            pending: String::new(),
+        })
+    }
+    fn send(&mut self, message: &BotMessage) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        self.writer.flush()
+    }
+    /// Reads every complete line that has arrived, returning the latest action.
+    fn receive(&mut self, lockstep: bool) -> std::io::Result<Option<BotAction>> {
+        let mut action = None;
+        loop {
+            match self.reader.read_line(&mut self.pending) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(_) => {
+                    if !self.pending.ends_with('\n') {
+                        continue;
+                    }
+                    match serde_json::from_str::<BotAction>(self.pending.trim()) {
+                        Ok(new_action) => action = Some(new_action),
+                        Err(err) => {
+                            tracing::warn!(%err, line = %self.pending.trim(), "invalid bot action")
+                        }
+                    }
+                    self.pending.clear();
+                    if lockstep {
+                        return Ok(action);
+                    }
+                }
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(action);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// A [`CpuBrain`] that asks an external process for its input.
+///
+/// Each registered brain has its own connection, so register one per
+/// player that a bot should drive.
+pub struct ExternalBrain {
+    pub address: BotAddress,
+    /// Whether or not the game waits for the bot every frame.
+    pub lockstep: bool,
+    connection: Mutex<Option<BotConnection>>,
+    /// The last action the bot sent.
+    action: Mutex<Option<BotAction>>,
+}
+impl ExternalBrain {
+    /// How long to wait between connection attempts in lockstep mode.
+    pub const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+    pub fn new(address: BotAddress, lockstep: bool) -> Self {
+        Self {
+            address,
+            lockstep,
+            connection: Mutex::new(None),
+            action: Mutex::new(None),
+        }
+    }
+    /// Connects if there isn't a connection yet, waiting for the bot in lockstep mode.
+    fn ensure_connected(&self, connection: &mut Option<BotConnection>) {
+        while connection.is_none() {
+            match BotConnection::connect(&self.address, self.lockstep) {
+                Ok(new) => {
+                    tracing::info!(address = %self.address, "connected to bot");
+                    *connection = Some(new);
+                }
+                Err(err) if self.lockstep => {
+                    tracing::debug!(%err, address = %self.address, "waiting for bot");
+                    std::thread::sleep(Self::RETRY_DELAY);
+                }
+                Err(err) => {
+                    tracing::debug!(%err, address = %self.address, "bot is not reachable");
+                    return;
+                }
+            }
+        }
+    }
+}
+impl CpuBrain for ExternalBrain {
+    fn think(&self, observation: &CpuObservation) -> PlayInput {
+        let mut connection = self.connection.lock().unwrap();
+        let mut action = self.action.lock().unwrap();
+        let mut input = observation.input;
+
+        self.ensure_connected(&mut connection);
+
+        if let Some(bot) = connection.as_mut() {
+            let message = BotMessage {
+                slot: format!("{:?}", observation.senses.slot),
+                game: observation.game,
+            };
+            let result = bot.send(&message).and_then(|_| bot.receive(self.lockstep));
+
+            match result {
+                Ok(Some(new_action)) => *action = Some(new_action),
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::warn!(%err, address = %self.address, "lost connection to bot");
+                    *connection = None;
+                    *action = None;
+                }
+            }
+        }
+
+        action.unwrap_or_default().apply(&mut input);
+        input
+    }
+    fn observes_game(&self) -> bool {
+        true
+    }
+}
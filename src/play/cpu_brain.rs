@@ -0,0 +1,134 @@
+use super::*;
+use std::sync::Arc;
+
+pub mod prelude {
+    pub use super::{CpuBrain, CpuBrains, CpuObservation, DefaultBrain};
+}
+
+/// Everything a [`CpuBrain`] gets to see when deciding on an input.
+#[derive(Clone, Debug)]
+pub struct CpuObservation<'a> {
+    pub senses: CpuSenses,
+    /// The state chosen by the cpu state machine.
+    ///
+    /// Brains are free to ignore it.
+    pub cpu_state: Ustr,
+    /// The input decided on the previous frame.
+    pub input: PlayInput,
+    /// The whole match as of the start of the frame.
+    ///
+    /// It's only kept up to date while a brain [observes the game](CpuBrain::observes_game).
+    pub game: &'a GameObservation,
+}
+
+/// Decides the input of a cpu player.
+///
+/// Register brains in the [`CpuBrains`] shared resource and select them by
+/// name with [`PlayerInfo::CPU`].
+pub trait CpuBrain: Send + Sync + 'static {
+    fn think(&self, observation: &CpuObservation) -> PlayInput;
+
+    /// Whether the brain reads [`CpuObservation::game`].
+    fn observes_game(&self) -> bool {
+        false
+    }
+}
+
+/// The original cpu logic, following the cpu state machine.
+pub struct DefaultBrain;
+impl DefaultBrain {
+    pub const NAME: &'static str = "default";
+}
+impl CpuBrain for DefaultBrain {
+    fn think(&self, observation: &CpuObservation) -> PlayInput {
+        let senses = &observation.senses;
+        let mut input = observation.input;
+
+        match observation.cpu_state {
+            s if s == cpu_player::state::chase() => chase_input(senses, &mut input),
+            s if s == cpu_player::state::guard() => guard_input(senses, &mut input),
+            s if s == cpu_player::state::drive() => drive_input(senses, &mut input),
+            s if s == cpu_player::state::aim() => aim_input(senses, &mut input),
+            s if s == cpu_player::state::fire() => fire_input(senses, &mut input),
+            s if s == cpu_player::state::catch() => catch_input(senses, &mut input),
+            s if s == cpu_player::state::save() => save_input(senses, &mut input),
+            _ => {}
+        }
+        input
+    }
+}
+
+/// The registry of [`CpuBrain`]s by name.
+///
+/// This is meant to be a shared resource so that brains can be registered
+/// once for the whole game. Sessions without it only have the [`DefaultBrain`].
+#[derive(HasSchema, Clone)]
+pub struct CpuBrains {
+    brains: HashMap<Ustr, Arc<dyn CpuBrain>>,
+}
+impl Default for CpuBrains {
+    fn default() -> Self {
+        let mut brains = Self {
+            brains: HashMap::default(),
+        };
+        brains.register(DefaultBrain::NAME, DefaultBrain);
+        brains
+    }
+}
+impl CpuBrains {
+    pub fn default_name() -> Ustr {
+        ustr(DefaultBrain::NAME)
+    }
+    pub fn register(&mut self, name: &str, brain: impl CpuBrain) -> &mut Self {
+        self.brains.insert(ustr(name), Arc::new(brain));
+        self
+    }
+    pub fn get(&self, name: Ustr) -> Option<Arc<dyn CpuBrain>> {
+        self.brains.get(&name).cloned()
+    }
+    pub fn names(&self) -> impl Iterator<Item = Ustr> + '_ {
+        self.brains.keys().copied()
+    }
+}
+
+/// Gets the brain by name, falling back to the [`DefaultBrain`]
+/// if there is no [`CpuBrains`] resource or the name isn't registered.
+pub fn get_brain(world: &World, name: Ustr) -> Arc<dyn CpuBrain> {
+    let brain = world
+        .get_resource::<CpuBrains>()
+        .and_then(|brains| brains.get(name));
+
+    brain.unwrap_or_else(|| {
+        if name != CpuBrains::default_name() {
+            tracing::warn!(%name, "cpu brain not registered, using the default");
+        }
+        Arc::new(DefaultBrain)
+    })
+}
+
+pub fn think_cpu_players(world: &World) {
+    let game = world.resource::<GameObservation>();
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        let Some(cpu_state) = cpu_state(world, player_e) else {
+            continue;
+        };
+        let (brain, input) = {
+            let cpu_players = world.component::<CpuPlayer>();
+            let cpu_player = cpu_players.get(player_e).unwrap();
+            (cpu_player.brain, cpu_player.input)
+        };
+        let observation = CpuObservation {
+            senses: world.run_system(cpu_senses, player_e),
+            cpu_state,
+            input,
+            game: &game,
+        };
+        let input = get_brain(world, brain).think(&observation);
+
+        world
+            .component_mut::<CpuPlayer>()
+            .get_mut(player_e)
+            .unwrap()
+            .input = input;
+    }
+}
@@ -14,6 +14,11 @@ pub fn get_cpu_input(world: &World, slot: PlayerSlot) -> PlayInputDense {
     }
 }
 
+pub mod state {
+    crate::states![chase, guard, drive, aim, fire, catch, save];
+}
+
+/// Marks the entity holding the [`State`] of a [`CpuPlayer`].
 #[derive(HasSchema, Clone, Default)]
 pub struct CpuPlayerState;
 
@@ -28,21 +33,301 @@ pub struct CpuPlayer {
     // to seem like humans.
     /// The desired input of the cpu player.
     pub input: PlayInput,
+    /// The job of the cpu within its team, assigned by the [`TacticsPlugin`].
+    pub role: CpuRole,
+    /// The name of the [`CpuBrain`] deciding the input, see [`CpuBrains`].
+    pub brain: Ustr,
 }
 
+/// Runs the cpu players as a state machine on top of the [`StatePlugin`].
+///
+/// Each cpu state has a transition system that runs in the [`StateStage`],
+/// the same way the [`PlayerPlugin`] handles player states. The state lives
+/// on a separate entity ([`CpuPlayer::state_e`]) so it doesn't collide with
+/// the player's own [`State`].
+///
+/// The inputs are then decided in [`PreUpdate`] by the [`CpuBrain`] each
+/// cpu was spawned with.
+///
+/// The current cpu state is drawn as a colored ring around each cpu with
+/// the rest of the debug lines (toggled with `F3`).
 pub struct CpuPlayerPlugin;
 impl SessionPlugin for CpuPlayerPlugin {
     fn install(self, session: &mut SessionBuilder) {
-        session.add_system_to_stage(PreUpdate, apply_cpu_inputs);
+        session
+            .init_resource::<TeamCpuParams>()
+            .add_system_to_stage(StateStage, chase_transition)
+            .add_system_to_stage(StateStage, guard_transition)
+            .add_system_to_stage(StateStage, drive_transition)
+            .add_system_to_stage(StateStage, aim_transition)
+            .add_system_to_stage(StateStage, fire_transition)
+            .add_system_to_stage(StateStage, catch_transition)
+            .add_system_to_stage(StateStage, save_transition)
+            .add_system_to_stage(PreUpdate, think_cpu_players)
+            .add_system_to_stage(PostUpdate, cpu_state_graphics);
+    }
+}
+
+/// Overrides the [`CpuParams`] from the cpu asset for a team.
+///
+/// This is how different parameters are put against each other when tuning.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct TeamCpuParams {
+    pub a: Option<CpuParams>,
+    pub b: Option<CpuParams>,
+}
+impl TeamCpuParams {
+    pub fn get(&self, team: Team) -> Option<CpuParams> {
+        match team {
+            Team::A => self.a,
+            Team::B => self.b,
+        }
+    }
+}
+
+/// Gets the current cpu state of the player if it is a cpu.
+pub fn cpu_state(world: &World, player_e: Entity) -> Option<Ustr> {
+    let cpu_players = world.component::<CpuPlayer>();
+    let cpu_player = cpu_players.get(player_e)?;
+    world
+        .component::<State>()
+        .get(cpu_player.state_e)
+        .map(|state| state.current)
+}
+
+/// Sets the cpu state of the player if a transition was chosen.
+fn set_cpu_state(world: &World, player_e: Entity, next: Option<Ustr>) {
+    let Some(next) = next else {
+        return;
+    };
+    let cpu_players = world.component::<CpuPlayer>();
+    let cpu_player = cpu_players.get(player_e).unwrap();
+    let mut states = world.component_mut::<State>();
+    let state = states.get_mut(cpu_player.state_e).unwrap();
+
+    if state.current != next {
+        let slot = world.component::<Player>().get(player_e).unwrap().slot;
+        tracing::trace!(?slot, from = ?state.current, to = ?next, "cpu state transition");
+        state.current = next;
     }
 }
-pub fn apply_cpu_inputs(world: &World, player_ent_signs: Res<PlayerEntSigns>) {
-    for entity in player_ent_signs.entities() {
-        world.run_system(apply_cpu_input, entity);
+
+/// Everything a cpu considers when deciding on a state or input,
+/// taken from the perspective of one cpu player.
+#[derive(Clone, Debug)]
+pub struct CpuSenses {
+    pub slot: PlayerSlot,
+    /// The angle the cpu's player is facing.
+    pub angle: Vec2,
+    /// The angle the cpu's player started its current action at, like a shot.
+    pub action_angle: Vec2,
+    /// The state of the cpu's player, not the cpu state.
+    pub player_state: Ustr,
+    pub role: CpuRole,
+    pub formation: Formation,
+    pub self_e: Entity,
+    pub self_pos: Vec2,
+    pub partner_e: Entity,
+    pub partner_pos: Vec2,
+    pub ball_pos: Vec2,
+    pub ball_owner: Option<Entity>,
+    pub owner_pos: Option<Vec2>,
+    /// The predicted path of the ball while it is loose.
+    pub ball_trajectory: BallTrajectory,
+    /// Where self can first get to the loose ball.
+    pub ball_intercept: Option<BallIntercept>,
+    /// Where the partner can first get to the loose ball.
+    pub partner_ball_intercept: Option<BallIntercept>,
+    pub closest_enemy_pos: Vec2,
+    /// The enemy that is the furthest into the cpu's defending side.
+    pub offensive_enemy_pos: Vec2,
+    pub closest_enemy_pin_pos: Vec2,
+    /// An enemy is close enough to tackle the partner.
+    pub partner_is_tackleable: bool,
+    /// An enemy is closing in on the partner.
+    pub partner_is_pressured: bool,
+
+    pub player_bounds: Vec2,
+    pub player_radius: f32,
+    pub ball_radius: f32,
+    pub tackle_distance: f32,
+    pub params: CpuParams,
+}
+impl CpuSenses {
+    pub fn attacking_direction(&self) -> f32 {
+        self.slot.team().attacking_direction()
+    }
+    pub fn defending_direction(&self) -> f32 {
+        self.slot.team().defending_direction()
+    }
+    pub fn direction_of(&self, pos: Vec2) -> Vec2 {
+        (pos - self.self_pos).normalize_or_zero()
+    }
+    pub fn owns_ball(&self) -> bool {
+        self.ball_owner == Some(self.self_e)
+    }
+    pub fn partner_owns_ball(&self) -> bool {
+        self.ball_owner == Some(self.partner_e)
+    }
+    pub fn enemy_owns_ball(&self) -> bool {
+        self.ball_owner
+            .is_some_and(|owner| owner != self.self_e && owner != self.partner_e)
+    }
+    /// The position of whoever owns the ball or the ball itself if it is loose.
+    pub fn target_pos(&self) -> Vec2 {
+        self.owner_pos.unwrap_or(self.ball_pos)
+    }
+    /// Where to run to get the ball, cutting off the ball when it is loose.
+    pub fn intercept_pos(&self) -> Vec2 {
+        self.owner_pos
+            .or(self.ball_intercept.map(|intercept| intercept.pos))
+            .or(self.ball_trajectory.end())
+            .unwrap_or(self.ball_pos)
+    }
+    /// Self gets to the target first or is the primary player slot when equal.
+    pub fn has_dibs(&self) -> bool {
+        if self.ball_owner.is_none() && !self.ball_trajectory.points.is_empty() {
+            let frames = self.ball_intercept.map_or(usize::MAX, |i| i.frame);
+            let partner_frames = self.partner_ball_intercept.map_or(usize::MAX, |i| i.frame);
+            return frames < partner_frames || frames == partner_frames && self.slot.is_primary();
+        }
+        let target_pos = self.target_pos();
+        target_pos.distance(self.self_pos) < target_pos.distance(self.partner_pos)
+            || target_pos.distance(self.self_pos) == target_pos.distance(self.partner_pos)
+                && self.slot.is_primary()
+    }
+    pub fn partner_is_ahead(&self) -> bool {
+        let attacking_direction = self.attacking_direction();
+        attacking_direction.is_sign_positive() && self.partner_pos.x > self.self_pos.x
+            || attacking_direction.is_sign_negative() && self.partner_pos.x < self.self_pos.x
+    }
+    /// Whether or not passing to the partner is a good idea.
+    pub fn partner_is_open(&self) -> bool {
+        self.partner_is_ahead() && !self.partner_is_tackleable
+    }
+    pub fn enemy_is_close(&self) -> bool {
+        self.closest_enemy_pos.distance(self.self_pos) < self.tackle_distance
+    }
+    pub fn direction_to_pin(&self) -> Vec2 {
+        self.direction_of(self.closest_enemy_pin_pos)
+    }
+    /// Whether or not the player is facing the closest enemy pin.
+    pub fn aimed_at_pin(&self) -> bool {
+        (self.angle.angle_between(Vec2::X) - self.direction_to_pin().angle_between(Vec2::X)).abs()
+            < self.params.aim_tolerance.to_radians()
+    }
+    /// The vertical direction to run away from the closest enemy
+    /// without getting closed in on a wall.
+    pub fn flee_direction_y(&self, input: &PlayInput) -> f32 {
+        let Self {
+            self_pos,
+            closest_enemy_pos,
+            player_bounds,
+            player_radius,
+            ..
+        } = *self;
+
+        let flee_direction_y = -self.direction_of(closest_enemy_pos).y.signum();
+        let distance_to_edge = player_bounds.y - self_pos.y.abs();
+        let wall_is_close = distance_to_edge <= player_radius * self.params.wall_distance;
+        let enemy_is_opposite = self_pos.y.is_sign_positive() && closest_enemy_pos.y <= self_pos.y
+            || self_pos.y.is_sign_negative() && closest_enemy_pos.y >= self_pos.y;
+        let already_escaping = input.y.signum() == -flee_direction_y;
+        let closed_in = enemy_is_opposite && wall_is_close || already_escaping;
+        if closed_in {
+            -flee_direction_y
+        } else {
+            flee_direction_y
+        }
+    }
+    /// Where the loose ball will cross self's x position while heading
+    /// towards the defending side, like a shot on the cpu's pins.
+    pub fn shot_crossing(&self) -> Option<Vec2> {
+        if self.ball_owner.is_some() {
+            return None;
+        }
+        let heading_to_defense = self.ball_trajectory.points.first().is_some_and(|next| {
+            (next.x - self.ball_pos.x).signum() == self.defending_direction().signum()
+        });
+        heading_to_defense
+            .then(|| self.ball_trajectory.crossing_x(self.self_pos.x))
+            .flatten()
+            .map(|crossing| crossing.pos)
+    }
+    pub fn possession(&self) -> Possession {
+        if self.owns_ball() || self.partner_owns_ball() {
+            Possession::Team
+        } else if self.enemy_owns_ball() {
+            Possession::Enemy
+        } else {
+            Possession::Loose
+        }
+    }
+    /// How far the ball is into the enemy side, from `-1.0` at the
+    /// cpu's own side to `1.0` at the enemy side.
+    pub fn ball_progress(&self) -> f32 {
+        (self.target_pos().x * self.attacking_direction() / self.player_bounds.x).clamp(-1.0, 1.0)
+    }
+    /// The role self should have given the team's formation.
+    pub fn choose_role(&self) -> CpuRole {
+        let on_ball = self.owns_ball() || (!self.partner_owns_ball() && self.has_dibs());
+        if on_ball {
+            CpuRole::Attacker
+        } else {
+            self.formation
+                .off_ball_role(self.possession(), self.ball_progress())
+        }
+    }
+    /// Where self should be to fill its role.
+    pub fn role_target(&self) -> Vec2 {
+        let Self {
+            player_bounds,
+            player_radius,
+            offensive_enemy_pos,
+            ..
+        } = *self;
+        let target_pos = self.target_pos();
+        let max_x = player_bounds.x - player_radius;
+        let max_y = player_bounds.y - player_radius;
+
+        match self.role {
+            CpuRole::Attacker => self.intercept_pos(),
+            CpuRole::Support => Vec2::new(
+                (target_pos.x
+                    + self.attacking_direction() * player_bounds.x * self.params.support_distance)
+                    .clamp(-max_x, max_x),
+                // Opposite side of the ball so a pass can get through.
+                -target_pos.y.signum() * player_bounds.y * self.params.support_spread,
+            ),
+            CpuRole::Defender => Vec2::new(
+                self.defending_direction() * player_bounds.x * self.formation.line_depth(),
+                ((target_pos.y + offensive_enemy_pos.y) / 2.0).clamp(-max_y, max_y),
+            ),
+            CpuRole::Sweeper => Vec2::new(
+                self.defending_direction() * (player_bounds.x - player_radius * 2.0),
+                self.shot_crossing()
+                    .map_or(target_pos.y, |crossing| crossing.y)
+                    .clamp(
+                        -player_bounds.y * self.params.sweeper_spread,
+                        player_bounds.y * self.params.sweeper_spread,
+                    ),
+            ),
+        }
+    }
+    pub fn match_offensive_enemy_y(&self, input: &mut PlayInput) {
+        let offensive_enemy_y_distance = (self.offensive_enemy_pos.y - self.self_pos.y).abs();
+        if self.offensive_enemy_pos.y.abs() > self.player_bounds.y * self.params.enemy_wall_distance
+        {
+            input.y = 0.0;
+        } else if offensive_enemy_y_distance < self.player_radius * self.params.enemy_y_tolerance {
+            // Close enough, keep going the same way.
+        } else {
+            input.y = self.direction_of(self.closest_enemy_pos).y.signum();
+        }
     }
 }
 
-pub fn apply_cpu_input(
+pub fn cpu_senses(
     In(self_e): In<Entity>,
     entities: Res<Entities>,
     transforms: Comp<Transform>,
@@ -50,79 +335,79 @@ pub fn apply_cpu_input(
     pins: Comp<Pin>,
     teams: Comp<Team>,
     players: Comp<Player>,
+    states: Comp<State>,
+    cpu_players: Comp<CpuPlayer>,
+    tactics: Res<CpuTactics>,
+    team_params: Res<TeamCpuParams>,
     player_ent_signs: Res<PlayerEntSigns>,
+    mut prediction: ResMut<BallPrediction>,
+    asset_server: Res<AssetServer>,
     root: Root<Data>,
-    mut cpu_players: CompMut<CpuPlayer>,
-) {
+) -> CpuSenses {
     let Constants {
         player_bounds,
         player_radius,
         ball_radius,
+        run_speed,
         ..
     } = root.constant;
 
-    let Some(cpu_player) = cpu_players.get_mut(self_e) else {
-        return;
-    };
-    let input = &mut cpu_player.input;
-
     let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
 
-    let partner_e = player_ent_signs.get_partner(self_e);
     let self_player = players.get(self_e).unwrap();
-    let partner_player = players.get(partner_e).unwrap();
-    let attacking_direction = self_player.slot.team().attacking_direction();
-    let defending_direction = -attacking_direction;
+    let self_pos = get_pos(self_e);
+    let partner_e = player_ent_signs.get_partner(self_e);
+    let partner_pos = get_pos(partner_e);
+    let defending_direction = self_player.team().defending_direction();
 
     let (ball_e, ball) = entities.get_single_with(&balls).unwrap();
+    let ball_owner = ball.owner.option();
     let ball_pos = get_pos(ball_e);
 
-    let partner_pos = transforms.get(partner_e).unwrap().translation.xy();
-    let self_pos = transforms.get(self_e).unwrap().translation.xy();
+    let ball_trajectory = if ball_owner.is_none() {
+        prediction
+            .trajectory(BallBody::new(ball_pos, ball), &root.constant)
+            .clone()
+    } else {
+        BallTrajectory::default()
+    };
+    let reach = player_radius + ball_radius;
 
-    let direction_of_ball = (ball_pos - self_pos).normalize_or_zero();
-    let distance_to_ball = self_pos.distance(ball_pos);
-    let direction_of_partner = (partner_pos - self_pos).normalize_or_zero();
+    let enemies = player_ent_signs.get_enemies(self_player.slot);
 
-    let closest_enemy_pos = {
-        let mut closest_enemy_pos: Option<Vec2> = None;
-        for enemy_e in player_ent_signs.get_enemies(self_player.slot) {
-            let new_pos = transforms.get(enemy_e).unwrap().translation.xy();
-            if closest_enemy_pos
-                .is_some_and(|pos| pos.distance(self_pos) > new_pos.distance(self_pos))
-                || closest_enemy_pos.is_none()
-            {
-                closest_enemy_pos = Some(new_pos);
+    let closest_enemy_pos = enemies
+        .map(get_pos)
+        .into_iter()
+        .reduce(|closest, pos| {
+            if pos.distance(self_pos) < closest.distance(self_pos) {
+                pos
+            } else {
+                closest
             }
-        }
-        closest_enemy_pos.unwrap()
-    };
-
-    let offensive_enemy_pos = {
-        let mut offensive_enemy_pos: Option<Vec2> = None;
-        for enemy_e in player_ent_signs.get_enemies(self_player.slot) {
-            let new_pos = transforms.get(enemy_e).unwrap().translation.xy();
-            if offensive_enemy_pos.is_some_and(|pos| {
-                if defending_direction.is_sign_negative() {
-                    new_pos.x < pos.x
-                } else {
-                    new_pos.x > pos.x
-                }
-            }) || offensive_enemy_pos.is_none()
-            {
-                offensive_enemy_pos = Some(new_pos);
+        })
+        .unwrap();
+
+    let offensive_enemy_pos = enemies
+        .map(get_pos)
+        .into_iter()
+        .reduce(|offensive, pos| {
+            let further = if defending_direction.is_sign_negative() {
+                pos.x < offensive.x
+            } else {
+                pos.x > offensive.x
+            };
+            if further {
+                pos
+            } else {
+                offensive
             }
-        }
-        offensive_enemy_pos.unwrap()
-    };
-
-    let direction_of_closest_enemy = (closest_enemy_pos - self_pos).normalize_or_zero();
-    let distance_to_closest_enemy = closest_enemy_pos.distance(self_pos);
+        })
+        .unwrap();
 
     let closest_enemy_pin_pos = {
         let mut closest_pin_pos: Option<Vec2> = None;
         for (_pin_e, (_pin, team, transform)) in entities.iter_with((&pins, &teams, &transforms)) {
-            if *team == self_player.slot.team() {
+            if *team == self_player.team() {
                 continue;
             }
             let new_pos = transform.translation.xy();
@@ -136,682 +421,316 @@ pub fn apply_cpu_input(
         closest_pin_pos.unwrap_or_default()
     };
 
-    // self is closer or the primary player slot when equal
-    let dibs_on = |target_pos: Vec2| {
-        target_pos.distance(self_pos) < target_pos.distance(partner_pos)
-            || target_pos.distance(self_pos) == target_pos.distance(partner_pos)
-                && self_player.slot.is_primary()
-    };
+    let params = team_params
+        .get(self_player.team())
+        .unwrap_or_else(|| *asset_server.get(root.cpu));
+    let tackle_distance = player_radius * params.tackle_distance;
+
+    let partner_enemy_distance = player_ent_signs
+        .get_enemies(self_player.slot.partner())
+        .map(|enemy_e| get_pos(enemy_e).distance(partner_pos))
+        .into_iter()
+        .fold(f32::MAX, f32::min);
+
+    CpuSenses {
+        slot: self_player.slot,
+        angle: self_player.angle,
+        action_angle: self_player.action_angle,
+        player_state: states.get(self_e).unwrap().current,
+        role: cpu_players
+            .get(self_e)
+            .map(|cpu_player| cpu_player.role)
+            .unwrap_or_default(),
+        formation: tactics.formation(self_player.team()),
+        self_e,
+        self_pos,
+        partner_e,
+        partner_pos,
+        ball_pos,
+        ball_owner,
+        owner_pos: ball_owner.map(get_pos),
+        ball_intercept: ball_trajectory.intercept(self_pos, run_speed, reach),
+        partner_ball_intercept: ball_trajectory.intercept(partner_pos, run_speed, reach),
+        ball_trajectory,
+        closest_enemy_pos,
+        offensive_enemy_pos,
+        closest_enemy_pin_pos,
+        partner_is_tackleable: partner_enemy_distance < tackle_distance,
+        partner_is_pressured: partner_enemy_distance < tackle_distance * params.pressure_distance,
+        player_bounds,
+        player_radius,
+        ball_radius,
+        tackle_distance,
+        params,
+    }
+}
+
+/// The input on one axis to move from `from` to `to`, stopping within `tolerance`.
+fn axis_toward(from: f32, to: f32, tolerance: f32) -> f32 {
+    if (to - from).abs() < tolerance {
+        0.0
+    } else {
+        (to - from).signum()
+    }
+}
+
+/// Presses pass and tackle every other frame so that they keep getting
+/// `just_pressed` for as long as this is called.
+fn tap_pass_tackle(input: &mut PlayInput) {
+    if input.pass.pressed() {
+        input.pass.release();
+        input.tackle.release();
+    } else {
+        input.pass.press();
+        input.tackle.press();
+    }
+}
 
-    let tackle_distance = player_radius * 5.0;
+//
+// Transitions
+//
 
-    let partner_is_ahead = attacking_direction.is_sign_positive() && partner_pos.x > self_pos.x
-        || attacking_direction.is_sign_negative() && partner_pos.x < self_pos.x;
+// Each of these checks whether or not the cpu should leave to the returned state.
+// They are reused by the transition systems of every state that can leave to it.
 
-    let partner_is_tackleable = 'pressured: {
-        for enemy_e in player_ent_signs.get_enemies(partner_player.slot) {
-            if transforms
-                .get(enemy_e)
-                .unwrap()
-                .translation
-                .xy()
-                .distance(partner_pos)
-                < tackle_distance
-            {
-                break 'pressured true;
-            }
+fn to_drive(senses: &CpuSenses) -> Option<Ustr> {
+    (senses.owns_ball() && (!senses.enemy_is_close() || senses.partner_is_open()))
+        .then(state::drive)
+}
+fn to_aim(senses: &CpuSenses) -> Option<Ustr> {
+    (senses.owns_ball() && senses.enemy_is_close() && !senses.partner_is_open()).then(state::aim)
+}
+fn to_catch(senses: &CpuSenses) -> Option<Ustr> {
+    (senses.partner_owns_ball() && !senses.partner_is_pressured).then(state::catch)
+}
+fn to_save(senses: &CpuSenses) -> Option<Ustr> {
+    (senses.partner_owns_ball() && senses.partner_is_pressured).then(state::save)
+}
+fn to_chase(senses: &CpuSenses) -> Option<Ustr> {
+    (!senses.owns_ball() && !senses.partner_owns_ball() && senses.role == CpuRole::Attacker)
+        .then(state::chase)
+}
+fn to_guard(senses: &CpuSenses) -> Option<Ustr> {
+    (!senses.owns_ball() && !senses.partner_owns_ball() && senses.role != CpuRole::Attacker)
+        .then(state::guard)
+}
+
+// These are the core transition stages.
+// Each one corresponds to one unique cpu state and loops through the cpu players.
+
+fn chase_transition(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if cpu_state(world, player_e) == Some(state::chase()) {
+            let senses = world.run_system(cpu_senses, player_e);
+            let next = to_drive(&senses)
+                .or_else(|| to_aim(&senses))
+                .or_else(|| to_catch(&senses))
+                .or_else(|| to_save(&senses))
+                .or_else(|| to_guard(&senses));
+            set_cpu_state(world, player_e, next);
         }
-        false
-    };
-    let partner_is_pressured = 'pressured: {
-        for enemy_e in player_ent_signs.get_enemies(partner_player.slot) {
-            if transforms
-                .get(enemy_e)
-                .unwrap()
-                .translation
-                .xy()
-                .distance(partner_pos)
-                < tackle_distance * 2.0
-            {
-                break 'pressured true;
-            }
+    }
+}
+fn guard_transition(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if cpu_state(world, player_e) == Some(state::guard()) {
+            let senses = world.run_system(cpu_senses, player_e);
+            let next = to_drive(&senses)
+                .or_else(|| to_aim(&senses))
+                .or_else(|| to_catch(&senses))
+                .or_else(|| to_save(&senses))
+                .or_else(|| to_chase(&senses));
+            set_cpu_state(world, player_e, next);
         }
-        false
-    };
-
-    let flee_direction_y = {
-        let flee_direction_y = -direction_of_closest_enemy.y.signum();
-        let distance_to_edge = player_bounds.y - self_pos.y.abs();
-        let wall_is_close = distance_to_edge <= player_radius * 3.0;
-        let enemy_is_opposite = self_pos.y.is_sign_positive() && closest_enemy_pos.y <= self_pos.y
-            || self_pos.y.is_sign_negative() && closest_enemy_pos.y >= self_pos.y;
-        let already_escaping = input.y.signum() == -flee_direction_y;
-        let closed_in = enemy_is_opposite && wall_is_close || already_escaping;
-        if closed_in {
-            -flee_direction_y
-        } else {
-            flee_direction_y
+    }
+}
+fn drive_transition(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if cpu_state(world, player_e) == Some(state::drive()) {
+            let senses = world.run_system(cpu_senses, player_e);
+            let next = to_aim(&senses)
+                .or_else(|| to_catch(&senses))
+                .or_else(|| to_save(&senses))
+                .or_else(|| to_chase(&senses))
+                .or_else(|| to_guard(&senses));
+            set_cpu_state(world, player_e, next);
         }
-    };
-
-    let mut match_offensive_enemy_y = || {
-        let offensive_enemy_y_distance = (offensive_enemy_pos.y - self_pos.y).abs();
-        if offensive_enemy_pos.y.abs() > player_bounds.y * 0.75 {
-            input.y = 0.0;
-        } else if offensive_enemy_y_distance < player_radius * 4.0 {
-            // input.y = 0.0; //
-        } else {
-            input.y = direction_of_closest_enemy.y.signum();
+    }
+}
+fn aim_transition(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if cpu_state(world, player_e) == Some(state::aim()) {
+            let senses = world.run_system(cpu_senses, player_e);
+            let next = (senses.player_state == player::state::shoot())
+                .then(state::fire)
+                .or_else(|| to_drive(&senses))
+                .or_else(|| to_catch(&senses))
+                .or_else(|| to_save(&senses))
+                .or_else(|| to_chase(&senses))
+                .or_else(|| to_guard(&senses));
+            set_cpu_state(world, player_e, next);
         }
-    };
-
-    if let Maybe::Set(owner_e) = ball.owner {
-        let owner_pos = get_pos(owner_e);
-
-        if owner_e == self_e {
-            input.x = attacking_direction;
-
-            if partner_is_ahead && !partner_is_tackleable {
-                if input.pass.pressed() {
-                    input.pass.apply_bool(false);
-                    input.tackle.apply_bool(false);
-                } else {
-                    input.pass.apply_bool(true);
-                    input.tackle.apply_bool(true);
-                }
-            } else if distance_to_closest_enemy < tackle_distance {
-                let direction_to_pin = (closest_enemy_pin_pos - self_pos).normalize_or_zero();
-                input.x = direction_to_pin.x;
-                input.y = direction_to_pin.y;
-
-                let near_target_angle = (self_player.angle.angle_between(Vec2::X)
-                    - direction_to_pin.angle_between(Vec2::X))
-                .abs()
-                    < 3_f32.to_radians();
-
-                if !input.shoot.pressed() && near_target_angle {
-                    input.shoot.apply_bool(true);
-                } else {
-                    input.shoot.apply_bool(false);
-                }
-            } else {
-                input.y = flee_direction_y;
-            }
-        } else if owner_e == partner_e {
-            if partner_is_pressured {
-                input.x = direction_of_partner.x.signum();
-                match_offensive_enemy_y();
-            } else {
-                input.x = attacking_direction;
-                input.y = -direction_of_partner.y.signum();
-            }
+    }
+}
+fn fire_transition(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if cpu_state(world, player_e) == Some(state::fire()) {
+            let senses = world.run_system(cpu_senses, player_e);
+            let next = (senses.owns_ball() && senses.player_state != player::state::shoot())
+                .then(state::drive)
+                .or_else(|| to_catch(&senses))
+                .or_else(|| to_save(&senses))
+                .or_else(|| to_chase(&senses))
+                .or_else(|| to_guard(&senses));
+            set_cpu_state(world, player_e, next);
         }
-        // enemy owns the ball
-        else {
-            let dibs = dibs_on(owner_pos);
-
-            if dibs {
-                let direction_of_target = (owner_pos - self_pos).normalize_or_zero();
-                input.x = direction_of_target.x;
-                input.y = direction_of_target.y;
-
-                let self_distance = self_pos.distance(owner_pos);
-                if self_distance < tackle_distance {
-                    if input.pass.pressed() {
-                        input.pass.apply_bool(false);
-                        input.tackle.apply_bool(false);
-                    } else {
-                        input.pass.apply_bool(true);
-                        input.tackle.apply_bool(true);
-                    }
-                }
-            } else {
-                let distance_to_defense =
-                    (player_bounds.x * defending_direction - self_pos.x).abs();
-
-                if distance_to_defense < player_radius {
-                    input.x = 0.0;
-                } else {
-                    input.x = defending_direction;
-                }
-                match_offensive_enemy_y();
-            }
+    }
+}
+fn catch_transition(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if cpu_state(world, player_e) == Some(state::catch()) {
+            let senses = world.run_system(cpu_senses, player_e);
+            let next = to_save(&senses)
+                .or_else(|| to_drive(&senses))
+                .or_else(|| to_aim(&senses))
+                .or_else(|| to_chase(&senses))
+                .or_else(|| to_guard(&senses));
+            set_cpu_state(world, player_e, next);
         }
     }
-    // un-owned ball
-    else {
-        let dibs = dibs_on(ball_pos);
+}
+fn save_transition(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if cpu_state(world, player_e) == Some(state::save()) {
+            let senses = world.run_system(cpu_senses, player_e);
+            let next = to_catch(&senses)
+                .or_else(|| to_drive(&senses))
+                .or_else(|| to_aim(&senses))
+                .or_else(|| to_chase(&senses))
+                .or_else(|| to_guard(&senses));
+            set_cpu_state(world, player_e, next);
+        }
+    }
+}
 
-        if dibs {
-            input.x = direction_of_ball.x;
-            input.y = direction_of_ball.y;
+//
+// Inputs
+//
 
-            if distance_to_ball < tackle_distance
-                && (ball_pos.x - self_pos.x).abs() < ball_radius / 2.0
-            {
-                if input.pass.pressed() {
-                    input.pass.apply_bool(false);
-                    input.tackle.apply_bool(false);
-                } else {
-                    input.pass.apply_bool(true);
-                    input.tackle.apply_bool(true);
-                }
-            }
+// These are the inputs of the default brain.
+// Each one corresponds to one unique cpu state and only changes the cpu's input.
+
+pub fn chase_input(senses: &CpuSenses, input: &mut PlayInput) {
+    let direction_of_intercept = senses.direction_of(senses.intercept_pos());
+    input.x = direction_of_intercept.x;
+    input.y = direction_of_intercept.y;
+    input.shoot.release();
+
+    let target_pos = senses.target_pos();
+
+    let in_tackle_distance = senses.self_pos.distance(target_pos) < senses.tackle_distance;
+    // Lined up so that the tackle slides into the ball.
+    let lined_up = senses.enemy_owns_ball()
+        || (target_pos.x - senses.self_pos.x).abs()
+            < senses.ball_radius * senses.params.tackle_alignment;
+
+    if in_tackle_distance && lined_up {
+        tap_pass_tackle(input);
+    }
+}
+
+pub fn guard_input(senses: &CpuSenses, input: &mut PlayInput) {
+    let role_target = senses.role_target();
+    input.x = axis_toward(senses.self_pos.x, role_target.x, senses.player_radius);
+    input.shoot.release();
+
+    if let Some(crossing) = senses.shot_crossing() {
+        // Get in the way of the ball.
+        let y_distance = crossing.y - senses.self_pos.y;
+        input.y = if y_distance.abs() < senses.ball_radius {
+            0.0
         } else {
-            input.x = defending_direction;
+            y_distance.signum()
+        };
+    } else {
+        input.y = axis_toward(senses.self_pos.y, role_target.y, senses.player_radius);
+    }
+}
 
-            match_offensive_enemy_y();
-        }
+pub fn drive_input(senses: &CpuSenses, input: &mut PlayInput) {
+    input.x = senses.attacking_direction();
+    input.shoot.release();
+
+    if senses.partner_is_open() {
+        tap_pass_tackle(input);
+    } else {
+        input.y = senses.flee_direction_y(input);
     }
 }
 
-pub mod state {
-    crate::states![chase, drive, aim, fire, catch, save];
-}
-
-// pub struct CpuPlayerPlugin;
-// impl SessionPlugin for CpuPlayerPlugin {
-//     fn install(self, session: &mut SessionBuilder) {
-//         session
-//             .install_plugin(CpuStatePlugin::new(
-//                 state::chase(),
-//                 chase_transition,
-//                 chase_update,
-//             ))
-//             .install_plugin(CpuStatePlugin::new(
-//                 state::drive(),
-//                 drive_transition,
-//                 drive_update,
-//             ))
-//             .install_plugin(CpuStatePlugin::new(
-//                 state::catch(),
-//                 catch_transition,
-//                 catch_update,
-//             ))
-//             .install_plugin(CpuStatePlugin::new(
-//                 state::save(),
-//                 save_transition,
-//                 save_update,
-//             ))
-//             .install_plugin(CpuStatePlugin::new(
-//                 state::aim(),
-//                 aim_transition,
-//                 aim_update,
-//             ))
-//             .install_plugin(CpuStatePlugin::new(
-//                 state::fire(),
-//                 fire_transition,
-//                 fire_update,
-//             ));
-//     }
-// }
-
-// //
-// // Reused
-// fn to_drive_transition(
-//     In(self_e): In<Entity>,
-//     cpu_players: Comp<CpuPlayer>,
-//     balls: Comp<Ball>,
-//     mut states: CompMut<State>,
-// ) {
-//     let cpu_player = cpu_players.get(self_e).unwrap();
-//     let cpu_state = states.get_mut(cpu_player.state_e).unwrap();
-//     let ball = balls.iter().next().unwrap();
-//     if ball.owner.option().is_some_and(|owner_e| owner_e == self_e) {
-//         cpu_state.current = state::drive();
-//     }
-// }
-// fn to_catch_transition(
-//     In(self_e): In<Entity>,
-//     cpu_players: Comp<CpuPlayer>,
-//     balls: Comp<Ball>,
-//     player_ent_signs: Res<PlayerEntSigns>,
-//     mut states: CompMut<State>,
-// ) {
-//     let cpu_player = cpu_players.get(self_e).unwrap();
-//     let cpu_state = states.get_mut(cpu_player.state_e).unwrap();
-//     let partner_e = player_ent_signs.get_partner(self_e);
-//     let ball = balls.iter().next().unwrap();
-//     if ball
-//         .owner
-//         .option()
-//         .is_some_and(|owner_e| owner_e == partner_e)
-//     {
-//         cpu_state.current = state::catch();
-//     }
-// }
-// fn to_chase_transition(
-//     In(self_e): In<Entity>,
-//     cpu_players: Comp<CpuPlayer>,
-//     balls: Comp<Ball>,
-//     player_ent_signs: Res<PlayerEntSigns>,
-//     mut states: CompMut<State>,
-// ) {
-//     let cpu_player = cpu_players.get(self_e).unwrap();
-//     let cpu_state = states.get_mut(cpu_player.state_e).unwrap();
-//     let partner_e = player_ent_signs.get_partner(self_e);
-//     let ball = balls.iter().next().unwrap();
-//     if ball
-//         .owner
-//         .option()
-//         .is_some_and(|owner_e| owner_e != self_e && owner_e != partner_e)
-//     {
-//         cpu_state.current = state::chase();
-//     }
-// }
-// fn run_to_ball_update(
-//     In(self_e): In<Entity>,
-//     entities: Res<Entities>,
-//     transforms: Comp<Transform>,
-//     balls: Comp<Ball>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-// ) {
-//     let cpu_player = cpu_players.get_mut(self_e).unwrap();
-//     let input = &mut cpu_player.input;
-
-//     let (ball_e, ball) = entities.get_single_with(&balls).unwrap();
-
-//     let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
-//     let self_pos = get_pos(self_e);
-
-//     let target_pos = if let Some(owner_e) = ball.owner.option() {
-//         get_pos(owner_e)
-//     } else {
-//         get_pos(ball_e)
-//     };
-
-//     let direction_of_target = (target_pos - self_pos).normalize_or_zero();
-//     input.x = direction_of_target.x;
-//     input.y = direction_of_target.y;
-// }
-// fn tackle_within_distance_update(
-//     In(self_e): In<Entity>,
-//     entities: Res<Entities>,
-//     transforms: Comp<Transform>,
-//     balls: Comp<Ball>,
-//     states: Comp<State>,
-//     root: Root<Data>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-// ) {
-//     // let Constants { player_radius, .. } = root.constant;
-
-//     // let cpu_player = cpu_players.get_mut(self_e).unwrap();
-
-//     // let input = &mut cpu_player.input;
-
-//     // let (ball_e, ball) = entities.get_single_with(&balls).unwrap();
-
-//     // let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
-
-//     // let self_state = states.get(self_e).unwrap();
-//     // let self_pos = get_pos(self_e);
-
-//     // let target_pos = if let Some(owner_e) = ball.owner.option() {
-//     //     get_pos(owner_e)
-//     // } else {
-//     //     get_pos(ball_e)
-//     // };
-
-//     // let distance_to_target = target_pos.distance(self_pos);
-//     // let tackling_distance = player_radius * 6.0;
-//     // let chase_player = ball.owner.option().is_some_and(|e| e != self_e);
-//     // let in_tackling_distance = distance_to_target <= tackling_distance;
-//     // let ready_to_tackle = self_state.current != player::state::tackle();
-
-//     // if chase_player && in_tackling_distance && ready_to_tackle {
-//     //     input.pass.toggle();
-//     // } else {
-//     //     input.pass.release();
-//     // }
-// }
-
-// // Chase
-// //
-// fn chase_transition(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(to_drive_transition, player_e);
-//     world.run_system(to_catch_transition, player_e);
-// }
-// fn chase_update(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(run_to_ball_update, player_e);
-//     world.run_system(tackle_within_distance_update, player_e);
-// }
-
-// // Drive
-// //
-// fn drive_transition(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(to_aim_transition, player_e);
-//     world.run_system(to_chase_transition, player_e);
-//     world.run_system(to_catch_transition, player_e);
-// }
-// fn drive_update(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(drive_update_impl, player_e);
-// }
-// fn drive_update_impl(
-//     In(self_e): In<Entity>,
-//     players: Comp<Player>,
-//     transforms: Comp<Transform>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-// ) {
-//     let cpu_player = cpu_players.get_mut(self_e).unwrap();
-//     let input = &mut cpu_player.input;
-
-//     let self_player = players.get(self_e).unwrap();
-
-//     let attacking_direction = self_player.team().attacking_direction();
-//     input.x = attacking_direction;
-//     input.y = 0.0;
-// }
-// fn to_aim_transition(
-//     In(self_e): In<Entity>,
-//     players: Comp<Player>,
-//     entities: Res<Entities>,
-//     transforms: Comp<Transform>,
-//     pins: Comp<Pin>,
-//     teams: Comp<Team>,
-//     root: Root<Data>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-// ) {
-//     let Constants {
-//         player_radius,
-//         player_bounds,
-//         ..
-//     } = root.constant;
-
-//     let cpu_player = cpu_players.get_mut(self_e).unwrap();
-//     let input = &mut cpu_player.input;
-
-//     let self_player = players.get(self_e).unwrap();
-
-//     let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
-//     let self_pos = get_pos(self_e);
-
-//     let shooting_distance = player_radius * 10.0;
-//     let distance_to_border = player_bounds.x - self_pos.x.abs();
-
-//     if distance_to_border <= shooting_distance {
-//         let mut furthest_pin_pos: Option<Vec2> = None;
-
-//         for (pin_e, (_pin, pin_team)) in entities.iter_with((&pins, &teams)) {
-//             let pin_pos = get_pos(pin_e);
-//             if *pin_team != self_player.team()
-//                 && (furthest_pin_pos.is_none()
-//                     || furthest_pin_pos
-//                         .is_some_and(|p| p.distance(self_pos) < pin_pos.distance(self_pos)))
-//             {
-//                 furthest_pin_pos = Some(pin_pos);
-//             }
-//         }
-//         let Some(furthest_pin_pos) = furthest_pin_pos else {
-//             return;
-//         };
-//         let direction_to_furthest_pin = (furthest_pin_pos - self_pos).normalize_or_zero();
-
-//         input.x = direction_to_furthest_pin.x;
-//         input.y = direction_to_furthest_pin.y;
-//     }
-// }
-
-// //
-// // Aim
-
-// fn aim_transition(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(to_fire_transition, player_e);
-//     world.run_system(to_chase_transition, player_e);
-//     world.run_system(to_catch_transition, player_e);
-// }
-// fn aim_update(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(aim_update_impl, player_e);
-// }
-// fn aim_update_impl(
-//     In(self_e): In<Entity>,
-//     players: Comp<Player>,
-//     entities: Res<Entities>,
-//     transforms: Comp<Transform>,
-//     pins: Comp<Pin>,
-//     teams: Comp<Team>,
-//     root: Root<Data>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-// ) {
-//     let Constants {
-//         player_radius,
-//         player_bounds,
-//         ..
-//     } = root.constant;
-
-//     let cpu_player = cpu_players.get_mut(self_e).unwrap();
-//     let input = &mut cpu_player.input;
-
-//     let self_player = players.get(self_e).unwrap();
-
-//     let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
-//     let self_pos = get_pos(self_e);
-
-//     let attacking_direction = self_player.team().attacking_direction();
-//     input.x = attacking_direction;
-//     input.y = 0.0;
-
-//     let shooting_distance = player_radius * 10.0;
-//     let distance_to_border = player_bounds.x - self_pos.x.abs();
-
-//     if distance_to_border <= shooting_distance {
-//         let mut furthest_pin_pos: Option<Vec2> = None;
-
-//         for (pin_e, (_pin, pin_team)) in entities.iter_with((&pins, &teams)) {
-//             let pin_pos = get_pos(pin_e);
-//             if *pin_team != self_player.team()
-//                 && (furthest_pin_pos.is_none()
-//                     || furthest_pin_pos
-//                         .is_some_and(|p| p.distance(self_pos) < pin_pos.distance(self_pos)))
-//             {
-//                 furthest_pin_pos = Some(pin_pos);
-//             }
-//         }
-//         let Some(furthest_pin_pos) = furthest_pin_pos else {
-//             return;
-//         };
-//         let direction_to_furthest_pin = (furthest_pin_pos - self_pos).normalize_or_zero();
-
-//         input.x = direction_to_furthest_pin.x;
-//         input.y = direction_to_furthest_pin.y;
-//     }
-// }
-// fn to_fire_transition(
-//     In(self_e): In<Entity>,
-//     cpu_players: Comp<CpuPlayer>,
-//     mut states: CompMut<State>,
-// ) {
-//     let cpu_player = cpu_players.get(self_e).unwrap();
-//     let self_state = states.get(self_e).unwrap();
-//     let shooting = self_state.current == player::state::shoot();
-//     let cpu_state = states.get_mut(cpu_player.state_e).unwrap();
-//     if shooting {
-//         cpu_state.current = state::fire();
-//     }
-// }
-
-// //
-// // Fire
-// fn fire_transition(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(to_chase_transition, player_e);
-//     world.run_system(to_catch_transition, player_e);
-// }
-// fn fire_update(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(fire_update_impl, player_e);
-// }
-// fn fire_update_impl(
-//     In(self_e): In<Entity>,
-//     players: Comp<Player>,
-//     entities: Res<Entities>,
-//     pins: Comp<Pin>,
-//     transforms: Comp<Transform>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-// ) {
-//     let cpu_player = cpu_players.get_mut(self_e).unwrap();
-//     let input = &mut cpu_player.input;
-
-//     let self_player = players.get(self_e).unwrap();
-
-//     let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
-//     let self_pos = get_pos(self_e);
-
-//     let mut furthest_pin_pos: Option<Vec2> = None;
-
-//     for (pin_e, _pin) in entities.iter_with(&pins) {
-//         let pin_pos = get_pos(pin_e);
-//         if furthest_pin_pos.is_none()
-//             || furthest_pin_pos.is_some_and(|p| p.distance(self_pos) < pin_pos.distance(self_pos))
-//         {
-//             furthest_pin_pos = Some(pin_pos);
-//         }
-//     }
-//     let Some(furthest_pin_pos) = furthest_pin_pos else {
-//         return;
-//     };
-//     let direction_to_furthest_pin = (furthest_pin_pos - self_pos).normalize_or_zero();
-
-//     let range = (player::SPREAD - 0.01).to_radians();
-//     let diff = self_player.action_angle.angle_between(self_player.angle);
-//     let shoot_angle_max_reached = diff.is_sign_positive() && diff.abs() >= range
-//         || diff.is_sign_negative() && diff.abs() >= range;
-//     let shoot_angle_achieved = self_player.angle.normalize_or_zero() == direction_to_furthest_pin;
-
-//     if shoot_angle_max_reached || shoot_angle_achieved {
-//         input.shoot.release();
-//     }
-// }
-
-// // Catch
-// //
-// fn catch_transition(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(catch_out_transition, player_e);
-//     world.run_system(to_chase_transition, player_e);
-//     world.run_system(to_drive_transition, player_e);
-// }
-// fn catch_update(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(catch_update_impl, player_e);
-// }
-// fn catch_out_transition(
-//     In(self_e): In<Entity>,
-//     player_ent_signs: Res<PlayerEntSigns>,
-//     transforms: Comp<Transform>,
-//     root: Root<Data>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-//     mut states: CompMut<State>,
-// ) {
-//     let Constants { player_radius, .. } = root.constant;
-
-//     let cpu_player = cpu_players.get_mut(self_e).unwrap();
-//     let cpu_state = states.get_mut(cpu_player.state_e).unwrap();
-
-//     let partner_e = player_ent_signs.get_partner(self_e);
-
-//     let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
-
-//     let [enemy_a, enemy_b] = player_ent_signs.get_enemies_with_e(self_e);
-//     let enemy_a_pos = get_pos(enemy_a);
-//     let enemy_b_pos = get_pos(enemy_b);
-//     let partner_pos = get_pos(partner_e);
-
-//     let partner_pressure_a = enemy_a_pos.distance(partner_pos);
-//     let partner_pressure_b = enemy_b_pos.distance(partner_pos);
-
-//     let pressured_distance = player_radius * 6.0;
-//     let pressured =
-//         partner_pressure_a >= pressured_distance || partner_pressure_b >= pressured_distance;
-
-//     if pressured {
-//         cpu_state.current = state::save();
-//     }
-// }
-// fn catch_update_impl(
-//     In(self_e): In<Entity>,
-//     players: Comp<Player>,
-//     mut cpu_players: CompMut<CpuPlayer>,
-// ) {
-//     let cpu_player = cpu_players.get_mut(self_e).unwrap();
-//     let input = &mut cpu_player.input;
-
-//     let self_player = players.get(self_e).unwrap();
-
-//     let attacking_direction = self_player.team().attacking_direction();
-//     input.x = attacking_direction;
-//     input.y = 0.0;
-// }
-
-// //
-// // Save
-
-// fn save_transition(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(to_chase_transition, player_e);
-//     world.run_system(to_drive_transition, player_e);
-// }
-// fn save_update(In(player_e): In<Entity>, world: &World) {
-//     world.run_system(run_to_ball_update, player_e);
-// }
-
-// pub struct CpuStatePlugin {
-//     pub state: Ustr,
-//     pub transition: fn(In<Entity>, &World),
-//     pub update: fn(In<Entity>, &World),
-// }
-// impl CpuStatePlugin {
-//     pub fn new(
-//         state: Ustr,
-//         transition: fn(In<Entity>, &World),
-//         update: fn(In<Entity>, &World),
-//     ) -> Self {
-//         Self {
-//             state,
-//             transition,
-//             update,
-//         }
-//     }
-// }
-// impl SessionPlugin for CpuStatePlugin {
-//     fn install(self, session: &mut SessionBuilder) {
-//         session
-//             .add_system_to_stage(First, cpu_player_refresh_tick)
-//             .add_system_to_stage(
-//                 StateStage,
-//                 cpu_state_transition(self.state, self.transition),
-//             )
-//             .add_system_to_stage(PreUpdate, cpu_state_update(self.state, self.update));
-//     }
-// }
-
-// pub fn cpu_state_transition(
-//     state_id: Ustr,
-//     transition: fn(In<Entity>, &World),
-// ) -> StaticSystem<(), ()> {
-//     (move |world: &World| {
-//         for player_e in world.resource::<PlayerEntSigns>().entities() {
-//             if world
-//                 .component::<CpuPlayer>()
-//                 .get(player_e)
-//                 .is_some_and(|cpu| {
-//                     world.component::<State>().get(cpu.state_e).unwrap().current == state_id
-//                 })
-//             {
-//                 world.run_system(transition, player_e);
-//             }
-//         }
-//     })
-//     .system()
-// }
-
-// pub fn cpu_state_update(state_id: Ustr, update: fn(In<Entity>, &World)) -> StaticSystem<(), ()> {
-//     (move |world: &World| {
-//         for player_e in world.resource::<PlayerEntSigns>().entities() {
-//             if world
-//                 .component::<CpuPlayer>()
-//                 .get(player_e)
-//                 .is_some_and(|cpu| {
-//                     world.component::<State>().get(cpu.state_e).unwrap().current == state_id
-//                 })
-//             {
-//                 world.run_system(update, player_e);
-//             }
-//         }
-//     })
-//     .system()
-// }
+pub fn aim_input(senses: &CpuSenses, input: &mut PlayInput) {
+    let direction_to_pin = senses.direction_to_pin();
+    input.x = direction_to_pin.x;
+    input.y = direction_to_pin.y;
+
+    if !input.shoot.pressed() && senses.aimed_at_pin() {
+        input.shoot.press();
+    } else {
+        input.shoot.release();
+    }
+}
+
+pub fn fire_input(senses: &CpuSenses, input: &mut PlayInput) {
+    // Keep steering towards the pin while the shot is held.
+    let direction_to_pin = senses.direction_to_pin();
+    input.x = direction_to_pin.x;
+    input.y = direction_to_pin.y;
+
+    let range = (player::SPREAD - 0.01).to_radians();
+    let diff = senses.action_angle.angle_between(senses.angle);
+    let shoot_angle_max_reached = diff.abs() >= range;
+
+    if shoot_angle_max_reached || senses.aimed_at_pin() {
+        input.shoot.release();
+    }
+}
+
+pub fn catch_input(senses: &CpuSenses, input: &mut PlayInput) {
+    // Get into position to be open for a pass or to cover the partner.
+    let role_target = senses.role_target();
+    input.x = axis_toward(senses.self_pos.x, role_target.x, senses.player_radius);
+    input.y = axis_toward(senses.self_pos.y, role_target.y, senses.player_radius);
+    input.shoot.release();
+}
+
+pub fn save_input(senses: &CpuSenses, input: &mut PlayInput) {
+    // Fall in behind the partner to be ready for a loose ball.
+    input.x = senses.direction_of(senses.partner_pos).x.signum();
+    input.shoot.release();
+    senses.match_offensive_enemy_y(input);
+}
+
+//
+// Graphics Updates
+//
+fn cpu_state_graphics(
+    entities: Res<Entities>,
+    markers: Comp<CpuPlayerState>,
+    states: Comp<State>,
+    mut path_colors: CompMut<Path2dToggle>,
+) {
+    for (_e, (_marker, state, path_color)) in
+        entities.iter_with((&markers, &states, &mut path_colors))
+    {
+        match state.current {
+            s if s == state::chase() => **path_color = path2d::color::CPU_CHASE,
+            s if s == state::guard() => **path_color = path2d::color::CPU_GUARD,
+            s if s == state::drive() => **path_color = path2d::color::CPU_DRIVE,
+            s if s == state::aim() => **path_color = path2d::color::CPU_AIM,
+            s if s == state::fire() => **path_color = path2d::color::CPU_FIRE,
+            s if s == state::catch() => **path_color = path2d::color::CPU_CATCH,
+            s if s == state::save() => **path_color = path2d::color::CPU_SAVE,
+            _ => {}
+        }
+    }
+}
@@ -11,13 +11,19 @@ pub enum PlayState {
     MatchDone,
 }
 
-pub struct PlayFlowPlugin;
+pub struct PlayFlowPlugin {
+    /// The score that wins the match.
+    pub score_target: u8,
+    pub start_score: PinScore,
+}
 impl SessionPlugin for PlayFlowPlugin {
     fn install(self, session: &mut SessionBuilder) {
         session.insert_resource(PlayState::default());
+        session.insert_resource(self.start_score);
         session.insert_resource(Score {
-            target: 7,
-            ..Default::default()
+            target: self.score_target,
+            current: self.start_score,
+            previous: self.start_score,
         });
         #[cfg(not(target_arch = "wasm32"))]
         session.add_single_success_system(handle_disconnections);
@@ -43,7 +49,13 @@ fn update_flow(world: &World) {
 pub fn fade_in_update(play: &World) {
     if play.resource::<Fade>().finished() {
         *play.resource_mut() = PlayState::Countdown;
-        play.resource_mut::<Countdown>().restart();
+        let mut countdown = play.resource_mut::<Countdown>();
+        countdown.restart();
+        // A resumed match finishes the countdown it stopped in.
+        if let Some(resume) = play.resource::<PlayMode>().resume() {
+            let elapsed = std::time::Duration::from_secs_f32(resume.countdown);
+            countdown.timer.set_elapsed(elapsed);
+        }
     }
 }
 
@@ -147,27 +159,28 @@ fn podium_update(play: &World) {
         winner.visual.hide();
 
         #[cfg(not(target_arch = "wasm32"))]
-        if play.get_resource::<SyncingInfo>().is_some() {
-            // TODO: Add `NetworkMatchDone.show()` and rematch option.
+        if matches!(*play.resource::<PlayMode>(), PlayMode::Spectate { .. }) {
+            // Spectators go back to look for another match to watch.
             let mut sessions = play.resource_mut::<Sessions>();
             let ui = sessions.get_world(session::UI).unwrap();
-            start_fade(
-                ui,
-                FadeTransition {
-                    hide: play_leave,
-                    prep: network::lan_ui_prep,
-                    finish: MenuState::Lan,
-                },
-            );
+            network::leave_network_match(ui);
+        } else if play.get_resource::<SyncingInfo>().is_some() {
+            let mut sessions = play.resource_mut::<Sessions>();
+            let ui = sessions.get_world(session::UI).unwrap();
+            ui.resource_mut::<NetworkQuit>().visible = false;
+            let mut match_done = ui.resource_mut::<NetworkMatchDone>();
+            match_done.show();
+            // A peer dropped during the match so there is no one to rematch.
+            match_done.peer_left = play
+                .get_resource::<NetworkGrace>()
+                .is_some_and(|grace| grace.0.lock().unwrap().started.is_some());
+            *ui.resource_mut() = MenuState::NetworkMatchDone;
         } else {
             play.resource_mut::<MatchDone>().visual.show();
-            *play.resource_mut() = PlayState::MatchDone;
         }
         #[cfg(target_arch = "wasm32")]
-        {
-            play.resource_mut::<MatchDone>().visual.show();
-            *play.resource_mut() = PlayState::MatchDone;
-        }
+        play.resource_mut::<MatchDone>().visual.show();
+        *play.resource_mut() = PlayState::MatchDone;
     }
 }
 
@@ -278,15 +291,45 @@ fn handle_disconnections(play: &World) -> Option<()> {
         if !disconnects.disconnected_players.is_empty() {
             let mut sessions = play.resource_mut::<Sessions>();
             let ui = sessions.get_world(session::UI).unwrap();
-            ui.resource_mut::<LanUI>().state = LanUIState::Disconnected;
-            start_fade(
-                ui,
-                FadeTransition {
-                    hide: play_leave,
-                    prep: network::lan_ui_prep,
-                    finish: MenuState::Lan,
-                },
-            );
+            // Once the match is done the peers that stay can still leave
+            // from the match done screen.
+            if matches!(*play.resource::<PlayState>(), PlayState::MatchDone) {
+                ui.resource_mut::<NetworkMatchDone>().peer_left = true;
+                return Some(());
+            }
+            // When no one else is left to play with the match waits for the
+            // dropped peers, see `NetworkGrace`. With others left it can't,
+            // since all of them would have to agree on how to go on.
+            let (local, peers) = match &*play.resource::<PlayMode>() {
+                PlayMode::Online { socket, .. } => {
+                    (socket.player_idx() as u8, socket.player_count())
+                }
+                PlayMode::Spectate { .. } | PlayMode::Offline(..) => return None,
+            };
+            let missing = disconnects
+                .disconnected_players
+                .iter()
+                .map(|player| *player as u32)
+                .collect::<Vec<_>>();
+            let others_left =
+                (0..peers).any(|peer| peer != local as u32 && !missing.contains(&peer));
+            if !others_left {
+                let slots = play.resource::<PlayTeamInputs>().slots;
+                let grace = play.resource::<NetworkGrace>();
+                let mut state = grace.0.lock().unwrap();
+                state.start(missing);
+
+                let mut grace_ui = ui.resource_mut::<NetworkGraceUI>();
+                grace_ui.show();
+                let forfeit_team = state.forfeit_team(&slots);
+                grace_ui.take_win = forfeit_team.is_some()
+                    && forfeit_team != slots.slot(local, false).map(|slot| slot.team());
+                grace_ui.rejoin = peers == 2 && ui.resource::<Matchmaker>().is_lan_discovered();
+                grace_ui.host = local == 0;
+                return Some(());
+            }
+            ui.resource_mut::<LanUI>().state = LanUIState::PeerDropped;
+            network::leave_network_match(ui);
             return Some(());
         }
     }
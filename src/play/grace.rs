@@ -0,0 +1,150 @@
+use super::*;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long the dropped peer of a network match has to rejoin.
+pub const GRACE_PERIOD: Duration = Duration::from_secs(20);
+
+/// What the remaining player chose once the dropped peer didn't rejoin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraceDecision {
+    /// The team of the dropped peer forfeits the match.
+    TakeWin,
+    /// Cpus take over the players of the dropped peer.
+    Cpus,
+}
+
+#[derive(Debug, Default)]
+pub struct NetworkGraceState {
+    /// The peers that dropped out of the match.
+    pub missing: Vec<u32>,
+    /// When the peers dropped, the simulation is frozen from then until a
+    /// decision is made.
+    pub started: Option<Instant>,
+    pub decision: Option<GraceDecision>,
+}
+impl NetworkGraceState {
+    pub fn start(&mut self, missing: Vec<u32>) {
+        tracing::info!(?missing, "waiting for the dropped peers");
+        self.missing = missing;
+        self.started = Some(Instant::now());
+    }
+    /// Whether the simulation is frozen, waiting for the dropped peers.
+    pub fn is_frozen(&self) -> bool {
+        self.started.is_some() && self.decision.is_none()
+    }
+    /// The time left for the dropped peers to rejoin.
+    pub fn remaining(&self) -> Duration {
+        self.started.map_or(GRACE_PERIOD, |started| {
+            GRACE_PERIOD.saturating_sub(started.elapsed())
+        })
+    }
+    pub fn timed_out(&self) -> bool {
+        self.is_frozen() && self.remaining().is_zero()
+    }
+    pub fn is_missing(&self, peer: u8) -> bool {
+        self.missing.contains(&(peer as u32))
+    }
+    /// The team of the players of the dropped peers, which loses when the
+    /// remaining player takes the win.
+    pub fn forfeit_team(&self, slots: &SlotAssignment) -> Option<Team> {
+        PlayerSlot::variants()
+            .into_iter()
+            .find(|slot| {
+                let owner = slots.owner(*slot);
+                !owner.cpu && self.is_missing(owner.peer)
+            })
+            .map(|slot| slot.team())
+    }
+}
+
+/// Freezes a two machine network match when the other machine drops, which
+/// gives it [`GRACE_PERIOD`] to come back before the remaining player
+/// decides how to go on, see [`GraceDecision`].
+///
+/// Only matches between two machines are frozen. With more, the peers that
+/// are left would all have to agree on the decision at the same frame, so one
+/// dropping ends the match for everyone instead, see [`LanUIState::PeerDropped`].
+/// Only the machines of a LAN match found through the discovery wait for each
+/// other, since they can find each other again. Online and direct connect
+/// matches go right to the decision, see [`NetworkGraceUI::rejoin`].
+///
+/// The state is shared between the snapshots of the play session so
+/// rollbacks don't restore it, and with the [`GraceRunner`] that stops
+/// stepping the match while it is frozen.
+#[derive(HasSchema, Clone, Default)]
+pub struct NetworkGrace(pub Arc<Mutex<NetworkGraceState>>);
+impl SessionPlugin for NetworkGrace {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+        session.add_system_to_stage(First, apply_grace_decision);
+    }
+}
+
+/// Carries out the [`GraceDecision`] every frame, so that it holds through
+/// rollbacks and the cpu inputs are part of what the spectators are sent,
+/// see [`SpectatorFeed`].
+pub fn apply_grace_decision(world: &World) {
+    let grace = world.resource::<NetworkGrace>();
+    let state = grace.0.lock().unwrap();
+    let Some(decision) = state.decision else {
+        return;
+    };
+    let slots = world.resource::<PlayTeamInputs>().slots;
+    let missing_slots = PlayerSlot::variants()
+        .into_iter()
+        .filter(|slot| state.is_missing(slots.owner(*slot).peer))
+        .collect::<Vec<_>>();
+
+    match decision {
+        GraceDecision::TakeWin => {
+            let Some(team) = state.forfeit_team(&slots) else {
+                return;
+            };
+            let target = world.resource::<Score>().target;
+            let mut pin_score = world.resource_mut::<PinScore>();
+            let winner = match team {
+                Team::A => &mut pin_score.b,
+                Team::B => &mut pin_score.a,
+            };
+            *winner = (*winner).max(target);
+        }
+        GraceDecision::Cpus => {
+            let ent_signs = world.resource::<PlayerEntSigns>().clone();
+            for slot in missing_slots {
+                let player_e = ent_signs.get(slot);
+                if !world.component::<CpuPlayer>().contains(player_e) {
+                    spawn::cpu_player(world, player_e, CpuBrains::default_name());
+                }
+                let input = get_cpu_input(world, slot);
+                world
+                    .resource_mut::<PlayTeamInputs>()
+                    .get_character_control_mut(slot)
+                    .update_from_dense(&input);
+            }
+        }
+    }
+}
+
+/// Wraps the runner of a network match to stop stepping it while the
+/// [`NetworkGrace`] is frozen.
+pub struct GraceRunner {
+    pub runner: Box<dyn SessionRunner>,
+    pub grace: NetworkGrace,
+}
+impl SessionRunner for GraceRunner {
+    fn step(&mut self, frame_start: Instant, world: &mut World, stages: &mut SystemStages) {
+        if self.grace.0.lock().unwrap().is_frozen() {
+            return;
+        }
+        self.runner.step(frame_start, world, stages);
+    }
+
+    fn restart_session(&mut self) {
+        self.runner.restart_session();
+    }
+
+    fn disable_local_input(&mut self, disable_input: bool) {
+        self.runner.disable_local_input(disable_input);
+    }
+}
@@ -1,11 +1,20 @@
 use super::*;
 
+pub mod buffer;
+pub use buffer::*;
+
 pub mod collection;
 pub use collection::*;
 
 pub mod dense;
 pub use dense::*;
 
+pub mod mouse;
+pub use mouse::*;
+
+pub mod slots;
+pub use slots::*;
+
 pub mod prelude {
     pub use super::*;
 }
@@ -29,16 +38,33 @@ pub struct PlayTeamInput {
 
 #[derive(HasSchema, Clone, Default, Debug, Deref, DerefMut)]
 pub struct PlayTeamInputs {
-    pub clients: [PlayTeamInput; 2usize],
+    /// The input of every peer, which is just the first two offline.
+    #[deref]
+    pub clients: [PlayTeamInput; MAX_PEERS],
+    /// Which of the `clients` controls each character.
+    pub slots: SlotAssignment,
 }
 impl PlayTeamInputs {
+    pub fn new(slots: SlotAssignment) -> Self {
+        Self { slots, ..default() }
+    }
     /// Uses the player slot to get the exact control for one character.
     pub fn get_character_control(&self, slot: PlayerSlot) -> &PlayInput {
-        match slot {
-            PlayerSlot::A1 => &self.clients[0].p1,
-            PlayerSlot::A2 => &self.clients[0].p2,
-            PlayerSlot::B1 => &self.clients[1].p1,
-            PlayerSlot::B2 => &self.clients[1].p2,
+        let owner = self.slots.owner(slot);
+        let client = &self.clients[owner.peer as usize];
+        if owner.secondary {
+            &client.p2
+        } else {
+            &client.p1
+        }
+    }
+    pub fn get_character_control_mut(&mut self, slot: PlayerSlot) -> &mut PlayInput {
+        let owner = self.slots.owner(slot);
+        let client = &mut self.clients[owner.peer as usize];
+        if owner.secondary {
+            &mut client.p2
+        } else {
+            &mut client.p1
         }
     }
     // This is how this will be handled in the future; I call this in my own
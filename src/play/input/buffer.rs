@@ -0,0 +1,155 @@
+use super::*;
+
+/// Remembers shoot, pass and tackle presses for a few frames so that a press
+/// that lands just before it is valid still goes through.
+///
+/// Each field is the amount of frames the press is still usable for. This is
+/// only ever changed by the simulation, so it stays in sync across rollbacks.
+/// It must be updated once per frame, before the state transitions take the
+/// presses.
+#[derive(HasSchema, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct ActionBuffer {
+    pub shoot: u64,
+    pub pass: u64,
+    pub tackle: u64,
+}
+impl ActionBuffer {
+    /// Ages the buffered presses by a frame and buffers new presses for the
+    /// current frame and the `window` after it.
+    pub fn update(&mut self, input: &PlayInput, window: u64) {
+        for (frames, press) in [
+            (&mut self.shoot, input.shoot),
+            (&mut self.pass, input.pass),
+            (&mut self.tackle, input.tackle),
+        ] {
+            *frames = frames.saturating_sub(1);
+            if press.just_pressed() {
+                *frames = window + 1;
+            }
+        }
+    }
+    /// Uses up the buffered shoot, returning whether or not there was one.
+    pub fn take_shoot(&mut self) -> bool {
+        std::mem::take(&mut self.shoot) > 0
+    }
+    /// Uses up the buffered pass along with the tackle, which shares its
+    /// buttons by default, so that one press doesn't do both.
+    ///
+    /// The tackle is only used up when there was a pass, so that a pass
+    /// bound apart from it isn't lost while tackling is looked for.
+    pub fn take_pass(&mut self) -> bool {
+        let passed = std::mem::take(&mut self.pass) > 0;
+        if passed {
+            self.tackle = 0;
+        }
+        passed
+    }
+    /// Uses up the buffered tackle along with the pass, see [`Self::take_pass`].
+    pub fn take_tackle(&mut self) -> bool {
+        let tackled = std::mem::take(&mut self.tackle) > 0;
+        if tackled {
+            self.pass = 0;
+        }
+        tackled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: u64 = 6;
+
+    /// The input of each frame, with pass and tackle held on the `pressed` frames.
+    fn inputs(frames: usize, pressed: &[usize]) -> Vec<PlayInput> {
+        let mut input = PlayInput::default();
+        (0..frames)
+            .map(|frame| {
+                input.pass.advance();
+                input.tackle.advance();
+                input.pass.apply_bool(pressed.contains(&frame));
+                input.tackle.apply_bool(pressed.contains(&frame));
+                input
+            })
+            .collect()
+    }
+
+    /// Updates the buffer for each input, taking the pass on every frame
+    /// from `pass_frame` on, and returns the buffer and whether the pass
+    /// went through for each frame.
+    fn simulate(
+        mut buffer: ActionBuffer,
+        inputs: &[PlayInput],
+        first_frame: usize,
+        pass_frame: usize,
+    ) -> Vec<(ActionBuffer, bool)> {
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                buffer.update(input, WINDOW);
+                let passed = first_frame + i >= pass_frame && buffer.take_pass();
+                (buffer, passed)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn window_expires() {
+        let mut buffer = ActionBuffer::default();
+        for (frame, input) in inputs(WINDOW as usize + 4, &[0]).iter().enumerate() {
+            buffer.update(input, WINDOW);
+            assert_eq!(buffer.pass > 0, frame as u64 <= WINDOW, "frame {frame}");
+        }
+        assert!(!buffer.take_pass());
+    }
+
+    #[test]
+    fn press_fires_once() {
+        // The button is held, which only counts as a press on the first frame.
+        let frames = simulate(default(), &inputs(12, &[1, 2, 3, 4, 5]), 0, 3);
+        let passes = frames.iter().filter(|(_, passed)| *passed).count();
+        assert_eq!(passes, 1);
+        assert!(frames[3].1);
+
+        // The tackle on the same button is used up with the pass.
+        assert_eq!(frames[3].0.tackle, 0);
+        let mut buffer = frames[2].0;
+        assert!(buffer.take_tackle());
+        assert!(!buffer.take_pass());
+    }
+
+    #[test]
+    fn pass_bound_apart_from_tackle() {
+        let mut input = PlayInput::default();
+        let mut buffer = ActionBuffer::default();
+        for frame in 0..4 {
+            input.pass.advance();
+            input.pass.apply_bool(frame == 0);
+            buffer.update(&input, WINDOW);
+            // The free state looks for a tackle on every frame.
+            assert!(!buffer.take_tackle(), "frame {frame}");
+        }
+        // The pass still goes through once the ball is gained.
+        assert!(buffer.take_pass());
+        assert!(!buffer.take_pass());
+    }
+
+    #[test]
+    fn rollback_replay() {
+        let inputs = inputs(16, &[1, 9]);
+        let frames = simulate(default(), &inputs, 0, 4);
+        assert_eq!(frames.iter().filter(|(_, passed)| *passed).count(), 2);
+
+        // Rolling back to any frame and replaying the same inputs gives the same buffers.
+        for rollback in 0..inputs.len() - 1 {
+            let replay = simulate(frames[rollback].0, &inputs[rollback + 1..], rollback + 1, 4);
+            assert_eq!(
+                replay,
+                frames[rollback + 1..],
+                "rollback to frame {rollback}"
+            );
+        }
+    }
+}
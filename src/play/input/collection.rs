@@ -4,27 +4,56 @@ use super::*;
 pub enum SingleSource {
     #[default]
     KeyboardMouse,
+    /// The right hand side of the keyboard so that two players can share it.
+    Keyboard2,
     CPU(PlayerSlot),
     Gamepad(u32),
 }
+impl SingleSource {
+    pub fn is_keyboard(&self) -> bool {
+        matches!(self, Self::KeyboardMouse | Self::Keyboard2)
+    }
+    /// Whether or not the source can control both players of a team, the
+    /// keyboard and mouse doing so by steering the second player with the mouse.
+    pub fn can_twin_stick(&self) -> bool {
+        matches!(self, Self::KeyboardMouse | Self::Gamepad(..))
+    }
+}
 
-fn apply_keyboard_state(input: &mut PlayInput, key: &KeyCode, keyboard_state: &KeyboardState) {
-    match key {
-        KeyCode::W | KeyCode::S => {
-            input.y = keyboard_state.is_pressed(&KeyCode::W) as i8 as f32
-                - keyboard_state.is_pressed(&KeyCode::S) as i8 as f32;
-        }
-        KeyCode::A | KeyCode::D => {
-            input.x = keyboard_state.is_pressed(&KeyCode::D) as i8 as f32
-                - keyboard_state.is_pressed(&KeyCode::A) as i8 as f32;
-        }
-        KeyCode::J => {
-            input.shoot.apply_bool(keyboard_state.is_pressed(key));
-        }
-        KeyCode::K => {
-            input.pass.apply_bool(keyboard_state.is_pressed(key));
+/// Applies the key to the input.
+///
+/// `stick` is the direction of the held movement keys, which the mouse aim
+/// goes back to, see [`apply_mouse_aim`].
+fn apply_keyboard_state(
+    input: &mut PlayInput,
+    stick: &mut Vec2,
+    key: &KeyCode,
+    keyboard_state: &KeyboardState,
+    keys: &KeyBindings,
+) {
+    let held = |action| keys.action_pressed(action, keyboard_state);
+    for action in keys.key_actions(*key) {
+        match action {
+            BindAction::Up | BindAction::Down => {
+                stick.y = held(BindAction::Up) as i8 as f32 - held(BindAction::Down) as i8 as f32;
+                input.y = stick.y;
+            }
+            BindAction::Left | BindAction::Right => {
+                stick.x =
+                    held(BindAction::Right) as i8 as f32 - held(BindAction::Left) as i8 as f32;
+                input.x = stick.x;
+            }
+            BindAction::Shoot => {
+                input.shoot.apply_bool(held(BindAction::Shoot));
+            }
+            BindAction::Pass => {
+                input.pass.apply_bool(held(BindAction::Pass));
+            }
+            BindAction::Tackle => {
+                input.tackle.apply_bool(held(BindAction::Tackle));
+            }
+            _ => {}
         }
-        _ => {}
     }
 }
 fn apply_mouse_event(input: &mut PlayInput, event: &MouseButtonEvent) {
@@ -38,81 +67,53 @@ fn apply_mouse_event(input: &mut PlayInput, event: &MouseButtonEvent) {
         MouseButton::Middle | MouseButton::Other(_) => {}
     }
 }
-fn apply_gamepad_event_primary(input: &mut PlayInput, event: &GamepadEvent, gamepad_id: u32) {
-    if *event.gamepad_id() != gamepad_id {
-        return;
-    }
-    match event {
-        GamepadEvent::Axis(GamepadAxisEvent { axis, value, .. }) => match axis {
-            GamepadAxis::LeftStickX => {
-                input.x = *value;
-            }
-            GamepadAxis::LeftStickY => {
-                input.y = *value;
-            }
-            _ => {}
-        },
-        GamepadEvent::Button(GamepadButtonEvent { button, value, .. }) => match button {
-            GamepadButton::South => {
-                input.shoot.apply_value(*value);
-            }
-            GamepadButton::West => {
-                input.tackle.apply_value(*value);
-            }
-            GamepadButton::East => {
-                input.pass.apply_value(*value);
-            }
-            GamepadButton::RightTrigger2 => {
-                input.shoot.apply_value(*value);
-            }
-            GamepadButton::LeftTrigger2 => {
-                input.shoot.apply_value(*value);
-            }
-            GamepadButton::LeftTrigger => {
-                input.pass.apply_value(*value);
-                input.tackle.apply_value(*value);
-            }
-            _ => {}
-        },
-        _ => {}
-    }
-}
-fn apply_gamepad_event_secondary(input: &mut PlayInput, event: &GamepadEvent, gamepad_id: u32) {
+/// Applies the gamepad event to the input.
+///
+/// `stick` is the raw position of the stick, which goes through the
+/// [`StickSettings`] before it reaches the input. The `secondary` player on a
+/// shared gamepad moves with the right stick and uses the mirrored shoulder buttons.
+fn apply_gamepad_event(
+    input: &mut PlayInput,
+    stick: &mut Vec2,
+    event: &GamepadEvent,
+    gamepad_id: u32,
+    bindings: &InputBindings,
+    sticks: &StickSettings,
+    secondary: bool,
+) {
     if *event.gamepad_id() != gamepad_id {
         return;
     }
     match event {
-        GamepadEvent::Axis(GamepadAxisEvent { axis, value, .. }) => match axis {
-            GamepadAxis::RightStickX => {
-                input.x = *value;
-            }
-            GamepadAxis::RightStickY => {
-                input.y = *value;
-            }
-            _ => {}
-        },
-        GamepadEvent::Button(GamepadButtonEvent { button, value, .. }) => match button {
-            GamepadButton::South => {
-                input.shoot.apply_value(*value);
-            }
-            GamepadButton::West => {
-                input.tackle.apply_value(*value);
-            }
-            GamepadButton::East => {
-                input.pass.apply_value(*value);
-            }
-            GamepadButton::RightTrigger2 => {
-                input.shoot.apply_value(*value);
-            }
-            GamepadButton::LeftTrigger2 => {
-                input.shoot.apply_value(*value);
+        GamepadEvent::Axis(GamepadAxisEvent { axis, value, .. }) => {
+            match (axis, secondary) {
+                (GamepadAxis::LeftStickX, false) | (GamepadAxis::RightStickX, true) => {
+                    stick.x = *value;
+                }
+                (GamepadAxis::LeftStickY, false) | (GamepadAxis::RightStickY, true) => {
+                    stick.y = *value;
+                }
+                _ => return,
             }
-            GamepadButton::RightTrigger => {
-                input.pass.apply_value(*value);
-                input.tackle.apply_value(*value);
+            let Vec2 { x, y } = sticks.apply(*stick);
+            input.x = x;
+            input.y = y;
+        }
+        GamepadEvent::Button(GamepadButtonEvent { button, value, .. }) => {
+            let button = if secondary {
+                mirror_button(*button)
+            } else {
+                *button
+            };
+            for action in bindings.button_actions(button) {
+                match action {
+                    BindAction::Shoot => input.shoot.apply_value(*value),
+                    BindAction::Pass => input.pass.apply_value(*value),
+                    BindAction::Tackle => input.tackle.apply_value(*value),
+                    _ => {}
+                }
             }
-            _ => {}
-        },
+        }
         _ => {}
     }
 }
@@ -122,6 +123,13 @@ fn apply_gamepad_event_secondary(input: &mut PlayInput, event: &GamepadEvent, ga
 pub struct PlayTeamInputCollector {
     p1_source: SingleSource,
     p2_source: SingleSource,
+    /// The slots of the players, used to find them for mouse controls.
+    /// The second player is `None` on a machine with one player.
+    slots: [Option<PlayerSlot>; 2],
+    /// The raw stick positions of gamepad players, or the direction of the
+    /// held movement keys of keyboard players.
+    p1_stick: Vec2,
+    p2_stick: Vec2,
     // TODO: FIXME: This should only include the state, but `PlayTeamInput` has just_pressed data in it.
     // NOTE: We can only change this by disolving the unnecessary type restrictions on the collector.
     current: PlayTeamInput,
@@ -132,13 +140,104 @@ impl PlayTeamInputCollector {
         Self {
             p1_source,
             p2_source,
+            slots: Team::A.slots().map(Some),
+            p1_stick: Vec2::ZERO,
+            p2_stick: Vec2::ZERO,
             current: Default::default(),
         }
     }
+    pub fn with_team(mut self, team: Team) -> Self {
+        self.set_team(team);
+        self
+    }
+    /// Changes the sources, clearing the input of the players whose source changed.
     pub fn set_sources(&mut self, p1_source: SingleSource, p2_source: SingleSource) {
+        if self.p1_source != p1_source {
+            self.current.p1 = default();
+            self.p1_stick = Vec2::ZERO;
+        }
+        if self.p2_source != p2_source {
+            self.current.p2 = default();
+            self.p2_stick = Vec2::ZERO;
+        }
         self.p1_source = p1_source;
         self.p2_source = p2_source;
     }
+    /// Clears the input of the players whose gamepad connected or disconnected
+    /// so that they don't keep doing what the gamepad was doing.
+    pub fn apply_connection_events(&mut self, gamepad_inputs: &GamepadInputs) {
+        for event in &gamepad_inputs.gamepad_events {
+            let GamepadEvent::Connection(GamepadConnectionEvent { gamepad, .. }) = event else {
+                continue;
+            };
+            if self.p1_source == SingleSource::Gamepad(*gamepad) {
+                self.current.p1 = default();
+                self.p1_stick = Vec2::ZERO;
+            }
+            if self.p2_source == SingleSource::Gamepad(*gamepad) {
+                self.current.p2 = default();
+                self.p2_stick = Vec2::ZERO;
+            }
+        }
+    }
+    pub fn set_team(&mut self, team: Team) {
+        self.slots = team.slots().map(Some);
+    }
+    /// Sets the slots of the players in a network match, where the players
+    /// of a peer don't have to be a team.
+    pub fn set_slots(&mut self, slots: [Option<PlayerSlot>; 2]) {
+        self.slots = slots;
+    }
+    /// Whether or not both players are controlled by the keyboard and mouse,
+    /// the mouse steering the secondary player.
+    pub fn is_mouse_twin_stick(&self) -> bool {
+        self.slots[1].is_some()
+            && self.p1_source == SingleSource::KeyboardMouse
+            && self.p2_source == SingleSource::KeyboardMouse
+    }
+    /// Steers the secondary player with the cursor in twin stick mode, otherwise
+    /// aims the shot of the keyboard and mouse player if mouse aim is on.
+    fn apply_mouse_pointer(&mut self, world: &World, mouse_aim: bool) {
+        let twin_stick = self.is_mouse_twin_stick();
+        if !twin_stick && !mouse_aim {
+            return;
+        }
+        let Some(ent_signs) = world.get_resource::<PlayerEntSigns>() else {
+            return;
+        };
+        let cursor = mouse_world_position(world);
+        let transforms = world.component::<Transform>();
+        let states = world.component::<State>();
+        let [p1_slot, p2_slot] = self.slots;
+
+        for (slot, source, input, stick) in [
+            (p1_slot, self.p1_source, &mut self.current.p1, self.p1_stick),
+            (p2_slot, self.p2_source, &mut self.current.p2, self.p2_stick),
+        ] {
+            let Some(slot) = slot.filter(|_| source == SingleSource::KeyboardMouse) else {
+                continue;
+            };
+            let player_e = ent_signs.get(slot);
+            let Some(transform) = transforms.get(player_e) else {
+                continue;
+            };
+            let player_pos = transform.translation.xy();
+
+            if twin_stick {
+                if let Some(cursor) = cursor.filter(|_| slot.is_secondary()) {
+                    apply_mouse_steer(input, player_pos, cursor);
+                }
+                continue;
+            }
+            let shooting = states
+                .get(player_e)
+                .is_some_and(|state| state.current == player::state::shoot());
+            let aim = cursor
+                .filter(|_| shooting)
+                .map(|cursor| cursor - player_pos);
+            apply_mouse_aim(input, stick, aim);
+        }
+    }
 }
 impl InputCollector<'_, PlayTeamInput> for PlayTeamInputCollector {
     // Called on cpu cycle as opposed to the frame update.
@@ -147,22 +246,47 @@ impl InputCollector<'_, PlayTeamInput> for PlayTeamInputCollector {
         let keyboard_state = world.resource::<KeyboardState>();
         let gamepad = world.resource::<GamepadInputs>();
         let mouse = world.resource::<MouseInputs>();
+        let settings = world.resource::<InputSettings>();
+        let InputSettings {
+            bindings, sticks, ..
+        } = &*settings;
+
+        self.apply_connection_events(&gamepad);
 
         match self.p1_source {
-            SingleSource::KeyboardMouse => {
+            source @ (SingleSource::KeyboardMouse | SingleSource::Keyboard2) => {
+                let keys = bindings.keys(source).unwrap();
                 for event in &keyboard.key_events {
                     let Maybe::Set(key) = &event.key_code else {
                         continue;
                     };
-                    apply_keyboard_state(&mut self.current.p1, key, &keyboard_state);
+                    apply_keyboard_state(
+                        &mut self.current.p1,
+                        &mut self.p1_stick,
+                        key,
+                        &keyboard_state,
+                        keys,
+                    );
                 }
-                for event in &mouse.button_events {
-                    apply_mouse_event(&mut self.current.p1, event);
+                // In twin stick mode the mouse belongs to the secondary player.
+                if source == SingleSource::KeyboardMouse && !self.is_mouse_twin_stick() {
+                    for event in &mouse.button_events {
+                        apply_mouse_event(&mut self.current.p1, event);
+                    }
                 }
             }
             SingleSource::Gamepad(gamepad_id) => {
+                let sticks = sticks.get(Some(gamepad_id));
                 for event in &gamepad.gamepad_events {
-                    apply_gamepad_event_primary(&mut self.current.p1, event, gamepad_id)
+                    apply_gamepad_event(
+                        &mut self.current.p1,
+                        &mut self.p1_stick,
+                        event,
+                        gamepad_id,
+                        bindings,
+                        &sticks,
+                        false,
+                    )
                 }
             }
             SingleSource::CPU(player_slot) => {
@@ -172,26 +296,43 @@ impl InputCollector<'_, PlayTeamInput> for PlayTeamInputCollector {
             }
         }
         match self.p2_source {
-            SingleSource::KeyboardMouse => {
+            // The machine only has one player in the network match.
+            _ if self.slots[1].is_none() => {}
+            source @ (SingleSource::KeyboardMouse | SingleSource::Keyboard2) => {
+                let keys = bindings.keys(source).unwrap();
                 for event in &keyboard.key_events {
                     let Maybe::Set(key) = &event.key_code else {
                         continue;
                     };
-                    apply_keyboard_state(&mut self.current.p2, key, &keyboard_state);
+                    if !self.is_mouse_twin_stick() {
+                        apply_keyboard_state(
+                            &mut self.current.p2,
+                            &mut self.p2_stick,
+                            key,
+                            &keyboard_state,
+                            keys,
+                        );
+                    }
                 }
-                for event in &mouse.button_events {
-                    apply_mouse_event(&mut self.current.p2, event);
+                if source == SingleSource::KeyboardMouse {
+                    for event in &mouse.button_events {
+                        apply_mouse_event(&mut self.current.p2, event);
+                    }
                 }
             }
             SingleSource::Gamepad(gamepad_id) => {
-                if self.p1_source == self.p2_source {
-                    for event in &gamepad.gamepad_events {
-                        apply_gamepad_event_secondary(&mut self.current.p2, event, gamepad_id)
-                    }
-                } else {
-                    for event in &gamepad.gamepad_events {
-                        apply_gamepad_event_primary(&mut self.current.p2, event, gamepad_id)
-                    }
+                let sticks = sticks.get(Some(gamepad_id));
+                let secondary = self.p1_source == self.p2_source;
+                for event in &gamepad.gamepad_events {
+                    apply_gamepad_event(
+                        &mut self.current.p2,
+                        &mut self.p2_stick,
+                        event,
+                        gamepad_id,
+                        bindings,
+                        &sticks,
+                        secondary,
+                    )
                 }
             }
             SingleSource::CPU(player_slot) => {
@@ -200,6 +341,11 @@ impl InputCollector<'_, PlayTeamInput> for PlayTeamInputCollector {
                     .update_from_dense(&cpu_player::get_cpu_input(world, player_slot));
             }
         }
+        if self.p1_source == SingleSource::KeyboardMouse
+            || self.p2_source == SingleSource::KeyboardMouse
+        {
+            self.apply_mouse_pointer(world, bindings.mouse_aim);
+        }
     }
     fn update_just_pressed(&mut self) {
         // Not neccessary as this is called at the same time as `apply_inputs`.
@@ -1,6 +1,12 @@
 use super::*;
+use numquant::{IntRange, Quantized};
+use std::f32::consts::TAU;
 
 bitfield::bitfield! {
+    /// The input of one player.
+    ///
+    /// Only the lower 32 bits make it into a [`PlayTeamInputDense`], so the
+    /// fields have to stay below bit 32.
     #[derive(bytemuck::Pod, bytemuck::Zeroable, Default, Clone, Copy, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct PlayInputDense(u64);
@@ -9,13 +15,15 @@ bitfield::bitfield! {
     pub pass, set_pass: 1;
     pub tackle, set_tackle: 2;
     pub some_angle, set_some_angle: 3;
-    pub from into DenseAngle, angle, set_angle: 32, 4;
+    pub from into DenseAngle, angle, set_angle: 15, 4;
+    pub from into DenseMagnitude, magnitude, set_magnitude: 23, 16;
 }
 
 impl DenseControl<PlayInputDense> for PlayInput {
     fn get_dense_input(&self) -> PlayInputDense {
         let vec2 = Vec2::new(self.x, self.y);
-        let angle = (vec2.length() > 0.1).then_some(vec2.angle_between(Vec2::X));
+        // The dead zones are already applied by the `StickSettings`.
+        let angle = (vec2 != Vec2::ZERO).then_some(Vec2::X.angle_between(vec2));
 
         let mut dense = PlayInputDense::default();
         dense.set_shoot(self.shoot.pressed());
@@ -25,11 +33,16 @@ impl DenseControl<PlayInputDense> for PlayInput {
 
         if let Some(angle) = angle {
             dense.set_angle(DenseAngle(angle));
+            dense.set_magnitude(DenseMagnitude(vec2.length()));
         }
         dense
     }
     fn update_from_dense(&mut self, dense: &PlayInputDense) {
-        let Vec2 { x, y } = Vec2::from_angle(*dense.angle());
+        let Vec2 { x, y } = if dense.some_angle() {
+            Vec2::from_angle(*dense.angle()) * *dense.magnitude()
+        } else {
+            Vec2::ZERO
+        };
         self.x = x;
         self.y = y;
         self.shoot.apply_bool(dense.shoot());
@@ -57,6 +70,17 @@ bitfield::bitfield! {
     pub from into PlayInputDense, p2, set_p2: 63, 32;
 }
 
+impl From<u64> for PlayTeamInputDense {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+impl From<PlayTeamInputDense> for u64 {
+    fn from(dense: PlayTeamInputDense) -> Self {
+        dense.0
+    }
+}
+
 impl DenseControl<PlayTeamInputDense> for PlayTeamInput {
     fn get_dense_input(&self) -> PlayTeamInputDense {
         let mut dense = PlayTeamInputDense::default();
@@ -70,54 +94,67 @@ impl DenseControl<PlayTeamInputDense> for PlayTeamInput {
     }
 }
 
+/// An angle in radians, stored as one of 4096 steps around the circle.
 #[derive(Default, Deref, DerefMut, Debug)]
 pub struct DenseAngle(pub f32);
 
+impl DenseAngle {
+    const STEPS: f32 = 4096.0;
+}
 impl From<u16> for DenseAngle {
     fn from(bits: u16) -> Self {
-        DenseAngle(
-            move_direction::DenseMoveDirection::from(bits)
-                .0
-                .angle_between(Vec2::X),
-        )
+        DenseAngle((bits & 0xfff) as f32 / Self::STEPS * TAU)
     }
 }
 impl From<DenseAngle> for u16 {
     fn from(angle: DenseAngle) -> Self {
-        u16::from(move_direction::DenseMoveDirection(Vec2::from_angle(
-            angle.0,
-        )))
+        ((angle.0.rem_euclid(TAU) / TAU * DenseAngle::STEPS).round() as u16) & 0xfff
     }
 }
-impl From<u32> for DenseAngle {
-    fn from(bits: u32) -> Self {
+impl From<u64> for DenseAngle {
+    fn from(bits: u64) -> Self {
         let bits_16 = bits as u16;
         bits_16.into()
     }
 }
-impl From<DenseAngle> for u32 {
-    fn from(dir: DenseAngle) -> Self {
-        let bits_16 = u16::from(dir);
-        bits_16 as u32
+impl From<DenseAngle> for u64 {
+    fn from(angle: DenseAngle) -> Self {
+        let bits_16 = u16::from(angle);
+        bits_16 as u64
     }
 }
-impl From<u64> for DenseAngle {
+
+/// How far the stick is tilted, from `0.0` to `1.0`.
+#[derive(Default, Deref, DerefMut, Debug)]
+pub struct DenseMagnitude(pub f32);
+
+/// The [`Quantized`] type for [`DenseMagnitude`], which fits in a byte.
+type MagnitudeQuant = Quantized<IntRange<u16, 0xff, 0, 1>>;
+
+impl From<u64> for DenseMagnitude {
     fn from(bits: u64) -> Self {
-        let bits_32 = bits as u32;
-        bits_32.into()
+        DenseMagnitude(MagnitudeQuant::from_raw(bits as u16 & 0xff).to_f32())
     }
 }
-impl From<DenseAngle> for u64 {
-    fn from(dir: DenseAngle) -> Self {
-        let bits_16 = u32::from(dir);
-        bits_16 as u64
+impl From<DenseMagnitude> for u64 {
+    fn from(magnitude: DenseMagnitude) -> Self {
+        MagnitudeQuant::from_f32(magnitude.0.clamp(0.0, 1.0)).raw() as u64
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    // TODO: This is an insufficient test since default is much more likely to convert properly.
+
+    fn round_trip(input: PlayInput) -> PlayInput {
+        let mut output = PlayInput::default();
+        output.update_from_dense(&input.get_dense_input());
+        output
+    }
+    fn stick(x: f32, y: f32) -> PlayInput {
+        PlayInput { x, y, ..default() }
+    }
+
     #[test]
     pub fn dense_conversions() {
         let mut b = PlayInput::default();
@@ -125,89 +162,71 @@ mod tests {
 
         assert_eq!(PlayInput::default(), b);
     }
-}
 
-mod move_direction {
-    //! Copied from `bones_framework::networking::proto`.
-
-    // use bevy::reflect::Reflect;
-    use numquant::{IntRange, Quantized};
-
-    use crate::prelude::*;
-
-    /// A newtype around [`Vec2`] that implements [`From<u16>`] and [`Into<u16>`] as a way to compress
-    /// user stick input for use in [`self::input::DenseInput`].
-    #[derive(Debug, Deref, DerefMut, Default)]
-    pub struct DenseMoveDirection(pub Vec2);
-
-    /// This is the specific [`Quantized`] type that we use to represent movement directions in
-    /// [`DenseMoveDirection`]. This encodes magnitude of direction, but sign is encoded separately.
-    type MoveDirQuant = Quantized<IntRange<u16, 0b11111, 0, 1>>;
-
-    impl From<u16> for DenseMoveDirection {
-        fn from(bits: u16) -> Self {
-            // maximum movement value representable, we use 6 bits to represent each movement direction.
-            // Most significant is sign, and other 5 encode float value between 0 and
-            let bit_length = 6;
-            let quantized = 0b011111;
-            let sign = 0b100000;
-            // The first six bits represent the x movement
-            let x_move_bits = bits & quantized;
-            let x_move_sign = if bits & sign == 0 { 1.0 } else { -1.0 };
-            // The second six bits represents the y movement
-            let y_move_bits = (bits >> bit_length) & quantized;
-            let y_move_sign = if (bits >> bit_length) & sign == 0 {
-                1.0
-            } else {
-                -1.0
-            };
-
-            // Round near-zero values to zero
-            let mut x = MoveDirQuant::from_raw(x_move_bits).to_f32();
-            x *= x_move_sign;
-            if x.abs() < 0.02 {
-                x = 0.0;
-            }
-            let mut y = MoveDirQuant::from_raw(y_move_bits).to_f32();
-            y *= y_move_sign;
-            if y.abs() < 0.02 {
-                y = 0.0;
-            }
-
-            DenseMoveDirection(Vec2::new(x, y))
+    #[test]
+    pub fn dense_buttons() {
+        for bits in 0..8u8 {
+            let mut input = PlayInput::default();
+            input.shoot.apply_bool(bits & 1 != 0);
+            input.pass.apply_bool(bits & 2 != 0);
+            input.tackle.apply_bool(bits & 4 != 0);
+
+            let output = round_trip(input);
+            assert_eq!(output.shoot.pressed(), bits & 1 != 0);
+            assert_eq!(output.pass.pressed(), bits & 2 != 0);
+            assert_eq!(output.tackle.pressed(), bits & 4 != 0);
         }
     }
-
-    impl From<DenseMoveDirection> for u16 {
-        fn from(dir: DenseMoveDirection) -> Self {
-            let x_bits = MoveDirQuant::from_f32(dir.x.abs()).raw();
-            let y_bits = MoveDirQuant::from_f32(dir.y.abs()).raw();
-            let x_sign_bit = if dir.x.is_sign_positive() {
-                0
-            } else {
-                0b100000
-            };
-            let y_sign_bit = if dir.y.is_sign_positive() {
-                0
-            } else {
-                0b100000
-            };
-
-            (x_bits | x_sign_bit) | ((y_bits | y_sign_bit) << 6)
+    #[test]
+    pub fn dense_stick() {
+        for step in 0..360 {
+            let angle = (step as f32).to_radians();
+            for magnitude in [0.15, 0.3, 0.5, 0.75, 1.0] {
+                let direction = Vec2::from_angle(angle) * magnitude;
+                let output = round_trip(stick(direction.x, direction.y));
+                let output = Vec2::new(output.x, output.y);
+
+                assert!(
+                    (output.length() - magnitude).abs() <= 1.0 / 255.0,
+                    "magnitude {magnitude} became {}",
+                    output.length()
+                );
+                assert!(
+                    direction.angle_between(output).abs() <= TAU / 4096.0,
+                    "angle {angle} became {}",
+                    Vec2::X.angle_between(output)
+                );
+            }
         }
     }
-
-    impl From<u32> for DenseMoveDirection {
-        fn from(bits: u32) -> Self {
-            let bits_16 = bits as u16;
-            bits_16.into()
-        }
+    #[test]
+    pub fn dense_stick_limits() {
+        // Too little tilt to encode centers the stick exactly.
+        assert_eq!(round_trip(stick(0.001, -0.001)), PlayInput::default());
+        // Keyboard diagonals are longer than 1.
+        let output = round_trip(stick(1.0, 1.0));
+        assert!((Vec2::new(output.x, output.y).length() - 1.0).abs() < 1e-3);
+        // The angle wraps around instead of overflowing into the magnitude.
+        let output = round_trip(stick(1.0, -0.0001));
+        assert!((output.x - 1.0).abs() < 1e-3 && output.y.abs() < 1e-2);
     }
-
-    impl From<DenseMoveDirection> for u32 {
-        fn from(dir: DenseMoveDirection) -> Self {
-            let bits_16 = u16::from(dir);
-            bits_16 as u32
-        }
+    #[test]
+    pub fn dense_team() {
+        let mut team = PlayTeamInput {
+            p1: stick(0.0, -0.4),
+            p2: stick(-1.0, 0.0),
+        };
+        team.p1.shoot.apply_bool(true);
+        team.p2.tackle.apply_bool(true);
+
+        let mut output = PlayTeamInput::default();
+        output.update_from_dense(&team.get_dense_input());
+
+        assert_eq!(output.p1.get_dense_input(), team.p1.get_dense_input());
+        assert_eq!(output.p2.get_dense_input(), team.p2.get_dense_input());
+        assert!(output.p1.shoot.pressed() && !output.p1.tackle.pressed());
+        assert!(output.p2.tackle.pressed() && !output.p2.shoot.pressed());
+        assert!((output.p1.y + 0.4).abs() < 1e-2 && output.p1.x.abs() < 1e-2);
+        assert!((output.p2.x + 1.0).abs() < 1e-2 && output.p2.y.abs() < 1e-2);
     }
 }
@@ -0,0 +1,88 @@
+use super::*;
+
+/// How far, in world units, the cursor has to be from a mouse steered player
+/// to move it at full speed.
+pub const MOUSE_STEER_RADIUS: f32 = 32.0;
+
+/// The world position of the cursor, through the [`Camera`] transform.
+///
+/// Returns `None` if the cursor is outside of the window or there is no camera.
+pub fn mouse_world_position(world: &World) -> Option<Vec2> {
+    let ctx = world.get_resource::<EguiCtx>()?;
+    let (pointer, screen) = ctx.input(|r| (r.pointer.latest_pos(), r.screen_rect()));
+    let pointer = pointer?;
+
+    let entities = world.resource::<Entities>();
+    let cameras = world.component::<Camera>();
+    let transforms = world.component::<Transform>();
+    let (_camera_e, (camera, transform)) = entities.iter_with((&cameras, &transforms)).next()?;
+
+    let units_per_point = match camera.size {
+        CameraSize::FixedHeight(height) => height / screen.height(),
+        CameraSize::FixedWidth(width) => width / screen.width(),
+        CameraSize::Min {
+            min_width,
+            min_height,
+        } => (min_width / screen.width()).max(min_height / screen.height()),
+    };
+    let from_center = pointer - screen.center();
+
+    Some(
+        transform.translation.xy()
+            + vec2(from_center.x, -from_center.y) * units_per_point * transform.scale.xy(),
+    )
+}
+
+/// Steers the input toward the cursor the way a stick would, slowing down
+/// within [`MOUSE_STEER_RADIUS`].
+///
+/// The offset goes through the default [`StickSettings`] so that the player
+/// doesn't jitter around the cursor.
+pub fn apply_mouse_steer(input: &mut PlayInput, player_pos: Vec2, cursor: Vec2) {
+    let offset = cursor - player_pos;
+    let stick = offset.normalize_or_zero() * (offset.length() / MOUSE_STEER_RADIUS).min(1.0);
+    let steer = StickSettings::default().apply(stick);
+    input.x = steer.x;
+    input.y = steer.y;
+}
+
+/// Points the input along the `aim` toward the cursor while the player is
+/// shooting, and along the held movement keys, the `keys`, otherwise.
+///
+/// The movement keys only change the input when they are pressed or
+/// released, so their direction is kept apart from the aim for the player to
+/// walk with again once the shot is over.
+pub fn apply_mouse_aim(input: &mut PlayInput, keys: Vec2, aim: Option<Vec2>) {
+    let direction = aim.map_or(Vec2::ZERO, Vec2::normalize_or_zero);
+    let stick = if direction == Vec2::ZERO {
+        keys
+    } else {
+        direction
+    };
+    input.x = stick.x;
+    input.y = stick.y;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aim_until_the_shot_is_over() {
+        let mut input = PlayInput::default();
+        let keys = vec2(1.0, 0.0);
+        apply_mouse_aim(&mut input, keys, None);
+        assert_eq!((input.x, input.y), (1.0, 0.0));
+
+        // Shooting points at the cursor, whatever keys are held.
+        apply_mouse_aim(&mut input, keys, Some(vec2(0.0, 10.0)));
+        assert_eq!((input.x, input.y), (0.0, 1.0));
+
+        // Once the shot is over the player walks with the keys again.
+        apply_mouse_aim(&mut input, keys, None);
+        assert_eq!((input.x, input.y), (1.0, 0.0));
+        apply_mouse_aim(&mut input, Vec2::ZERO, Some(vec2(0.0, -10.0)));
+        apply_mouse_aim(&mut input, Vec2::ZERO, None);
+        assert_eq!((input.x, input.y), (0.0, 0.0));
+    }
+}
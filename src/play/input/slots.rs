@@ -0,0 +1,265 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+/// The most machines that can play in one match, one for each player.
+pub const MAX_PEERS: usize = 4;
+
+/// Which client input controls a [`PlayerSlot`].
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct SlotOwner {
+    /// The index of the client in [`PlayTeamInputs::clients`], which is the
+    /// player index of the peer in a network match.
+    pub peer: u8,
+    /// Whether the slot follows the second [`PlayInput`] of the client.
+    pub secondary: bool,
+    /// Whether the peer runs a cpu for the slot because there weren't
+    /// enough players to go around.
+    pub cpu: bool,
+}
+
+/// The [`SlotOwner`] of every [`PlayerSlot`].
+///
+/// By default each client controls a whole team, which is how offline
+/// matches and two machine network matches are played.
+#[derive(HasSchema, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotAssignment {
+    owners: [SlotOwner; 4],
+}
+impl Default for SlotAssignment {
+    fn default() -> Self {
+        Self {
+            owners: PlayerSlot::variants().map(|slot| SlotOwner {
+                peer: slot.team() as u8,
+                secondary: slot.is_secondary(),
+                cpu: false,
+            }),
+        }
+    }
+}
+impl SlotAssignment {
+    pub fn owner(&self, slot: PlayerSlot) -> SlotOwner {
+        self.owners[slot.index()]
+    }
+    /// The slot that follows the input of the peer, the primary or the
+    /// secondary one.
+    pub fn slot(&self, peer: u8, secondary: bool) -> Option<PlayerSlot> {
+        PlayerSlot::variants().into_iter().find(|slot| {
+            let owner = self.owner(*slot);
+            owner.peer == peer && owner.secondary == secondary
+        })
+    }
+    /// Gives out the slots to the peers, `players` being how many local
+    /// players each peer has, which has to be the same on every peer.
+    ///
+    /// Peers with two players get a team to themselves, the rest are spread
+    /// between the teams, and a peer with a free input runs a cpu in any
+    /// slot left over, preferring the teammate of the cpu.
+    pub fn assign(players: &[u8]) -> Result<Self, String> {
+        Self::assign_sides(players, &[])
+    }
+    /// Like [`SlotAssignment::assign`], but the peers get the team in `sides`
+    /// when there is room for their players on it.
+    ///
+    /// The peers with one player that picked a side are placed before the
+    /// ones that didn't.
+    pub fn assign_sides(players: &[u8], sides: &[Option<Team>]) -> Result<Self, String> {
+        if players.is_empty() || players.len() > MAX_PEERS {
+            return Err(format!("can't play with {} machines", players.len()));
+        }
+        let total = players.iter().map(|count| *count as usize).sum::<usize>();
+        if total > 4 {
+            return Err(format!("too many players ({total}/4)"));
+        }
+
+        let mut owners: [Option<SlotOwner>; 4] = [None; 4];
+        let mut used = vec![0u8; players.len()];
+        let is_free =
+            |owners: &[Option<SlotOwner>; 4], slot: PlayerSlot| owners[slot.index()].is_none();
+
+        let side = |peer: usize| sides.get(peer).copied().flatten();
+
+        for (peer, _) in players.iter().enumerate().filter(|(_, count)| **count >= 2) {
+            let team = side(peer)
+                .into_iter()
+                .chain([Team::A, Team::B])
+                .find(|team| team.slots().iter().all(|slot| is_free(&owners, *slot)))
+                .ok_or_else(|| "too many machines with two players".to_string())?;
+            for slot in team.slots() {
+                owners[slot.index()] = Some(SlotOwner {
+                    peer: peer as u8,
+                    secondary: slot.is_secondary(),
+                    cpu: false,
+                });
+            }
+            used[peer] = 2;
+        }
+        let mut singles = (0..players.len())
+            .filter(|peer| players[*peer] == 1)
+            .collect::<Vec<_>>();
+        singles.sort_by_key(|peer| side(*peer).is_none());
+        for peer in singles {
+            let free = |team: Team| {
+                team.slots()
+                    .into_iter()
+                    .filter(|slot| is_free(&owners, *slot))
+                    .count()
+            };
+            let team = side(peer).filter(|team| free(*team) > 0).unwrap_or(
+                if free(Team::B) > free(Team::A) {
+                    Team::B
+                } else {
+                    Team::A
+                },
+            );
+            let slot = team
+                .slots()
+                .into_iter()
+                .find(|slot| is_free(&owners, *slot))
+                .unwrap();
+            owners[slot.index()] = Some(SlotOwner {
+                peer: peer as u8,
+                secondary: false,
+                cpu: false,
+            });
+            used[peer] = 1;
+        }
+        for slot in PlayerSlot::variants() {
+            if !is_free(&owners, slot) {
+                continue;
+            }
+            let teammate = owners[slot.partner().index()]
+                .filter(|owner| !owner.cpu)
+                .map(|owner| owner.peer as usize);
+            let peer = teammate
+                .filter(|peer| used[*peer] < 2)
+                .or_else(|| (0..players.len()).find(|peer| used[*peer] < 2))
+                .ok_or_else(|| "no machine can run the cpu".to_string())?;
+            owners[slot.index()] = Some(SlotOwner {
+                peer: peer as u8,
+                secondary: used[peer] == 1,
+                cpu: true,
+            });
+            used[peer] += 1;
+        }
+
+        Ok(Self {
+            owners: owners.map(Option::unwrap),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owners(slots: SlotAssignment) -> [(u8, bool, bool); 4] {
+        PlayerSlot::variants().map(|slot| {
+            let owner = slots.owner(slot);
+            (owner.peer, owner.secondary, owner.cpu)
+        })
+    }
+
+    #[test]
+    fn two_peers() {
+        assert_eq!(
+            SlotAssignment::assign(&[2, 2]),
+            Ok(SlotAssignment::default())
+        );
+        assert_eq!(
+            owners(SlotAssignment::assign(&[1, 2]).unwrap()),
+            [
+                (1, false, false),
+                (1, true, false),
+                (0, false, false),
+                (0, true, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn four_peers() {
+        assert_eq!(
+            owners(SlotAssignment::assign(&[1, 1, 1, 1]).unwrap()),
+            [
+                (0, false, false),
+                (2, false, false),
+                (1, false, false),
+                (3, false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn three_peers() {
+        assert_eq!(
+            owners(SlotAssignment::assign(&[1, 2, 1]).unwrap()),
+            [
+                (1, false, false),
+                (1, true, false),
+                (0, false, false),
+                (2, false, false)
+            ]
+        );
+        // The cpu is run by the teammate of the missing player.
+        assert_eq!(
+            owners(SlotAssignment::assign(&[1, 1, 1]).unwrap()),
+            [
+                (0, false, false),
+                (2, false, false),
+                (1, false, false),
+                (1, true, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn sides() {
+        assert_eq!(
+            owners(SlotAssignment::assign_sides(&[1, 1], &[Some(Team::A), Some(Team::A)]).unwrap()),
+            [
+                (0, false, false),
+                (1, false, false),
+                (0, true, true),
+                (1, true, true)
+            ]
+        );
+        // The peer without a side fills in around the ones with a side.
+        assert_eq!(
+            owners(
+                SlotAssignment::assign_sides(&[1, 1, 1], &[None, Some(Team::B), Some(Team::B)])
+                    .unwrap()
+            ),
+            [
+                (0, false, false),
+                (0, true, true),
+                (1, false, false),
+                (2, false, false)
+            ]
+        );
+        assert_eq!(
+            owners(SlotAssignment::assign_sides(&[2, 1], &[Some(Team::B), None]).unwrap()),
+            [
+                (1, false, false),
+                (1, true, true),
+                (0, false, false),
+                (0, true, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn too_many_players() {
+        assert!(SlotAssignment::assign(&[2, 2, 1]).is_err());
+        assert!(SlotAssignment::assign(&[1, 1, 1, 1, 1]).is_err());
+        assert!(SlotAssignment::assign(&[]).is_err());
+    }
+
+    #[test]
+    fn slot_of_peer() {
+        let slots = SlotAssignment::assign(&[1, 1, 1]).unwrap();
+        assert_eq!(slots.slot(1, false), Some(PlayerSlot::B1));
+        assert_eq!(slots.slot(1, true), Some(PlayerSlot::B2));
+        assert_eq!(slots.slot(2, true), None);
+    }
+}
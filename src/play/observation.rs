@@ -0,0 +1,152 @@
+use super::*;
+use serde::Serialize;
+
+pub mod prelude {
+    pub use super::{
+        BallObservation, GameObservation, ObservationPlugin, PinObservation, PlayerObservation,
+    };
+}
+
+/// A plain snapshot of the match for anything outside of the ECS,
+/// like external bots.
+///
+/// Vectors are `[x, y]` arrays and slots, teams and states are strings
+/// so that it serializes into something easy to read from other languages.
+#[derive(HasSchema, Clone, Debug, Default, Serialize)]
+pub struct GameObservation {
+    /// The amount of frames observed so far.
+    pub frame: u64,
+    pub players: Vec<PlayerObservation>,
+    pub ball: BallObservation,
+    /// The pins that are still standing.
+    pub pins: Vec<PinObservation>,
+    pub score_a: u8,
+    pub score_b: u8,
+}
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PlayerObservation {
+    pub slot: String,
+    pub team: String,
+    pub pos: [f32; 2],
+    /// The movement since the previous frame.
+    pub velocity: [f32; 2],
+    pub angle: [f32; 2],
+    pub state: String,
+    pub cpu: bool,
+}
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct BallObservation {
+    pub pos: [f32; 2],
+    pub velocity: [f32; 2],
+    /// The slot of the player dribbling the ball.
+    pub owner: Option<String>,
+}
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PinObservation {
+    pub team: String,
+    pub pos: [f32; 2],
+}
+
+impl GameObservation {
+    pub fn player(&self, slot: PlayerSlot) -> Option<&PlayerObservation> {
+        let slot = format!("{slot:?}");
+        self.players.iter().find(|player| player.slot == slot)
+    }
+}
+
+/// Keeps the [`GameObservation`] resource up to date at the start of every
+/// frame while a [`CpuBrain`] observes the game.
+pub struct ObservationPlugin;
+impl SessionPlugin for ObservationPlugin {
+    fn install(self, session: &mut SessionBuilder) {
+        session
+            .init_resource::<GameObservation>()
+            .add_system_to_stage(First, observe_game);
+    }
+}
+
+/// Counts the frame and updates the rest of the [`GameObservation`] only if
+/// one of the cpu brains reads it, so matches without one don't rebuild it.
+pub fn observe_game(world: &World) {
+    world.resource_mut::<GameObservation>().frame += 1;
+
+    let brains = world.run_system(cpu_brain_names, ());
+    let observed = world.get_resource::<CpuBrains>().is_some_and(|registry| {
+        brains
+            .into_iter()
+            .filter_map(|name| registry.get(name))
+            .any(|brain| brain.observes_game())
+    });
+    if observed {
+        world.run_system(update_observation, ());
+    }
+}
+
+fn cpu_brain_names(entities: Res<Entities>, cpu_players: Comp<CpuPlayer>) -> Vec<Ustr> {
+    entities
+        .iter_with(&cpu_players)
+        .map(|(_, cpu_player)| cpu_player.brain)
+        .collect()
+}
+
+pub fn update_observation(
+    entities: Res<Entities>,
+    transforms: Comp<Transform>,
+    players: Comp<Player>,
+    cpu_players: Comp<CpuPlayer>,
+    states: Comp<State>,
+    balls: Comp<Ball>,
+    pins: Comp<Pin>,
+    teams: Comp<Team>,
+    pin_score: Res<PinScore>,
+    mut observation: ResMut<GameObservation>,
+) {
+    let get_pos = |entity: Entity| transforms.get(entity).unwrap().translation.xy();
+
+    let mut new_players = Vec::new();
+    for (player_e, (player, state)) in entities.iter_with((&players, &states)) {
+        let pos = get_pos(player_e);
+        let slot = format!("{:?}", player.slot);
+        let previous_pos = observation
+            .players
+            .iter()
+            .find(|previous| previous.slot == slot)
+            .map_or(pos, |previous| Vec2::from(previous.pos));
+
+        new_players.push(PlayerObservation {
+            team: format!("{:?}", player.team()),
+            slot,
+            pos: pos.into(),
+            velocity: (pos - previous_pos).into(),
+            angle: player.angle.into(),
+            state: state.current.to_string(),
+            cpu: cpu_players.get(player_e).is_some(),
+        });
+    }
+    new_players.sort_by(|a, b| a.slot.cmp(&b.slot));
+
+    if let Some((ball_e, ball)) = entities.iter_with(&balls).next() {
+        observation.ball = BallObservation {
+            pos: get_pos(ball_e).into(),
+            velocity: ball.velocity.into(),
+            owner: ball
+                .owner
+                .option()
+                .and_then(|owner| players.get(owner))
+                .map(|owner| format!("{:?}", owner.slot)),
+        };
+    }
+
+    observation.pins = entities
+        .iter_with((&pins, &teams))
+        .filter(|(_pin_e, (pin, _team))| pin.standing)
+        .map(|(pin_e, (_pin, team))| PinObservation {
+            team: format!("{team:?}"),
+            pos: get_pos(pin_e).into(),
+        })
+        .collect();
+
+    observation.score_a = pin_score.a;
+    observation.score_b = pin_score.b;
+    observation.players = new_players;
+}
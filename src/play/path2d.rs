@@ -4,6 +4,7 @@ pub mod color {
     use super::*;
 
     pub const BALL: Color = Color::CYAN;
+    pub const BALL_TRAJECTORY: Color = Color::CYAN;
     pub const PIN: Color = Color::RED;
     pub const BOUNDS: Color = Color::RED;
 
@@ -12,6 +13,14 @@ pub mod color {
     pub const SHOOT: Color = Color::YELLOW;
     pub const AIM: Color = Color::RED;
     pub const DRIBBLE: Color = Color::GREEN;
+
+    pub const CPU_CHASE: Color = Color::RED;
+    pub const CPU_GUARD: Color = Color::BLUE;
+    pub const CPU_DRIVE: Color = Color::GREEN;
+    pub const CPU_AIM: Color = Color::ORANGE;
+    pub const CPU_FIRE: Color = Color::YELLOW;
+    pub const CPU_CATCH: Color = Color::CYAN;
+    pub const CPU_SAVE: Color = Color::PURPLE;
 }
 
 pub fn bounds(root: &Data) -> Path2d {
@@ -27,6 +36,16 @@ pub fn bounds(root: &Data) -> Path2d {
     }
 }
 
+/// The points are filled in every frame by the [`BallPlugin`].
+pub fn ball_trajectory() -> Path2d {
+    Path2d {
+        color: color::BALL_TRAJECTORY,
+        points: Vec::new(),
+        thickness: 1.,
+        line_breaks: Vec::new(),
+    }
+}
+
 pub fn player(root: &Data) -> Path2d {
     Path2d {
         color: color::FREE,
@@ -63,3 +82,11 @@ pub fn pin(root: &Data) -> Path2d {
         line_breaks: vec![],
     }
 }
+pub fn cpu_state(root: &Data) -> Path2d {
+    Path2d {
+        color: color::CPU_CHASE,
+        points: circle_points(root.constant.player_radius * 2.0, 12),
+        thickness: 1.,
+        line_breaks: vec![],
+    }
+}
@@ -4,8 +4,16 @@ pub mod prelude {
     pub use super::{Pin, PinPlugin, PinScore};
 }
 
-#[derive(HasSchema, Clone, Default)]
-pub struct Pin;
+#[derive(HasSchema, Clone)]
+pub struct Pin {
+    /// Cleared once the ball knocks the pin over, before it finishes exploding.
+    pub standing: bool,
+}
+impl Default for Pin {
+    fn default() -> Self {
+        Self { standing: true }
+    }
+}
 
 #[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct PinScore {
@@ -60,7 +68,7 @@ pub fn animation_bank() -> AnimationBankSprite {
 }
 
 pub fn update(
-    pins: Comp<Pin>,
+    mut pins: CompMut<Pin>,
     teams: Comp<Team>,
     balls: Comp<Ball>,
     transforms: Comp<Transform>,
@@ -80,9 +88,9 @@ pub fn update(
 
     let Sounds { pin_explosion, .. } = root.sound;
 
-    for (pin_e, (_pin, team)) in entities.iter_with((&pins, &teams)) {
+    for (pin_e, (pin, team)) in entities.iter_with((&mut pins, &teams)) {
         let bank = banks.get_mut(pin_e).unwrap();
-        if bank.current == ustr("still") {
+        if pin.standing {
             let pin_pos = transforms.get(pin_e).unwrap().translation.xy();
             for (ball_e, ball) in entities.iter_with(&balls) {
                 if ball.owner.is_none() {
@@ -92,6 +100,7 @@ pub fn update(
                             Team::A => score.inc_b(),
                             Team::B => score.inc_a(),
                         }
+                        pin.standing = false;
                         bank.set_current("explode");
                         audio.play_sound(*pin_explosion, pin_explosion.volume());
                     }
@@ -102,6 +102,13 @@ impl Team {
             Team::B => Vec2::X,
         }
     }
+    /// Returns the primary and secondary [`PlayerSlot`]s of the team.
+    pub fn slots(&self) -> [PlayerSlot; 2] {
+        match self {
+            Team::A => [PlayerSlot::A1, PlayerSlot::A2],
+            Team::B => [PlayerSlot::B1, PlayerSlot::B2],
+        }
+    }
 }
 
 #[derive(HasSchema, Clone, Default, Copy, PartialEq, Eq, Hash, Debug)]
@@ -179,6 +186,8 @@ pub struct Player {
     pub slot: PlayerSlot,
     pub flip_x: bool,
     pub animation: Ustr,
+    /// The presses waiting for the player to be able to act on them.
+    pub buffer: ActionBuffer,
 }
 impl Player {
     pub fn new(id: PlayerSlot) -> Self {
@@ -191,6 +200,7 @@ impl Player {
             slot: id,
             flip_x: false,
             animation: ustr("idle"),
+            buffer: default(),
         }
     }
     pub fn team(&self) -> Team {
@@ -207,6 +217,7 @@ pub struct PlayerPlugin;
 impl SessionPlugin for PlayerPlugin {
     fn install(self, session: &mut SessionBuilder) {
         session
+            .add_system_to_stage(First, buffer_actions)
             .add_system_to_stage(StateStage, dribble_transition)
             .add_system_to_stage(StateStage, free_transition)
             .add_system_to_stage(StateStage, recieve_transition)
@@ -245,6 +256,25 @@ impl SessionPlugin for PlayerPlugin {
     }
 }
 
+/// Buffers the presses of every player before the transitions use them.
+///
+/// This runs once per frame, unlike the [`StateStage`] that loops until the
+/// states settle and would age and buffer the presses again every loop.
+fn buffer_actions(
+    inputs: Res<PlayTeamInputs>,
+    player_ent_signs: Res<PlayerEntSigns>,
+    root: Root<Data>,
+    mut players: CompMut<Player>,
+) {
+    for player_e in player_ent_signs.entities() {
+        let player = players.get_mut(player_e).unwrap();
+        let control = inputs.get_character_control(player.slot);
+        player
+            .buffer
+            .update(control, root.constant.input_buffer_frames);
+    }
+}
+
 //
 // Transitions
 //
@@ -382,20 +412,18 @@ fn to_dribble_transition(
 
 fn dribble_out_transition(
     In(player_e): In<Entity>,
-    inputs: Res<PlayTeamInputs>,
     player_ent_signs: Res<PlayerEntSigns>,
     transforms: CompMut<Transform>,
     mut players: CompMut<Player>,
     mut states: CompMut<State>,
 ) {
-    let control = inputs.get_character_control(players.get(player_e).unwrap().slot);
-
     // pass
-    if control.pass.just_pressed() {
+    if players.get(player_e).unwrap().buffer.pass > 0 {
         let partner_e = player_ent_signs.get_partner(player_e);
         let partner_state = states.get_mut(partner_e).unwrap();
 
         if partner_state.current == state::free() {
+            players.get_mut(player_e).unwrap().buffer.take_pass();
             partner_state.current = state::recieve();
 
             states.get_mut(player_e).unwrap().current = state::turn();
@@ -409,7 +437,7 @@ fn dribble_out_transition(
         }
     }
     // shoot
-    if control.shoot.just_pressed() {
+    if players.get_mut(player_e).unwrap().buffer.take_shoot() {
         states.get_mut(player_e).unwrap().current = state::shoot();
 
         let player = players.get_mut(player_e).unwrap();
@@ -491,7 +519,6 @@ fn to_tackled_transition(
 
 fn to_tackle_transition(
     In(player_e): In<Entity>,
-    inputs: Res<PlayTeamInputs>,
     root: Root<Data>,
     mut audio: ResMut<AudioCenter>,
     mut players: CompMut<Player>,
@@ -499,10 +526,9 @@ fn to_tackle_transition(
 ) {
     let player = players.get_mut(player_e).unwrap();
     let state = states.get_mut(player_e).unwrap();
-    let control = inputs.get_character_control(player.slot);
     let Sounds { player_tackle, .. } = root.sound;
 
-    if control.tackle.just_pressed() && state.age() > 0 {
+    if state.age() > 0 && player.buffer.take_tackle() {
         state.current = state::tackle();
         player.action_angle = player.angle;
         audio.play_sound(*player_tackle, player_tackle.volume());
@@ -573,7 +599,7 @@ fn shoot_update(
         let player = players.get_mut(player_e).unwrap();
         let control = inputs.get_character_control(player.slot);
         let direction = Vec2::new(control.x, control.y);
-        if direction.length() > 0.2 {
+        if direction != Vec2::ZERO {
             let direction = direction.normalize();
             let range_clock = Vec2::new(1.0, 0.05 * time.delta_multiplier()).normalize_or_zero();
             let range_count = Vec2::new(1.0, -0.05 * time.delta_multiplier()).normalize_or_zero();
@@ -680,10 +706,12 @@ fn walk(In(player_e): In<Entity>, world: &World) {
         let control = inputs.get_character_control(player.slot);
         let direction = Vec2::new(control.x, control.y);
 
-        if direction.length() > 0.2 {
+        if direction != Vec2::ZERO {
             player.animation = ustr("walk");
             player.angle = direction.normalize_or_zero();
 
+            // A half tilted stick walks at half speed.
+            let speed = speed * direction.length().min(1.0);
             transform.translation.x += player.angle.x * speed * time.delta_multiplier();
             transform.translation.y += player.angle.y * speed * time.delta_multiplier();
         } else {
@@ -7,13 +7,21 @@ pub struct PlayPlugin {
 }
 impl SessionPlugin for PlayPlugin {
     fn install(self, session: &mut SessionBuilder) {
+        let score_target = self.mode.score_target();
+        let start_score = self.mode.start_score();
+        let tactics = self.mode.tactics();
+        let countdown = self.mode.countdown();
         session
             .set_priority(session::PLAY_PRIORITY)
             .install_plugin(DefaultSessionPlugin)
             .install_plugin(self::ScenePlugin { mode: self.mode })
             .install_plugin(self::BehaviorsPlugin)
-            .install_plugin(self::PlayUIPlugin)
-            .install_plugin(self::PlayFlowPlugin);
+            .insert_resource(tactics)
+            .install_plugin(self::PlayUIPlugin { countdown })
+            .install_plugin(self::PlayFlowPlugin {
+                score_target,
+                start_score,
+            });
     }
 }
 
@@ -27,11 +35,16 @@ impl SessionPlugin for BehaviorsPlugin {
             .install_plugin(PinPlugin)
             .install_plugin(LifetimePlugin)
             .install_plugin(FollowPlugin)
+            .install_plugin(ObservationPlugin)
+            .install_plugin(TacticsPlugin)
             .install_plugin(CpuPlayerPlugin);
     }
 }
 
-pub struct PlayUIPlugin;
+pub struct PlayUIPlugin {
+    /// The number the [`Countdown`] counts down from.
+    pub countdown: u8,
+}
 impl SessionPlugin for PlayUIPlugin {
     fn install(self, session: &mut SessionBuilder) {
         session.install_plugin({
@@ -39,7 +52,8 @@ impl SessionPlugin for PlayUIPlugin {
             fade.restart_at_wait();
             fade
         });
-        session.install_plugin(Countdown::new(4.0, 1.2));
+        // One second for each number and one for the "Go".
+        session.install_plugin(Countdown::new(self.countdown as f32 + 1.0, 1.2));
         session.install_plugin(ScoreDisplay::new(3.65));
         session.install_plugin(WinnerBanner::default());
         session.install_plugin(MatchDone::default());
@@ -0,0 +1,155 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+pub mod prelude {
+    pub use super::{capture_match, resume_match, MatchResume, ResumeBall, ResumePlayer};
+}
+
+/// Where a player was when the [`MatchResume`] was taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResumePlayer {
+    pub position: [f32; 2],
+    /// The direction the player faced.
+    pub angle: [f32; 2],
+}
+
+/// Where the ball was when the [`MatchResume`] was taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResumeBall {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub bounced: bool,
+}
+
+/// The state a network match picks up from once the peers found each other
+/// again after one dropped, see [`NetworkGraceUI`].
+///
+/// The host takes it from its match, which has the last say. The players
+/// wait for the countdown like at the start of a round, so the ball is loose
+/// and the state of each player isn't kept.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchResume {
+    /// The score of team a and b.
+    pub score: [u8; 2],
+    /// The heights of the pins of team a and b that are still standing,
+    /// which tell the pins apart the same way on every peer.
+    pub pins: [Vec<f32>; 2],
+    /// The players by slot, unless the match stopped between two rounds,
+    /// in which case the next one starts from the usual places.
+    pub players: Option<[ResumePlayer; 4]>,
+    /// The ball, unless the match stopped between two rounds.
+    pub ball: Option<ResumeBall>,
+    /// How far into the countdown the match stopped, in seconds of the
+    /// countdown, which is run in full when the ball was in play.
+    pub countdown: f32,
+}
+impl MatchResume {
+    pub fn score(&self) -> PinScore {
+        PinScore {
+            a: self.score[0],
+            b: self.score[1],
+        }
+    }
+}
+
+/// Takes the [`MatchResume`] of the match in the play session.
+pub fn capture_match(
+    entities: Res<Entities>,
+    play_state: Res<PlayState>,
+    pin_score: Res<PinScore>,
+    countdown: Res<Countdown>,
+    player_ent_signs: Res<PlayerEntSigns>,
+    players: Comp<Player>,
+    balls: Comp<Ball>,
+    pins: Comp<Pin>,
+    teams: Comp<Team>,
+    transforms: Comp<Transform>,
+) -> MatchResume {
+    let mut standing = [Vec::new(), Vec::new()];
+    for (_pin_e, (pin, team, transform)) in entities.iter_with((&pins, &teams, &transforms)) {
+        if pin.standing {
+            standing[*team as usize].push(transform.translation.y);
+        }
+    }
+    let in_round = matches!(*play_state, PlayState::Countdown | PlayState::WaitForScore);
+    let players = in_round.then(|| {
+        player_ent_signs.entities().map(|player_e| {
+            let player = players.get(player_e).unwrap();
+            let position = transforms.get(player_e).unwrap().translation.xy();
+            ResumePlayer {
+                position: position.to_array(),
+                angle: player.angle.to_array(),
+            }
+        })
+    });
+    let ball = in_round
+        .then(|| entities.iter_with(&balls).next())
+        .flatten()
+        .map(|(ball_e, ball)| ResumeBall {
+            position: transforms.get(ball_e).unwrap().translation.xy().to_array(),
+            velocity: ball.velocity.to_array(),
+            bounced: ball.bounced,
+        });
+    let countdown = match *play_state {
+        PlayState::Countdown => countdown.timer.elapsed_secs(),
+        _ => 0.0,
+    };
+    MatchResume {
+        score: [pin_score.a, pin_score.b],
+        pins: standing,
+        players,
+        ball,
+        countdown,
+    }
+}
+
+/// Puts the match where the [`MatchResume`] of its [`PlayMode`] left it, once
+/// the scene is spawned.
+pub fn resume_match(
+    mode: Res<PlayMode>,
+    root: Root<Data>,
+    mut entities: ResMut<Entities>,
+    player_ent_signs: Res<PlayerEntSigns>,
+    pins: Comp<Pin>,
+    teams: Comp<Team>,
+    mut players: CompMut<Player>,
+    mut balls: CompMut<Ball>,
+    mut transforms: CompMut<Transform>,
+) {
+    let Some(resume) = mode.resume() else {
+        return;
+    };
+    tracing::info!(?resume, "resuming the match");
+
+    // The pins are further apart than their radius.
+    let pin_radius = root.constant.pin_radius;
+    let knocked = entities
+        .iter_with((&pins, &teams, &transforms))
+        .filter(|(_pin_e, (_pin, team, transform))| {
+            !resume.pins[**team as usize]
+                .iter()
+                .any(|height| (height - transform.translation.y).abs() < pin_radius)
+        })
+        .map(|(pin_e, _)| pin_e)
+        .collect::<Vec<_>>();
+    for pin_e in knocked {
+        entities.kill(pin_e);
+    }
+    if let Some(resumed) = resume.players {
+        for (player_e, resumed) in player_ent_signs.entities().into_iter().zip(resumed) {
+            let transform = transforms.get_mut(player_e).unwrap();
+            transform.translation.x = resumed.position[0];
+            transform.translation.y = resumed.position[1];
+            players.get_mut(player_e).unwrap().angle = Vec2::from_array(resumed.angle);
+        }
+    }
+    if let Some(resumed) = resume.ball {
+        for (ball_e, ball) in entities.iter_with(&mut balls) {
+            let transform = transforms.get_mut(ball_e).unwrap();
+            transform.translation.x = resumed.position[0];
+            transform.translation.y = resumed.position[1];
+            ball.velocity = Vec2::from_array(resumed.velocity);
+            ball.bounced = resumed.bounced;
+        }
+    }
+}
@@ -9,12 +9,110 @@ pub enum PlayMode {
     Online {
         socket: NetworkMatchSocket,
         service_type: ServiceType,
+        /// The slots of each peer, agreed on in the lobby.
+        slots: SlotAssignment,
+        /// The rules the host picked in the lobby.
+        rules: MatchRules,
+        /// Where the match picks up from after a peer dropped, see
+        /// [`NetworkGraceUI`].
+        resume: Option<MatchResume>,
+        /// The input delay picked for the match.
+        delay: NetworkDelay,
+        /// Where the inputs are recorded for the spectators, which only
+        /// the host streams to them.
+        spectators: Option<SpectatorFeed>,
     },
-    Offline(PlayersInfo),
+    /// Watching a match from the inputs its host streams, see [`SpectatorRunner`].
+    #[cfg(not(target_arch = "wasm32"))]
+    Spectate {
+        stream: SpectatorStream,
+        slots: SlotAssignment,
+        rules: MatchRules,
+        /// Where the watched match picked up from after a peer dropped.
+        resume: Option<MatchResume>,
+    },
+    /// The players and the formations of the cpus picked in the [`TeamSelect`].
+    Offline(PlayersInfo, CpuTactics),
 }
 impl Default for PlayMode {
     fn default() -> Self {
-        Self::Offline(default())
+        Self::Offline(default(), default())
+    }
+}
+impl PlayMode {
+    /// The spawn information of the player in the slot.
+    pub fn player_info(&self, slot: PlayerSlot) -> PlayerInfo {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayMode::Online {
+                socket,
+                service_type,
+                slots,
+                ..
+            } => {
+                let owner = slots.owner(slot);
+                // Every peer spawns the cpus so that they think the same way.
+                if owner.cpu {
+                    PlayerInfo::cpu()
+                } else if owner.peer as u32 != socket.player_idx() {
+                    PlayerInfo::Network
+                } else if owner.secondary {
+                    service_type.player_info_secondary()
+                } else {
+                    service_type.player_info_primary()
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayMode::Spectate { slots, .. } => {
+                if slots.owner(slot).cpu {
+                    PlayerInfo::cpu()
+                } else {
+                    PlayerInfo::Network
+                }
+            }
+            PlayMode::Offline(PlayersInfo { a1, a2, b1, b2 }, _) => match slot {
+                PlayerSlot::A1 => *a1,
+                PlayerSlot::A2 => *a2,
+                PlayerSlot::B1 => *b1,
+                PlayerSlot::B2 => *b2,
+            },
+        }
+    }
+    /// Where the match picks up from, see [`MatchResume`].
+    pub fn resume(&self) -> Option<&MatchResume> {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayMode::Online { resume, .. } | PlayMode::Spectate { resume, .. } => resume.as_ref(),
+            PlayMode::Offline(..) => None,
+        }
+    }
+    /// The score the match starts from.
+    pub fn start_score(&self) -> PinScore {
+        self.resume().map(MatchResume::score).unwrap_or_default()
+    }
+    /// The score that wins the match.
+    pub fn score_target(&self) -> u8 {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayMode::Online { rules, .. } | PlayMode::Spectate { rules, .. } => rules.score_target,
+            PlayMode::Offline(..) => 7,
+        }
+    }
+    /// The number the countdown before every round counts down from.
+    pub fn countdown(&self) -> u8 {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayMode::Online { rules, .. } | PlayMode::Spectate { rules, .. } => rules.countdown,
+            PlayMode::Offline(..) => 3,
+        }
+    }
+    /// The formations of the cpus of each team.
+    pub fn tactics(&self) -> CpuTactics {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayMode::Online { rules, .. } | PlayMode::Spectate { rules, .. } => rules.tactics,
+            PlayMode::Offline(_, tactics) => *tactics,
+        }
     }
 }
 #[derive(HasSchema, Debug, Clone, Default)]
@@ -24,11 +122,50 @@ pub struct PlayersInfo {
     pub b1: PlayerInfo,
     pub b2: PlayerInfo,
 }
+impl PlayersInfo {
+    pub fn get_mut(&mut self, slot: PlayerSlot) -> &mut PlayerInfo {
+        match slot {
+            PlayerSlot::A1 => &mut self.a1,
+            PlayerSlot::A2 => &mut self.a2,
+            PlayerSlot::B1 => &mut self.b1,
+            PlayerSlot::B2 => &mut self.b2,
+        }
+    }
+    /// The input sources of the primary and secondary players of the team.
+    pub fn sources(&self, team: Team) -> [SingleSource; 2] {
+        team.slots().map(|slot| {
+            let info = match slot {
+                PlayerSlot::A1 => self.a1,
+                PlayerSlot::A2 => self.a2,
+                PlayerSlot::B1 => self.b1,
+                PlayerSlot::B2 => self.b2,
+            };
+            match info {
+                PlayerInfo::CPU { .. } => SingleSource::CPU(slot),
+                PlayerInfo::Local { source, .. } => source,
+                PlayerInfo::Network => unreachable!(),
+            }
+        })
+    }
+    /// Moves the players using one gamepad over to another.
+    pub fn reassign_gamepad(&mut self, from: u32, to: u32) {
+        for slot in PlayerSlot::variants() {
+            if let PlayerInfo::Local { source, .. } = self.get_mut(slot) {
+                if *source == SingleSource::Gamepad(from) {
+                    *source = SingleSource::Gamepad(to);
+                }
+            }
+        }
+    }
+}
 
 /// This is the player spawn information.
 #[derive(HasSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlayerInfo {
-    CPU,
+    CPU {
+        /// The name of the [`CpuBrain`] registered in [`CpuBrains`].
+        brain: Ustr,
+    },
     Network,
     Local {
         /// The user join index for display purposes,
@@ -41,6 +178,14 @@ pub enum PlayerInfo {
         twin_stick: bool,
     },
 }
+impl PlayerInfo {
+    /// A cpu player with the [`DefaultBrain`].
+    pub fn cpu() -> Self {
+        Self::CPU {
+            brain: CpuBrains::default_name(),
+        }
+    }
+}
 impl Default for PlayerInfo {
     fn default() -> Self {
         // This is the best default for dev testing
@@ -62,15 +207,35 @@ pub struct ScenePlugin {
 impl SessionPlugin for ScenePlugin {
     fn install(self, session: &mut SessionBuilder) {
         match &self.mode {
-            PlayMode::Offline(players_info) => {
+            PlayMode::Offline(players_info, _) => {
                 session.runner = offline_session_runner(players_info.clone());
             }
             #[cfg(not(target_arch = "wasm32"))]
             PlayMode::Online {
                 socket,
                 service_type,
+                slots,
+                delay,
+                spectators,
+                ..
             } => {
-                session.runner = lan_session_runner(socket, service_type);
+                let grace = NetworkGrace::default();
+                session.runner = Box::new(GraceRunner {
+                    runner: lan_session_runner(socket, service_type, slots, delay),
+                    grace: grace.clone(),
+                });
+                session.insert_resource(PlayTeamInputs::new(*slots));
+                session.install_plugin(NetworkDiagnostics::default());
+                session.install_plugin(grace);
+                // After the grace, which may change the inputs of the dropped peers.
+                if let Some(feed) = spectators {
+                    session.install_plugin(feed.clone());
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayMode::Spectate { stream, slots, .. } => {
+                session.runner = Box::new(SpectatorRunner::new(stream.clone()));
+                session.insert_resource(PlayTeamInputs::new(*slots));
             }
         };
         session.insert_resource(self.mode);
@@ -80,6 +245,7 @@ impl SessionPlugin for ScenePlugin {
         session.add_system_to_stage(Update, toggle_debug_lines);
 
         session.add_startup_system(spawn::scene);
+        session.add_startup_system(resume_match);
         session.add_startup_system(hide_debug_lines);
         session.add_system_to_stage(Last, |mut inputs: ResMut<PlayTeamInputs>| {
             inputs.advance_frame()
@@ -107,53 +273,40 @@ pub fn hide_debug_lines(mut toggles: CompMut<Path2dToggle>) {
 pub fn lan_session_runner(
     socket: &bones_framework::networking::NetworkMatchSocket,
     service_type: &ServiceType,
+    slots: &SlotAssignment,
+    delay: &NetworkDelay,
 ) -> Box<dyn SessionRunner> {
     use bones_framework::networking::{GgrsSessionRunner, GgrsSessionRunnerInfo};
 
+    tracing::info!(?delay, "starting network match");
     let mut runner = GgrsSessionRunner::<PlayTeamDenseInputConfig>::new(
         Some(60.0),
-        GgrsSessionRunnerInfo::new(socket.ggrs_socket(), Some(7), Some(2), 0),
+        GgrsSessionRunnerInfo::new(
+            socket.ggrs_socket(),
+            Some(delay.max_prediction as usize),
+            Some(delay.input_delay as usize),
+            0,
+        ),
     );
-    match service_type {
-        ServiceType::OnePlayer(p1) => {
-            runner
-                .input_collector
-                .set_sources(SingleSource::Gamepad(*p1), SingleSource::Gamepad(*p1));
-        }
-        ServiceType::TwoPlayer(p1, p2) => {
-            runner.input_collector.set_sources(*p1, *p2);
-        }
-    }
+    // The input of this peer controls its own players followed by the cpus
+    // it runs, if any.
+    let peer = socket.player_idx() as u8;
+    let local_slots = [false, true].map(|secondary| slots.slot(peer, secondary));
+    let [p1, p2] = [0, 1].map(|i| match local_slots[i] {
+        Some(slot) if slots.owner(slot).cpu => SingleSource::CPU(slot),
+        _ => service_type.sources()[i],
+    });
+    runner.input_collector.set_sources(p1, p2);
+    runner.input_collector.set_slots(local_slots);
     Box::new(runner)
 }
 pub fn offline_session_runner(players_info: PlayersInfo) -> Box<dyn SessionRunner> {
-    let PlayersInfo { a1, a2, b1, b2 } = players_info;
+    let [a1, a2] = players_info.sources(Team::A);
+    let [b1, b2] = players_info.sources(Team::B);
     Box::new(OfflineRunner {
         collectors: [
-            PlayTeamInputCollector::new(
-                match a1 {
-                    PlayerInfo::CPU => SingleSource::CPU(PlayerSlot::A1),
-                    PlayerInfo::Local { source, .. } => source,
-                    PlayerInfo::Network => unreachable!(),
-                },
-                match a2 {
-                    PlayerInfo::CPU => SingleSource::CPU(PlayerSlot::A2),
-                    PlayerInfo::Local { source, .. } => source,
-                    PlayerInfo::Network => unreachable!(),
-                },
-            ),
-            PlayTeamInputCollector::new(
-                match b1 {
-                    PlayerInfo::CPU => SingleSource::CPU(PlayerSlot::B1),
-                    PlayerInfo::Local { source, .. } => source,
-                    PlayerInfo::Network => unreachable!(),
-                },
-                match b2 {
-                    PlayerInfo::CPU => SingleSource::CPU(PlayerSlot::B2),
-                    PlayerInfo::Local { source, .. } => source,
-                    PlayerInfo::Network => unreachable!(),
-                },
-            ),
+            PlayTeamInputCollector::new(a1, a2),
+            PlayTeamInputCollector::new(b1, b2).with_team(Team::B),
         ],
         ..Default::default()
     })
@@ -48,56 +48,22 @@ pub fn scene(world: &World) {
         .insert(ball::sprite())
         .insert(Transform::from_translation(Vec3::new(0., 0., layers::BALL)));
 
+    // Ball Trajectory
+    world
+        .spawn()
+        .insert(BallTrajectoryPath)
+        .insert(path2d::ball_trajectory())
+        .insert(Path2dToggle::new(path2d::color::BALL_TRAJECTORY))
+        .insert(Transform::from_z(layers::OVERLAY));
+
     // Players
-    let ent_signs = match &*world.resource::<PlayMode>() {
-        #[cfg(not(target_arch = "wasm32"))]
-        PlayMode::Online {
-            socket,
-            service_type,
-        } => {
-            let primary_info = service_type.player_info_primary();
-            let secondary_info = service_type.player_info_secondary();
-
-            let local_team = match socket.player_idx() {
-                0 => Team::A,
-                1 => Team::B,
-                _ => panic!("index out of player count bounds"),
-            };
-
-            let a1 = if local_team == Team::A {
-                primary_info
-            } else {
-                PlayerInfo::Network
-            };
-            let a2 = if local_team == Team::A {
-                secondary_info
-            } else {
-                PlayerInfo::Network
-            };
-            let b1 = if local_team == Team::B {
-                primary_info
-            } else {
-                PlayerInfo::Network
-            };
-            let b2 = if local_team == Team::B {
-                secondary_info
-            } else {
-                PlayerInfo::Network
-            };
-
-            PlayerEntSigns {
-                a1: self::player(world, a1, PlayerSlot::A1),
-                a2: self::player(world, a2, PlayerSlot::A2),
-                b1: self::player(world, b1, PlayerSlot::B1),
-                b2: self::player(world, b2, PlayerSlot::B2),
-            }
-        }
-        PlayMode::Offline(PlayersInfo { a1, a2, b1, b2 }) => PlayerEntSigns {
-            a1: self::player(world, *a1, PlayerSlot::A1),
-            a2: self::player(world, *a2, PlayerSlot::A2),
-            b1: self::player(world, *b1, PlayerSlot::B1),
-            b2: self::player(world, *b2, PlayerSlot::B2),
-        },
+    let [a1, a2, b1, b2] =
+        PlayerSlot::variants().map(|slot| world.resource::<PlayMode>().player_info(slot));
+    let ent_signs = PlayerEntSigns {
+        a1: self::player(world, a1, PlayerSlot::A1),
+        a2: self::player(world, a2, PlayerSlot::A2),
+        b1: self::player(world, b1, PlayerSlot::B1),
+        b2: self::player(world, b2, PlayerSlot::B2),
     };
     world.insert_resource(ent_signs);
 
@@ -123,7 +89,7 @@ pub fn pins(world: &World, root: Root<Data>) {
 
         world
             .spawn()
-            .insert(Pin)
+            .insert(Pin::default())
             .insert(Team::A)
             .insert(AtlasSprite::new(a_pin))
             .insert(pin::animation_bank())
@@ -138,7 +104,7 @@ pub fn pins(world: &World, root: Root<Data>) {
 
         world
             .spawn()
-            .insert(Pin)
+            .insert(Pin::default())
             .insert(Team::B)
             .insert(AtlasSprite::new(b_pin))
             .insert(pin::animation_bank())
@@ -284,7 +250,7 @@ pub fn player(world: &World, player_info: PlayerInfo, slot: PlayerSlot) -> Entit
                 })
                 .insert(Transform::from_z(layers::PLAYER_SHADOW));
         }
-        PlayerInfo::CPU => {
+        PlayerInfo::CPU { brain } => {
             // indicator
             world
                 .spawn()
@@ -316,17 +282,7 @@ pub fn player(world: &World, player_info: PlayerInfo, slot: PlayerSlot) -> Entit
                 })
                 .insert(Transform::from_z(layers::PLAYER_SHADOW));
 
-            let mut cpu_state = world.spawn();
-            // This entity is not tagged very much but the only thing
-            // that needs to reference it is the cpu component below it.
-            cpu_state
-                .insert(CpuPlayerState)
-                .insert(State::new(&cpu_player::state::chase()));
-
-            player.insert(CpuPlayer {
-                state_e: cpu_state.entity,
-                input: Default::default(),
-            });
+            cpu_player(world, player.id(), brain);
         }
     }
 
@@ -351,3 +307,33 @@ pub fn player(world: &World, player_info: PlayerInfo, slot: PlayerSlot) -> Entit
 
     player.id()
 }
+
+/// Makes the player a cpu, which is also how a network player is taken over
+/// when its peer drops.
+pub fn cpu_player(world: &World, player_e: Entity, brain: Ustr) {
+    let root = world.asset_server().root::<Data>();
+
+    let mut cpu_state = world.spawn();
+    // This entity is not tagged very much but the only thing
+    // that needs to reference it is the cpu component below it.
+    cpu_state
+        .insert(CpuPlayerState)
+        .insert(State::new(&cpu_player::state::chase()))
+        .insert(path2d::cpu_state(&root))
+        .insert(Path2dToggle::new(path2d::color::CPU_CHASE))
+        .insert(Follow::XY {
+            target: player_e,
+            offset: Vec2::ZERO,
+        })
+        .insert(Transform::from_z(layers::OVERLAY));
+
+    world.component_mut::<CpuPlayer>().insert(
+        player_e,
+        CpuPlayer {
+            state_e: cpu_state.entity,
+            input: Default::default(),
+            role: Default::default(),
+            brain,
+        },
+    );
+}
@@ -0,0 +1,138 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+pub mod prelude {
+    pub use super::{CpuRole, CpuTactics, Formation, Possession, TacticsPlugin};
+}
+
+/// How a team of cpus positions the player that isn't going for the ball.
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Formation {
+    Aggressive,
+    #[default]
+    Balanced,
+    Defensive,
+}
+impl Formation {
+    pub fn cycle(&mut self, direction: i8) {
+        let formations = [
+            Formation::Aggressive,
+            Formation::Balanced,
+            Formation::Defensive,
+        ];
+        let position = formations
+            .iter()
+            .position(|formation| formation == self)
+            .unwrap() as isize;
+        *self = formations
+            [(position + direction as isize).rem_euclid(formations.len() as isize) as usize];
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Formation::Aggressive => "Aggressive",
+            Formation::Balanced => "Balanced",
+            Formation::Defensive => "Defensive",
+        }
+    }
+    /// How far back the defensive line sits as a fraction of the distance
+    /// from the middle of the field to the team's own side.
+    pub fn line_depth(&self) -> f32 {
+        match self {
+            Formation::Aggressive => 0.25,
+            Formation::Balanced => 0.5,
+            Formation::Defensive => 0.75,
+        }
+    }
+    /// The role of the player that isn't going for the ball.
+    ///
+    /// `ball_progress` is how far the ball is into the enemy side,
+    /// from `-1.0` at the team's own side to `1.0` at the enemy side.
+    pub fn off_ball_role(&self, possession: Possession, ball_progress: f32) -> CpuRole {
+        use CpuRole::*;
+        match (self, possession) {
+            (Formation::Aggressive, Possession::Enemy) if ball_progress < -0.5 => Defender,
+            (Formation::Aggressive, _) => Support,
+            (Formation::Balanced, Possession::Team) => Support,
+            (Formation::Balanced, Possession::Loose) if ball_progress > 0.0 => Support,
+            (Formation::Balanced, _) => Defender,
+            (Formation::Defensive, Possession::Enemy) => Sweeper,
+            (Formation::Defensive, _) => Defender,
+        }
+    }
+}
+
+/// Which team has the ball from the perspective of one team.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Possession {
+    Team,
+    Enemy,
+    Loose,
+}
+
+/// The job of a cpu player within its team.
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum CpuRole {
+    /// Goes for the ball.
+    #[default]
+    Attacker,
+    /// Stays ahead of the ball to be open for a pass.
+    Support,
+    /// Holds the defensive line between the ball and the pins.
+    Defender,
+    /// Stays right in front of the pins to block shots.
+    Sweeper,
+}
+
+/// The selected [`Formation`] of each team.
+///
+/// They're picked in the [`TeamSelect`] offline and by the host in the
+/// [`MatchRules`] of a network match, see [`PlayMode::tactics`].
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct CpuTactics {
+    pub team_a: Formation,
+    pub team_b: Formation,
+}
+impl CpuTactics {
+    pub fn formation(&self, team: Team) -> Formation {
+        match team {
+            Team::A => self.team_a,
+            Team::B => self.team_b,
+        }
+    }
+    pub fn formation_mut(&mut self, team: Team) -> &mut Formation {
+        match team {
+            Team::A => &mut self.team_a,
+            Team::B => &mut self.team_b,
+        }
+    }
+}
+
+/// Assigns each cpu player a [`CpuRole`] at the start of every frame,
+/// before the cpu state transitions run.
+pub struct TacticsPlugin;
+impl SessionPlugin for TacticsPlugin {
+    fn install(self, session: &mut SessionBuilder) {
+        session
+            .init_resource::<CpuTactics>()
+            .add_system_to_stage(First, assign_cpu_roles);
+    }
+}
+
+pub fn assign_cpu_roles(world: &World) {
+    for player_e in world.resource::<PlayerEntSigns>().entities() {
+        if world.component::<CpuPlayer>().get(player_e).is_none() {
+            continue;
+        }
+        let role = world.run_system(cpu_senses, player_e).choose_role();
+
+        let mut cpu_players = world.component_mut::<CpuPlayer>();
+        let cpu_player = cpu_players.get_mut(player_e).unwrap();
+        if cpu_player.role != role {
+            let slot = world.component::<Player>().get(player_e).unwrap().slot;
+            tracing::trace!(?slot, from = ?cpu_player.role, to = ?role, "cpu role change");
+            cpu_player.role = role;
+        }
+    }
+}
@@ -6,4 +6,6 @@ pub fn register_schemas() {
     FluentBundleAsset::register_schema();
     FluentResourceAsset::register_schema();
     Settings::register_schema();
+    InputBindings::register_schema();
+    StickSettingsMap::register_schema();
 }
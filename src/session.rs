@@ -63,7 +63,22 @@ impl SessionRunner for OfflineRunner {
 
         let last_run = self.last_run.unwrap_or(frame_start);
 
+        // The players may have been moved to other gamepads, see `ReconnectPrompt`.
+        if let Some(PlayMode::Offline(players_info, _)) =
+            world.get_resource::<PlayMode>().as_deref()
+        {
+            for (collector, team) in self.collectors.iter_mut().zip([Team::A, Team::B]) {
+                let [p1, p2] = players_info.sources(team);
+                collector.set_sources(p1, p2);
+                collector.set_team(team);
+            }
+        }
+
         if world.get_resource::<PauseGame>().is_some() {
+            // A gamepad disconnecting is what pauses the game, see `ReconnectPrompt`.
+            for collector in &mut self.collectors {
+                collector.apply_connection_events(&world.resource::<GamepadInputs>());
+            }
             self.last_run = Some(frame_start);
             return;
         }
@@ -103,13 +118,13 @@ impl SessionRunner for OfflineRunner {
                     *client = default();
                 }
             } else {
-                for (i, client) in world
+                for (client, collector) in world
                     .resource_mut::<PlayTeamInputs>()
                     .clients
                     .iter_mut()
-                    .enumerate()
+                    .zip(&self.collectors)
                 {
-                    client.update_from_dense(&self.collectors[i].get_control().get_dense_input());
+                    client.update_from_dense(&collector.get_control().get_dense_input());
                 }
             };
 
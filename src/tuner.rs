@@ -0,0 +1,133 @@
+//! Tunes the [`CpuParams`] by putting them against each other in headless
+//! self-play with a simple evolutionary search.
+//!
+//! Every generation, each candidate plays the current champion. The candidate
+//! that beats the champion by the most pins takes its place, and the next
+//! generation is made of mutations of the champion.
+use super::*;
+use crate::env::{Env, EnvConfig};
+use std::path::Path;
+
+#[derive(Clone, Debug)]
+pub struct TunerConfig {
+    pub seed: u64,
+    pub generations: usize,
+    /// The amount of candidates in each generation.
+    pub population: usize,
+    /// The amount of matches a candidate plays against the champion,
+    /// switching sides every match.
+    pub matches: u64,
+    /// How much each parameter changes in a mutation, relative to its value.
+    pub mutation: f32,
+    /// The pins either team needs to hit to finish a match.
+    pub target_score: u8,
+    pub max_frames: u64,
+}
+impl Default for TunerConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            generations: 20,
+            population: 8,
+            matches: 4,
+            mutation: 0.15,
+            target_score: 3,
+            max_frames: 60 * 90,
+        }
+    }
+}
+
+/// The path of the cpu asset the game loads the parameters from.
+pub const PARAMS_ASSET: &str = "assets/params.cpu.yaml";
+
+/// The parameters in the cpu asset.
+pub fn asset_params(env: &mut Env) -> CpuParams {
+    env.reset(default());
+    let asset_server = env.world().asset_server();
+    *asset_server.get(asset_server.root::<Data>().cpu)
+}
+
+/// Evolves the parameters, starting from `start`, and returns the best set.
+pub fn tune(env: &mut Env, start: CpuParams, config: &TunerConfig) -> CpuParams {
+    let mut rng = SplitMix64(config.seed);
+    let mut champion = start;
+
+    for generation in 0..config.generations {
+        let mut best: Option<(i32, CpuParams)> = None;
+
+        for _ in 0..config.population {
+            let candidate = mutate(&champion, config.mutation, &mut rng);
+            let margin = (0..config.matches)
+                .map(|i| {
+                    let seed = rng.next_u64();
+                    if i % 2 == 0 {
+                        play_match(env, config, seed, candidate, champion)
+                    } else {
+                        -play_match(env, config, seed, champion, candidate)
+                    }
+                })
+                .sum::<i32>();
+
+            if margin > 0 && best.is_none_or(|(best_margin, _)| margin > best_margin) {
+                best = Some((margin, candidate));
+            }
+        }
+
+        match best {
+            Some((margin, candidate)) => {
+                tracing::info!(generation, margin, params = ?candidate, "new cpu champion");
+                champion = candidate;
+            }
+            None => tracing::info!(generation, "cpu champion held"),
+        }
+    }
+
+    champion
+}
+
+/// Plays one match, returning how many more pins team A hit than team B.
+pub fn play_match(
+    env: &mut Env,
+    config: &TunerConfig,
+    seed: u64,
+    a: CpuParams,
+    b: CpuParams,
+) -> i32 {
+    env.reset(EnvConfig {
+        seed,
+        agents: Vec::new(),
+        kick_speed: 2.0,
+        max_frames: config.max_frames,
+        target_score: config.target_score,
+        cpu_params: TeamCpuParams {
+            a: Some(a),
+            b: Some(b),
+        },
+        ..Default::default()
+    });
+    while !env.step(&[]).2 {}
+
+    let score = *env.world().resource::<PinScore>();
+    score.a as i32 - score.b as i32
+}
+
+/// Changes every parameter by a random amount relative to its value.
+fn mutate(params: &CpuParams, mutation: f32, rng: &mut SplitMix64) -> CpuParams {
+    let mut params = *params;
+    for value in params.values_mut() {
+        *value = (*value * (1.0 + rng.next_gaussian() * mutation)).max(0.0);
+    }
+    params
+}
+
+/// Writes the parameters as a cpu asset, like the [`PARAMS_ASSET`] the game loads.
+pub fn write_params(params: &CpuParams, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let serde_json::Value::Object(values) = serde_json::to_value(params)? else {
+        unreachable!("cpu params serialize to a map");
+    };
+    let mut yaml = String::from("# The numbers the cpu players decide with, see `CpuParams`.\n");
+    for (name, value) in values {
+        yaml.push_str(&format!("{name}: {value}\n"));
+    }
+    std::fs::write(path, yaml)
+}
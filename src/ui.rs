@@ -1,37 +1,65 @@
 use super::*;
 
+pub mod controls;
 pub mod countdown;
 pub mod credits;
 pub mod fade;
 pub mod howtoplay;
+pub mod input_display;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod lan_select;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod lan_ui;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lobby_ui;
 pub mod match_done;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod network_diagnostics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod network_grace;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod network_match_done;
 pub mod network_quit;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod online_ui;
 pub mod pause;
+pub mod reconnect;
 pub mod score_display;
 pub mod settings;
 pub mod splash;
+pub mod sticks;
 pub mod team_select;
 pub mod utils;
 pub mod winner;
 
+pub use controls::*;
 pub use countdown::*;
 pub use credits::*;
 pub use fade::*;
 pub use howtoplay::*;
+pub use input_display::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub use lan_select::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub use lan_ui::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use lobby_ui::*;
 pub use match_done::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use network_diagnostics::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use network_grace::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use network_match_done::*;
 pub use network_quit::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use online_ui::*;
 pub use pause::*;
+pub use reconnect::*;
 pub use score_display::*;
 pub use settings::*;
 pub use splash::*;
+pub use sticks::*;
 pub use team_select::*;
 pub use utils::*;
 pub use winner::*;
@@ -0,0 +1,293 @@
+use super::*;
+
+/// The column of the [`ControlsUi`], which is the device being bound.
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BindingDevice {
+    #[default]
+    Keyboard,
+    Keyboard2,
+    Gamepad,
+}
+impl BindingDevice {
+    pub const ALL: [Self; 3] = [Self::Keyboard, Self::Keyboard2, Self::Gamepad];
+
+    /// The keyboard source, unless this is the gamepad.
+    pub fn keyboard_source(self) -> Option<SingleSource> {
+        match self {
+            Self::Keyboard => Some(SingleSource::KeyboardMouse),
+            Self::Keyboard2 => Some(SingleSource::Keyboard2),
+            Self::Gamepad => None,
+        }
+    }
+    pub fn cycle_right(&mut self) {
+        *self = match self {
+            Self::Keyboard => Self::Keyboard2,
+            Self::Keyboard2 => Self::Gamepad,
+            Self::Gamepad => Self::Keyboard,
+        }
+    }
+    pub fn cycle_left(&mut self) {
+        self.cycle_right();
+        self.cycle_right();
+    }
+    /// The names of the keys or buttons bound to the action.
+    pub fn binding_names(self, bindings: &InputBindings, action: BindAction) -> String {
+        let names = match self.keyboard_source() {
+            Some(source) => bindings
+                .keys(source)
+                .unwrap()
+                .action_keys(action)
+                .map(|key| format!("{key:?}"))
+                .collect::<Vec<_>>(),
+            None if !action.gamepad_bindable() => vec!["Stick".to_string()],
+            None => bindings
+                .action_buttons(action)
+                .map(|button| format!("{button:?}"))
+                .collect(),
+        };
+        names.join(" / ")
+    }
+}
+
+/// The remapping screen shown from the settings.
+///
+/// Every row is a [`BindAction`], followed by a mouse aim toggle and a reset
+/// row. Selecting a cell waits for the next key or button, which replaces the
+/// bindings of that action. While waiting for a key, any gamepad button
+/// cancels so that it can be used with just a gamepad, and the other way around.
+#[derive(HasSchema, Clone, Default)]
+pub struct ControlsUi {
+    pub visible: bool,
+    pub row: usize,
+    pub device: BindingDevice,
+    pub listening: bool,
+}
+impl ShowHide for ControlsUi {
+    fn show(&mut self) {
+        *self = Self {
+            visible: true,
+            ..default()
+        }
+    }
+    fn hide(&mut self) {
+        self.visible = false
+    }
+}
+
+pub struct ControlsOutput;
+
+impl ControlsUi {
+    /// The row after the actions that toggles [`InputBindings::mouse_aim`].
+    pub const MOUSE_AIM_ROW: usize = BindAction::ALL.len();
+    /// The last row, which resets the bindings.
+    pub const RESET_ROW: usize = Self::MOUSE_AIM_ROW + 1;
+
+    /// The action on the selected row, if it can be bound on the selected device.
+    pub fn selected_action(&self) -> Option<BindAction> {
+        let action = *BindAction::ALL.get(self.row)?;
+        (self.device != BindingDevice::Gamepad || action.gamepad_bindable()).then_some(action)
+    }
+
+    pub fn process_input(&mut self, world: &World) -> Option<ControlsOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+
+        if self.listening {
+            self.listen(world);
+            return output;
+        }
+
+        let local_inputs = world.resource::<LocalInputs>();
+
+        for (_source, input) in local_inputs.iter() {
+            if input.menu_back.just_pressed() {
+                output = Some(ControlsOutput);
+            }
+            if input.menu_up.just_pressed() {
+                self.row = self.row.checked_sub(1).unwrap_or(Self::RESET_ROW);
+            }
+            if input.menu_down.just_pressed() {
+                self.row = (self.row + 1) % (Self::RESET_ROW + 1);
+            }
+            if input.menu_left.just_pressed() {
+                self.device.cycle_left();
+            }
+            if input.menu_right.just_pressed() {
+                self.device.cycle_right();
+            }
+            if input.menu_select.just_pressed() {
+                self.select(world);
+            }
+        }
+        output
+    }
+
+    fn select(&mut self, world: &World) {
+        if self.row == Self::MOUSE_AIM_ROW {
+            let mut bindings = world.resource::<InputSettings>().bindings.clone();
+            bindings.mouse_aim = !bindings.mouse_aim;
+            InputSettings::save_bindings(world, bindings);
+            tracing::info!("storage saved");
+        } else if self.row == Self::RESET_ROW {
+            InputSettings::save_bindings(world, InputBindings::default());
+            tracing::info!("input bindings reset");
+        } else if self.selected_action().is_some() {
+            self.listening = true;
+        }
+    }
+
+    /// Binds the first key or button pressed to the selected action.
+    fn listen(&mut self, world: &World) {
+        let Some(action) = self.selected_action() else {
+            self.listening = false;
+            return;
+        };
+        let keyboard = world.resource::<KeyboardInputs>();
+        let gamepad = world.resource::<GamepadInputs>();
+
+        let pressed_key = keyboard.key_events.iter().find_map(|event| match event {
+            KeyboardEvent {
+                key_code: Maybe::Set(key),
+                button_state: ButtonState::Pressed,
+                ..
+            } => Some(*key),
+            _ => None,
+        });
+        let pressed_button = gamepad.gamepad_events.iter().find_map(|event| match event {
+            GamepadEvent::Button(GamepadButtonEvent { button, value, .. }) if *value > 0.5 => {
+                Some(*button)
+            }
+            _ => None,
+        });
+
+        let mut bindings = world.resource::<InputSettings>().bindings.clone();
+
+        match self.device.keyboard_source() {
+            Some(source) => {
+                if let Some(key) = pressed_key {
+                    bindings.keys_mut(source).unwrap().bind(action, key);
+                } else if pressed_button.is_none() {
+                    return;
+                }
+            }
+            None => {
+                if let Some(button) = pressed_button {
+                    bindings.bind_button(action, button);
+                } else if pressed_key.is_none() {
+                    return;
+                }
+            }
+        }
+        self.listening = false;
+
+        InputSettings::save_bindings(world, bindings);
+        tracing::info!("storage saved");
+    }
+
+    pub fn process_ui(&mut self, world: &World) {
+        if !self.visible {
+            return;
+        }
+
+        let asset_server = world.resource::<AssetServer>();
+        let root = asset_server.root::<Data>();
+        let textures = world.resource::<EguiTextures>();
+        let ctx = world.resource::<EguiCtx>();
+        let pointer_navigation = world.resource::<LocalInputs>().pointer_navigation();
+        let bindings = world.resource::<InputSettings>().bindings.clone();
+
+        use egui::*;
+
+        Area::new("controls_bg")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show(&ctx, |ui| {
+                ui.image(load::SizedTexture::new(
+                    textures.get(root.menu.splash.bg),
+                    root.screen_size.to_array(),
+                ));
+            });
+
+        let mut clicked = false;
+
+        Area::new("controls")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .order(Order::Foreground)
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        Grid::new("controls_grid")
+                            .num_columns(4)
+                            .spacing([12.0, 2.0])
+                            .show(ui, |ui| {
+                                for header in ["", "Keyboard", "Keyboard 2", "Gamepad"] {
+                                    super::primary_text(header, false, &asset_server, ui);
+                                }
+                                ui.end_row();
+
+                                for (row, action) in BindAction::ALL.into_iter().enumerate() {
+                                    super::primary_text(action.name(), false, &asset_server, ui);
+
+                                    for device in BindingDevice::ALL {
+                                        let selected = self.row == row && self.device == device;
+                                        let text = if selected && self.listening {
+                                            "...".to_string()
+                                        } else {
+                                            device.binding_names(&bindings, action)
+                                        };
+                                        let response =
+                                            super::primary_text(&text, selected, &asset_server, ui);
+                                        if response.hovered() && pointer_navigation {
+                                            self.row = row;
+                                            self.device = device;
+                                        }
+                                        if ctx.clicked_rect(response.rect) {
+                                            clicked = true;
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+
+                                let response = super::primary_text(
+                                    if bindings.mouse_aim {
+                                        "Mouse Aim: On"
+                                    } else {
+                                        "Mouse Aim: Off"
+                                    },
+                                    self.row == Self::MOUSE_AIM_ROW,
+                                    &asset_server,
+                                    ui,
+                                );
+                                if response.hovered() && pointer_navigation {
+                                    self.row = Self::MOUSE_AIM_ROW;
+                                }
+                                if ctx.clicked_rect(response.rect) {
+                                    clicked = true;
+                                }
+                                ui.end_row();
+
+                                let response = super::primary_text(
+                                    "Reset Defaults",
+                                    self.row == Self::RESET_ROW,
+                                    &asset_server,
+                                    ui,
+                                );
+                                if response.hovered() && pointer_navigation {
+                                    self.row = Self::RESET_ROW;
+                                }
+                                if ctx.clicked_rect(response.rect) {
+                                    clicked = true;
+                                }
+                                ui.end_row();
+                            });
+                    });
+            });
+
+        if clicked && !self.listening {
+            self.select(world);
+        }
+    }
+}
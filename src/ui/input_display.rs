@@ -0,0 +1,116 @@
+use super::*;
+
+/// Shows the [`PlayInput`] of every player along with where it comes from,
+/// toggled with `F6`.
+///
+/// The inputs are the ones the game used, so for cpu players this is what
+/// [`CpuPlayer::input`] decided on the frame before.
+///
+/// This is a resource of the [`UI`] session, since the play session is
+/// rolled back, which would restore it and toggle it again for every frame
+/// that is simulated again.
+#[derive(HasSchema, Clone, Default)]
+pub struct InputDisplay {
+    pub visible: bool,
+}
+impl SessionPlugin for InputDisplay {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+        session.add_system_to_stage(Update, toggle_input_display);
+    }
+}
+impl InputDisplay {
+    /// How many frames a press flashes for.
+    pub const FLASH_FRAMES: u32 = 8;
+}
+
+/// Toggles the overlay with `F6`.
+pub fn toggle_input_display(inputs: Res<KeyboardInputs>, mut display: ResMut<InputDisplay>) {
+    for input in inputs.key_events.iter() {
+        if input.button_state == ButtonState::Pressed && input.key_code == Set(KeyCode::F6) {
+            display.visible = !display.visible;
+        }
+    }
+}
+
+/// The name of the input source of a player.
+pub fn source_name(info: PlayerInfo) -> String {
+    match info {
+        PlayerInfo::CPU { brain } => format!("CPU {brain}"),
+        PlayerInfo::Network => "Network".to_string(),
+        PlayerInfo::Local { source, .. } => match source {
+            SingleSource::KeyboardMouse => "Keyboard".to_string(),
+            SingleSource::Keyboard2 => "Keyboard 2".to_string(),
+            SingleSource::CPU(..) => "CPU".to_string(),
+            SingleSource::Gamepad(id) => format!("Gamepad {id}"),
+        },
+    }
+}
+
+/// Shows the inputs of the play session if the overlay of the `ui` is visible.
+pub fn show(ui: &World, world: &World) {
+    if !ui.resource::<InputDisplay>().visible {
+        return;
+    }
+    let asset_server = world.resource::<AssetServer>();
+    let root = asset_server.root::<Data>();
+    let ctx = world.resource::<EguiCtx>();
+    let inputs = world.resource::<PlayTeamInputs>();
+    let mode = world.resource::<PlayMode>();
+
+    let font = asset_server
+        .get(root.font.primary_inner)
+        .family_name
+        .clone();
+
+    use egui::*;
+
+    let button_color = |input: PressInput| {
+        if input.pressed() && input.held() < InputDisplay::FLASH_FRAMES {
+            Color32::WHITE
+        } else if input.pressed() {
+            Color32::YELLOW
+        } else {
+            Color32::from_gray(60)
+        }
+    };
+
+    Area::new("input_display")
+        .order(Order::Foreground)
+        .anchor(Align2::LEFT_TOP, [4., 4.])
+        .show(&ctx, |ui| {
+            ui.spacing_mut().item_spacing = vec2(3.0, 2.0);
+
+            for slot in PlayerSlot::variants() {
+                let input = inputs.get_character_control(slot);
+
+                ui.horizontal(|ui| {
+                    let (rect, _) = ui.allocate_exact_size(vec2(10.0, 10.0), Sense::hover());
+                    let painter = ui.painter();
+                    let stick = rect.center() + vec2(input.x, -input.y) * rect.width() / 2.0;
+                    painter.circle_stroke(
+                        rect.center(),
+                        rect.width() / 2.0,
+                        Stroke::new(1.0, Color32::GRAY),
+                    );
+                    painter.line_segment([rect.center(), stick], Stroke::new(1.0, Color32::WHITE));
+                    painter.circle_filled(stick, 1.5, Color32::YELLOW);
+
+                    for (name, press) in
+                        [("S", input.shoot), ("P", input.pass), ("T", input.tackle)]
+                    {
+                        ui.label(RichText::new(name).color(button_color(press)).font(FontId {
+                            size: 7.0,
+                            family: FontFamily::Name(font.clone()),
+                        }));
+                    }
+
+                    let text = format!("{slot:?} {}", source_name(mode.player_info(slot)));
+                    ui.label(RichText::new(text).color(Color32::WHITE).font(FontId {
+                        size: 7.0,
+                        family: FontFamily::Name(font.clone()),
+                    }));
+                });
+            }
+        });
+}
@@ -9,6 +9,9 @@ pub const MATCHMAKER_SERVICE_NAME: &str = "StrikerBall";
 pub enum ServiceType {
     OnePlayer(u32),
     TwoPlayer(SingleSource, SingleSource),
+    /// One player controlling a single character, so that up to four
+    /// machines can play in one match.
+    Single(SingleSource),
 }
 impl Default for ServiceType {
     fn default() -> Self {
@@ -16,6 +19,21 @@ impl Default for ServiceType {
     }
 }
 impl ServiceType {
+    /// How many characters are controlled from this machine.
+    pub fn local_players(&self) -> u8 {
+        match self {
+            ServiceType::OnePlayer(..) | ServiceType::TwoPlayer(..) => 2,
+            ServiceType::Single(..) => 1,
+        }
+    }
+    /// The sources of the primary and secondary players.
+    pub fn sources(&self) -> [SingleSource; 2] {
+        match self {
+            ServiceType::OnePlayer(gamepad_id) => [SingleSource::Gamepad(*gamepad_id); 2],
+            ServiceType::TwoPlayer(p1, p2) => [*p1, *p2],
+            ServiceType::Single(source) => [*source; 2],
+        }
+    }
     pub fn player_info_primary(&self) -> PlayerInfo {
         match self {
             ServiceType::OnePlayer(gamepad_id) => PlayerInfo::Local {
@@ -30,6 +48,11 @@ impl ServiceType {
                     twin_stick: false,
                 }
             }
+            ServiceType::Single(source) => PlayerInfo::Local {
+                number: 0,
+                source: *source,
+                twin_stick: false,
+            },
         }
     }
     pub fn player_info_secondary(&self) -> PlayerInfo {
@@ -46,6 +69,7 @@ impl ServiceType {
                     twin_stick: false,
                 }
             }
+            ServiceType::Single(..) => self.player_info_primary(),
         }
     }
 }
@@ -54,6 +78,8 @@ impl ServiceType {
 pub struct LanSelect {
     pub visible: bool,
     pub selection: LanSelection,
+    /// Whether the match is found over the internet instead of on the local network.
+    pub online: bool,
     // TODO: all the back buttons should probably be handled like this.
     pub back_button_pressed: bool,
 }
@@ -66,7 +92,7 @@ impl ShowHide for LanSelect {
     }
 }
 
-#[derive(HasSchema, Clone, Default, PartialEq, Eq)]
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq)]
 pub enum LanSelection {
     #[default]
     /// Focus is on the one player button
@@ -80,12 +106,26 @@ pub enum LanSelection {
     /// Contains the source of the player that
     /// selected the two player button.
     TwoPlayerBind { player1: SingleSource },
-    // ThreePlayer,
+    /// Focus is on the single player button
+    Single,
+}
+
+impl LanSelection {
+    /// Moves the focus to the next button.
+    pub fn cycle(&mut self) {
+        *self = match self {
+            LanSelection::OnePlayer => LanSelection::TwoPlayer,
+            LanSelection::TwoPlayer => LanSelection::Single,
+            LanSelection::Single => LanSelection::OnePlayer,
+            selection => *selection,
+        }
+    }
 }
 
 pub enum LanSelectOutput {
     Exit,
     ServiceType(ServiceType),
+    Online(ServiceType),
 }
 
 impl SessionPlugin for LanSelect {
@@ -98,6 +138,14 @@ fn foreground() -> egui::LayerId {
     LayerId::new(Order::Foreground, Id::new("lan_select_foreground"))
 }
 impl LanSelect {
+    /// The output for the service that was picked, depending on the network.
+    fn service_output(&self, service: ServiceType) -> Option<LanSelectOutput> {
+        if self.online {
+            LanSelectOutput::Online(service).into()
+        } else {
+            LanSelectOutput::ServiceType(service).into()
+        }
+    }
     pub fn process_input(&mut self, world: &World) -> Option<LanSelectOutput> {
         let mut output = None;
 
@@ -108,33 +156,33 @@ impl LanSelect {
                 match self.selection {
                     LanSelection::OnePlayer => match *source {
                         SingleSource::Gamepad(gamepad_id) => {
-                            output =
-                                LanSelectOutput::ServiceType(ServiceType::OnePlayer(gamepad_id))
-                                    .into();
+                            output = self.service_output(ServiceType::OnePlayer(gamepad_id));
+                        }
+                        SingleSource::KeyboardMouse | SingleSource::Keyboard2 => {
+                            self.selection = LanSelection::OnePlayerBind
                         }
-                        SingleSource::KeyboardMouse => self.selection = LanSelection::OnePlayerBind,
                         SingleSource::CPU(..) => unreachable!(),
                     },
                     LanSelection::OnePlayerBind => match *source {
                         SingleSource::Gamepad(gamepad_id) => {
-                            output =
-                                LanSelectOutput::ServiceType(ServiceType::OnePlayer(gamepad_id))
-                                    .into();
+                            output = self.service_output(ServiceType::OnePlayer(gamepad_id));
+                        }
+                        SingleSource::KeyboardMouse | SingleSource::Keyboard2 => {
+                            self.selection = LanSelection::OnePlayer
                         }
-                        SingleSource::KeyboardMouse => self.selection = LanSelection::OnePlayer,
                         SingleSource::CPU(..) => unreachable!(),
                     },
                     LanSelection::TwoPlayer => {
                         self.selection = LanSelection::TwoPlayerBind { player1: *source };
                     }
+                    LanSelection::Single => {
+                        output = self.service_output(ServiceType::Single(*source));
+                    }
                     LanSelection::TwoPlayerBind { player1 } => {
                         if *source == player1 {
                             self.selection = LanSelection::TwoPlayer;
                         } else {
-                            output = LanSelectOutput::ServiceType(ServiceType::TwoPlayer(
-                                player1, *source,
-                            ))
-                            .into();
+                            output = self.service_output(ServiceType::TwoPlayer(player1, *source));
                         }
                     }
                 }
@@ -150,12 +198,20 @@ impl LanSelect {
                     output = LanSelectOutput::Exit.into();
                 }
             }
-            if input.menu_up.just_pressed() || input.menu_down.just_pressed() {
-                match self.selection {
-                    LanSelection::OnePlayer => self.selection = LanSelection::TwoPlayer,
-                    LanSelection::TwoPlayer => self.selection = LanSelection::OnePlayer,
-                    LanSelection::OnePlayerBind | LanSelection::TwoPlayerBind { .. } => {}
-                }
+            if input.menu_down.just_pressed() {
+                self.selection.cycle();
+            }
+            if input.menu_up.just_pressed() {
+                self.selection.cycle();
+                self.selection.cycle();
+            }
+            if (input.menu_left.just_pressed() || input.menu_right.just_pressed())
+                && matches!(
+                    self.selection,
+                    LanSelection::OnePlayer | LanSelection::TwoPlayer | LanSelection::Single
+                )
+            {
+                self.online = !self.online;
             }
         }
         output
@@ -199,12 +255,25 @@ impl LanSelect {
             .clone();
 
         match self.selection {
-            LanSelection::OnePlayer | LanSelection::TwoPlayer => {
+            LanSelection::OnePlayer | LanSelection::TwoPlayer | LanSelection::Single => {
                 Area::new("lan_select_buttons")
                     .anchor(Align2::CENTER_CENTER, [0., 0.])
                     .order(Order::Foreground)
                     .show(&world.resource::<EguiCtx>(), |ui| {
                         ui.vertical(|ui| {
+                            let irsp = BorderedFrame::new(&root.menu.bframe)
+                                .padding(Margin::same(6.0))
+                                .show(ui, |ui| {
+                                    super::primary_text(
+                                        if self.online { "< ONLINE >" } else { "< LAN >" },
+                                        false,
+                                        &asset_server,
+                                        ui,
+                                    );
+                                });
+                            if ctx.clicked_rect(irsp.response.rect) {
+                                self.online = !self.online;
+                            }
                             let irsp = BorderedFrame::new(&root.menu.bframe)
                                 .padding(Margin::same(6.0))
                                 .show(ui, |ui| {
@@ -240,6 +309,24 @@ impl LanSelect {
                                     player1: SingleSource::KeyboardMouse,
                                 };
                             }
+                            let irsp = BorderedFrame::new(&root.menu.bframe)
+                                .padding(Margin::same(6.0))
+                                .show(ui, |ui| {
+                                    super::primary_text(
+                                        "SINGLE - One Character",
+                                        self.selection == LanSelection::Single,
+                                        &asset_server,
+                                        ui,
+                                    );
+                                });
+                            if irsp.response.hovered() && pointer_navigation {
+                                self.selection = LanSelection::Single;
+                            }
+                            if ctx.clicked_rect(irsp.response.rect) {
+                                output = self.service_output(ServiceType::Single(
+                                    SingleSource::KeyboardMouse,
+                                ));
+                            }
                         });
                     });
             }
@@ -278,10 +365,14 @@ impl LanSelect {
                         BorderedFrame::new(&root.menu.bframe)
                             .padding(Margin::same(50.0))
                             .show(ui, |ui| {
-                                let text = if let SingleSource::Gamepad(..) = player1 {
-                                    locale.get("player2-join-controller-keyboard")
-                                } else {
-                                    locale.get("player2-join-controller")
+                                let text = match player1 {
+                                    SingleSource::Gamepad(..) | SingleSource::Keyboard2 => {
+                                        locale.get("player2-join-controller-keyboard")
+                                    }
+                                    SingleSource::KeyboardMouse => {
+                                        locale.get("player2-join-controller-split-keyboard")
+                                    }
+                                    SingleSource::CPU(..) => unreachable!(),
                                 };
                                 let response = ui.label(
                                     RichText::new(text.clone()).color(Color32::WHITE).font(
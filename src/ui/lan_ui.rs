@@ -6,27 +6,56 @@ pub enum LanUIState {
     Host,
     // HostNameHover, // TODO: Add with on-screen keyboard
     HostEditing,
+    /// Focus is on the input delay of network matches.
+    Delay,
+    /// Focus is on the prediction window of network matches.
+    Prediction,
     Disconnected,
+    /// A peer dropped out of a match between more than two machines, which
+    /// doesn't wait for it, see [`NetworkGrace`].
+    PeerDropped,
+    /// The direct connect address couldn't be joined.
+    BadAddress,
+    /// Hosting or joining failed.
+    Error(LanError),
+    /// Focus is on the direct connect address.
+    Direct,
+    /// Changing the digit of the direct connect address at the index.
+    DirectEditing(usize),
     Server(usize),
 }
 impl LanUIState {
+    pub fn is_popup(&self) -> bool {
+        matches!(
+            self,
+            Self::Disconnected | Self::PeerDropped | Self::BadAddress | Self::Error(..)
+        )
+    }
     pub fn cycle_up(&mut self) {
         match self {
-            Self::HostEditing | Self::Disconnected | Self::Host => {}
+            Self::HostEditing | Self::DirectEditing(..) | Self::Host => {}
+            Self::Delay => *self = Self::Host,
+            Self::Prediction => *self = Self::Delay,
+            Self::Direct => *self = Self::Prediction,
             Self::Server(i) => {
                 if let Some(reduced) = i.checked_sub(1) {
                     *i = reduced;
                 } else {
-                    *self = Self::Host;
+                    *self = Self::Direct;
                 }
             }
+            Self::Disconnected | Self::PeerDropped | Self::BadAddress | Self::Error(..) => {}
         }
     }
     pub fn cycle_down(&mut self) {
         match self {
-            Self::HostEditing | Self::Disconnected => {}
-            Self::Host => *self = Self::Server(0),
+            Self::HostEditing | Self::DirectEditing(..) => {}
+            Self::Host => *self = Self::Delay,
+            Self::Delay => *self = Self::Prediction,
+            Self::Prediction => *self = Self::Direct,
+            Self::Direct => *self = Self::Server(0),
             Self::Server(i) => *i = i.saturating_add(1), // This is capped in the `show` function
+            Self::Disconnected | Self::PeerDropped | Self::BadAddress | Self::Error(..) => {}
         }
     }
     // TODO: Add with on-screen keyboard
@@ -46,7 +75,17 @@ impl LanUIState {
 
 pub enum LanUIOutput {
     HostCancel,
+    /// Changes how many machines the hosted match is for.
+    Machines(i32),
+    /// Cycles the input delay setting.
+    Delay(i8),
+    /// Changes the prediction window setting.
+    Prediction(i8),
     Server(usize),
+    /// Watches the match in progress, see [`Matchmaker::watchable_matches`].
+    Watch(usize),
+    /// Joins the server at the address.
+    Direct(DirectAddress),
     Exit,
 }
 
@@ -55,6 +94,8 @@ pub struct LanUI {
     pub visible: bool,
     pub service: ServiceType,
     pub state: LanUIState,
+    /// The address of the server to connect to directly.
+    pub direct: DirectAddress,
 }
 impl ShowHide for LanUI {
     fn show(&mut self) {
@@ -71,12 +112,42 @@ impl SessionPlugin for LanUI {
     }
 }
 impl LanUI {
+    /// The servers are listed first, followed by the matches to watch.
+    fn server_output(world: &World, index: usize) -> LanUIOutput {
+        let servers = world.resource::<Matchmaker>().lan_servers().len();
+        match index.checked_sub(servers) {
+            Some(index) => LanUIOutput::Watch(index),
+            None => LanUIOutput::Server(index),
+        }
+    }
     pub fn process_input(&mut self, world: &World) -> Option<LanUIOutput> {
         let mut output = None;
 
         let local_inputs = world.resource::<LocalInputs>();
 
         for (_gamepad, input) in local_inputs.iter() {
+            if let LanUIState::DirectEditing(index) = &mut self.state {
+                if input.menu_up.just_pressed() {
+                    self.direct.cycle(*index, 1);
+                }
+                if input.menu_down.just_pressed() {
+                    self.direct.cycle(*index, -1);
+                }
+                if input.menu_left.just_pressed() {
+                    *index = index.checked_sub(1).unwrap_or(DirectAddress::LEN - 1);
+                }
+                if input.menu_right.just_pressed() {
+                    *index = (*index + 1) % DirectAddress::LEN;
+                }
+                if input.menu_select.just_pressed() || input.start.just_pressed() {
+                    self.state = LanUIState::Direct;
+                    output = LanUIOutput::Direct(self.direct).into();
+                }
+                if input.menu_back.just_pressed() {
+                    self.state = LanUIState::Direct;
+                }
+                continue;
+            }
             if input.start.just_pressed() {
                 if let LanUIState::HostEditing = self.state {
                     // TODO: Replace with on-screen keyboard
@@ -91,8 +162,13 @@ impl LanUI {
                     // LanUIState::HostNameHover => { self.state = LanUIState::HostEditing }
                     LanUIState::HostEditing => {}
                     LanUIState::Host => output = LanUIOutput::HostCancel.into(),
-                    LanUIState::Server(index) => output = LanUIOutput::Server(index).into(),
-                    LanUIState::Disconnected => self.state = LanUIState::Host,
+                    LanUIState::Server(index) => output = Self::server_output(world, index).into(),
+                    LanUIState::Direct => self.state = LanUIState::DirectEditing(0),
+                    LanUIState::DirectEditing(..) | LanUIState::Delay | LanUIState::Prediction => {}
+                    LanUIState::Disconnected
+                    | LanUIState::PeerDropped
+                    | LanUIState::BadAddress
+                    | LanUIState::Error(..) => self.state = LanUIState::Host,
                 }
             }
             if input.menu_back.just_pressed() {
@@ -104,6 +180,30 @@ impl LanUI {
             if input.menu_down.just_pressed() {
                 self.state.cycle_down();
             }
+            if self.state == LanUIState::Host {
+                if input.menu_left.just_pressed() {
+                    output = LanUIOutput::Machines(-1).into();
+                }
+                if input.menu_right.just_pressed() {
+                    output = LanUIOutput::Machines(1).into();
+                }
+            }
+            if self.state == LanUIState::Delay {
+                if input.menu_left.just_pressed() {
+                    output = LanUIOutput::Delay(-1).into();
+                }
+                if input.menu_right.just_pressed() {
+                    output = LanUIOutput::Delay(1).into();
+                }
+            }
+            if self.state == LanUIState::Prediction {
+                if input.menu_left.just_pressed() {
+                    output = LanUIOutput::Prediction(-1).into();
+                }
+                if input.menu_right.just_pressed() {
+                    output = LanUIOutput::Prediction(1).into();
+                }
+            }
             // TODO: Add with on-screen keyboard
             // if input.menu_left.just_pressed() {
             //     self.state.cycle_left();
@@ -119,6 +219,8 @@ impl LanUI {
             visible,
             state,
             service,
+            direct,
+            ..
         } = self;
 
         let mut output = None;
@@ -134,6 +236,11 @@ impl LanUI {
         let root = asset_server.root::<Data>();
         let pointer_navigation = local_inputs.pointer_navigation();
         let mut matchmaker = world.resource_mut::<Matchmaker>();
+        let settings = world
+            .resource::<Storage>()
+            .get::<Settings>()
+            .cloned()
+            .unwrap_or_default();
 
         let inner_font = asset_server
             .get(root.font.primary_inner)
@@ -154,10 +261,11 @@ impl LanUI {
         use egui::*;
 
         if let LanUIState::Server(i) = state {
-            if let Some(index) = matchmaker.lan_servers().len().checked_sub(1) {
+            let entries = matchmaker.lan_servers().len() + matchmaker.watchable_matches().len();
+            if let Some(index) = entries.checked_sub(1) {
                 *i = (*i).min(index);
             } else {
-                *state = LanUIState::Host;
+                *state = LanUIState::Direct;
             }
         }
 
@@ -180,7 +288,7 @@ impl LanUI {
 
         painter.set_clip_rect(area.response.rect);
 
-        if let LanUIState::Disconnected = state {
+        if state.is_popup() {
             Area::new("disconnected-popup")
                 .anchor(Align2::CENTER_CENTER, [0., 0.])
                 .order(Order::Foreground)
@@ -188,12 +296,24 @@ impl LanUI {
                     let irsp = BorderedFrame::new(&root.menu.bframe)
                         .padding(Margin::same(50.0))
                         .show(ui, |ui| {
-                            let text = "A Player Disconnected...";
+                            let text = match state {
+                                LanUIState::BadAddress => {
+                                    "Couldn't Connect To The Address...".to_string()
+                                }
+                                LanUIState::Error(error) => error.to_string(),
+                                LanUIState::PeerDropped => {
+                                    "A Player Dropped Out...\nOnly Two Machines Can Wait"
+                                        .to_string()
+                                }
+                                _ => "A Player Disconnected...".to_string(),
+                            };
                             let response =
-                                ui.label(RichText::new(text).color(Color32::WHITE).font(FontId {
-                                    size: 7.0,
-                                    family: FontFamily::Name(inner_font.clone()),
-                                }));
+                                ui.label(RichText::new(text.clone()).color(Color32::WHITE).font(
+                                    FontId {
+                                        size: 7.0,
+                                        family: FontFamily::Name(inner_font.clone()),
+                                    },
+                                ));
                             TextPainter::new(text)
                                 .size(7.0)
                                 .pos(response.rect.min)
@@ -217,6 +337,7 @@ impl LanUI {
                         let text = match &service {
                             ServiceType::OnePlayer(..) => "SOLO - Twin-Stick",
                             ServiceType::TwoPlayer(..) => "DOUBLES - CO-OP",
+                            ServiceType::Single(..) => "SINGLE - One Character",
                         };
                         let response = ui.label(
                             RichText::new(text)
@@ -391,13 +512,77 @@ impl LanUI {
                                             );
                                         });
                                     });
+
+                                let text = format!("Machines < {} >", matchmaker.player_count());
+                                let response = BorderedFrame::new(&root.menu.bframe)
+                                    .padding(Margin::same(4.0))
+                                    .margin(Margin::ZERO)
+                                    .show(ui, |ui| {
+                                        let rect = outer
+                                            .clone()
+                                            .text(text.clone())
+                                            .pos(ui.cursor().min)
+                                            .paint(ui.painter());
+                                        inner
+                                            .clone()
+                                            .text(text)
+                                            .pos(ui.cursor().min)
+                                            .paint(ui.painter());
+                                        ui.allocate_rect(rect, Sense::click());
+                                    });
+                                if ctx.clicked_rect(response.response.rect) {
+                                    output = Some(LanUIOutput::Machines(1));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                let rows = [
+                                    (
+                                        LanUIState::Delay,
+                                        format!("Delay < {} >", settings.input_delay),
+                                        LanUIOutput::Delay(1),
+                                    ),
+                                    (
+                                        LanUIState::Prediction,
+                                        format!("Prediction < {} >", *settings.max_prediction),
+                                        LanUIOutput::Prediction(1),
+                                    ),
+                                ];
+                                for (row, text, click) in rows {
+                                    outer
+                                        .clone()
+                                        .text(text.clone())
+                                        .pos(ui.cursor().min)
+                                        .paint(ui.painter());
+                                    let rect = inner
+                                        .clone()
+                                        .text(text)
+                                        .color(if *state == row {
+                                            Color32::YELLOW
+                                        } else {
+                                            Color32::WHITE
+                                        })
+                                        .pos(ui.cursor().min)
+                                        .paint(ui.painter());
+                                    let response = ui.allocate_rect(rect, Sense::click());
+                                    if ctx.clicked_rect(response.rect) {
+                                        *state = row;
+                                        output = Some(click);
+                                    }
+                                    if response.hovered() && pointer_navigation {
+                                        *state = row;
+                                    }
+                                }
                             });
                             if matchmaker.is_hosting() {
-                                let text = "Waiting for a player...";
+                                let text = format!(
+                                    "Waiting for players ({}/{})...",
+                                    matchmaker.joined_players().unwrap_or_default(),
+                                    matchmaker.player_count()
+                                );
 
                                 let rect = outer
                                     .clone()
-                                    .text(text)
+                                    .text(text.clone())
                                     .pos(ui.cursor().min)
                                     .color(Color32::BLACK)
                                     .paint(ui.painter());
@@ -408,6 +593,23 @@ impl LanUI {
                                     .color(Color32::WHITE)
                                     .paint(ui.painter());
                                 ui.advance_cursor_after_rect(rect);
+
+                                if let Some(address) = matchmaker.host_address() {
+                                    let text = format!("Address: {address}");
+                                    let rect = outer
+                                        .clone()
+                                        .text(text.clone())
+                                        .pos(ui.cursor().min)
+                                        .color(Color32::BLACK)
+                                        .paint(ui.painter());
+                                    inner
+                                        .clone()
+                                        .text(text)
+                                        .pos(ui.cursor().min)
+                                        .color(Color32::WHITE)
+                                        .paint(ui.painter());
+                                    ui.advance_cursor_after_rect(rect);
+                                }
                                 return;
                             }
                             if matchmaker.is_joining() {
@@ -429,8 +631,49 @@ impl LanUI {
                                 return;
                             }
 
+                            let address = match *state {
+                                LanUIState::DirectEditing(index) => {
+                                    let mut text = direct.to_string();
+                                    let position = DirectAddress::text_position(index);
+                                    text.insert(position + 1, ']');
+                                    text.insert(position, '[');
+                                    text
+                                }
+                                _ => direct.to_string(),
+                            };
+                            let text = format!("Direct Connect: {address}");
+                            let selected =
+                                matches!(state, LanUIState::Direct | LanUIState::DirectEditing(..));
+                            outer
+                                .clone()
+                                .text(text.clone())
+                                .pos(ui.cursor().min)
+                                .paint(ui.painter());
+                            let rect = inner
+                                .clone()
+                                .text(text)
+                                .color(if selected {
+                                    Color32::YELLOW
+                                } else {
+                                    Color32::WHITE
+                                })
+                                .pos(ui.cursor().min)
+                                .paint(ui.painter());
+                            let response = ui.allocate_rect(rect, Sense::click());
+                            if ctx.clicked_rect(response.rect) {
+                                if let LanUIState::DirectEditing(..) = state {
+                                    *state = LanUIState::Direct;
+                                    output = Some(LanUIOutput::Direct(*direct));
+                                } else {
+                                    *state = LanUIState::DirectEditing(0);
+                                }
+                            }
+                            if response.hovered() && pointer_navigation && !selected {
+                                *state = LanUIState::Direct;
+                            }
+
                             let servers = matchmaker.lan_servers();
-                            if servers.is_empty() {
+                            if servers.is_empty() && matchmaker.watchable_matches().is_empty() {
                                 let text = "No servers found";
                                 let rect = outer
                                     .clone()
@@ -453,8 +696,12 @@ impl LanUI {
                                     } else {
                                         Color32::WHITE
                                     };
+                                    // The delay the match would be played with, the
+                                    // lobby measures it again once connected.
                                     let ping = if let Some(ping) = server.ping {
-                                        format!("PING {ping}")
+                                        let rtt = std::time::Duration::from_millis(ping as u64);
+                                        let delay = settings.input_delay.frames(Some(rtt));
+                                        format!("PING {ping} DELAY {delay}")
                                     } else {
                                         "PING ?".to_string()
                                     };
@@ -493,6 +740,41 @@ impl LanUI {
                                     *state = LanUIState::Server(i);
                                 }
                             }
+
+                            // The matches in progress follow the servers.
+                            let servers = matchmaker.lan_servers().len();
+                            for (i, watchable) in matchmaker.watchable_matches().iter().enumerate()
+                            {
+                                let entry = servers + i;
+                                let irsp = ui.horizontal(|ui| {
+                                    let color = if LanUIState::Server(entry) == *state {
+                                        Color32::YELLOW
+                                    } else {
+                                        Color32::WHITE
+                                    };
+                                    for text in [watchable.host_name.as_str(), "WATCH"] {
+                                        outer
+                                            .clone()
+                                            .text(text)
+                                            .pos(ui.cursor().min)
+                                            .paint(ui.painter());
+                                        let rect = inner
+                                            .clone()
+                                            .color(color)
+                                            .text(text)
+                                            .pos(ui.cursor().min)
+                                            .paint(ui.painter());
+                                        ui.advance_cursor_after_rect(rect);
+                                    }
+                                });
+                                let rect = irsp.response.rect;
+                                if ctx.clicked_rect(rect) {
+                                    output = Some(LanUIOutput::Watch(i));
+                                }
+                                if ctx.hovered_rect(rect) && pointer_navigation {
+                                    *state = LanUIState::Server(entry);
+                                }
+                            }
                         });
                     });
             });
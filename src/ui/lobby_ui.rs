@@ -0,0 +1,246 @@
+use super::*;
+
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LobbyUIState {
+    #[default]
+    Side,
+    /// Only the host can focus the rules.
+    ScoreTarget,
+    Countdown,
+    FormationA,
+    FormationB,
+    Ready,
+    Leave,
+}
+impl LobbyUIState {
+    pub fn cycle(&mut self, direction: i8, host: bool) {
+        let mut states = vec![Self::Side, Self::Ready, Self::Leave];
+        if host {
+            states.splice(
+                1..1,
+                [
+                    Self::ScoreTarget,
+                    Self::Countdown,
+                    Self::FormationA,
+                    Self::FormationB,
+                ],
+            );
+        }
+        let position = states.iter().position(|state| state == self).unwrap_or(0) as isize;
+        *self = states[(position + direction as isize).rem_euclid(states.len() as isize) as usize];
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LobbyUIOutput {
+    Side(i8),
+    ScoreTarget(i8),
+    Countdown(i8),
+    Formation(Team, i8),
+    Ready,
+    Leave,
+}
+
+/// Shown once the peers of a network match are connected, see [`Lobby`].
+#[derive(HasSchema, Clone, Copy, Default)]
+pub struct LobbyUI {
+    pub visible: bool,
+    pub state: LobbyUIState,
+}
+impl ShowHide for LobbyUI {
+    fn show(&mut self) {
+        *self = Self {
+            visible: true,
+            ..default()
+        }
+    }
+    fn hide(&mut self) {
+        self.visible = false
+    }
+}
+impl SessionPlugin for LobbyUI {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+    }
+}
+
+impl LobbyUI {
+    fn select(&self) -> Option<LobbyUIOutput> {
+        match self.state {
+            LobbyUIState::Ready => Some(LobbyUIOutput::Ready),
+            LobbyUIState::Leave => Some(LobbyUIOutput::Leave),
+            LobbyUIState::Side
+            | LobbyUIState::ScoreTarget
+            | LobbyUIState::Countdown
+            | LobbyUIState::FormationA
+            | LobbyUIState::FormationB => None,
+        }
+    }
+    fn change(&self, direction: i8) -> Option<LobbyUIOutput> {
+        match self.state {
+            LobbyUIState::Side => Some(LobbyUIOutput::Side(direction)),
+            LobbyUIState::ScoreTarget => Some(LobbyUIOutput::ScoreTarget(direction)),
+            LobbyUIState::Countdown => Some(LobbyUIOutput::Countdown(direction)),
+            LobbyUIState::FormationA => Some(LobbyUIOutput::Formation(Team::A, direction)),
+            LobbyUIState::FormationB => Some(LobbyUIOutput::Formation(Team::B, direction)),
+            LobbyUIState::Ready | LobbyUIState::Leave => None,
+        }
+    }
+
+    pub fn process_input(&mut self, world: &World) -> Option<LobbyUIOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+        let host = world.resource::<Matchmaker>().lobby()?.is_host();
+        let local_inputs = world.resource::<LocalInputs>();
+
+        for (_source, input) in local_inputs.iter() {
+            if input.menu_select.just_pressed() {
+                output = self.select();
+            }
+            if input.menu_back.just_pressed() {
+                output = Some(LobbyUIOutput::Leave);
+            }
+            if input.menu_up.just_pressed() {
+                self.state.cycle(-1, host);
+            }
+            if input.menu_down.just_pressed() {
+                self.state.cycle(1, host);
+            }
+            if input.menu_left.just_pressed() {
+                output = self.change(-1);
+            }
+            if input.menu_right.just_pressed() {
+                output = self.change(1);
+            }
+        }
+        output
+    }
+
+    pub fn process_ui(&mut self, world: &World) -> Option<LobbyUIOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+        let matchmaker = world.resource::<Matchmaker>();
+        let Some(lobby) = matchmaker.lobby() else {
+            return output;
+        };
+        let local = lobby.local();
+        let assignment = lobby.assignment();
+
+        let asset_server = world.resource::<AssetServer>();
+        let root = asset_server.root::<Data>();
+        let ctx = world.resource::<EguiCtx>();
+        let pointer_navigation = world.resource::<LocalInputs>().pointer_navigation();
+
+        let peers = lobby
+            .peers()
+            .iter()
+            .enumerate()
+            .map(|(index, peer)| {
+                let Some(peer) = peer else {
+                    return "Connecting...".to_string();
+                };
+                let name = peer.name.chars().take(12).collect::<String>();
+                let team = match &assignment {
+                    Some(Ok(slots)) => match slots.slot(index as u8, false) {
+                        Some(slot) if slot.team() == Team::A => "Team A",
+                        Some(..) => "Team B",
+                        None => "",
+                    },
+                    _ => "",
+                };
+                let you = if index == lobby.local_idx() { "*" } else { "" };
+                let ready = if peer.ready { "Ready" } else { "" };
+                format!("{you}{name} P{} {team} {ready}", peer.players)
+            })
+            .collect::<Vec<_>>();
+        let status = match &assignment {
+            Some(Err(..)) => "Too Many Players For The Match",
+            _ if local.ready => "Waiting For Everyone...",
+            _ => "Pick A Side And Ready Up",
+        };
+        let side = match local.side {
+            LobbySide::Any => "Any",
+            LobbySide::A => "Team A",
+            LobbySide::B => "Team B",
+        };
+        let score_target = format!("First To: {}", lobby.rules().score_target);
+        let countdown = format!("Countdown: {}", lobby.rules().countdown);
+        let tactics = lobby.rules().tactics;
+        let formation_a = format!("Team A Cpus: {}", tactics.team_a.name());
+        let formation_b = format!("Team B Cpus: {}", tactics.team_b.name());
+        let settings = world
+            .resource::<Storage>()
+            .get::<Settings>()
+            .cloned()
+            .unwrap_or_default();
+        let delay = NetworkDelay::new(&settings, lobby.rtt());
+        let ping = lobby
+            .rtt()
+            .map_or("?".to_string(), |rtt| rtt.as_millis().to_string());
+        let network = format!(
+            "Ping: {ping} Delay: {} Prediction: {}",
+            delay.input_delay, delay.max_prediction
+        );
+
+        use egui::*;
+
+        Area::new("lobby_ui")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .order(Order::Foreground)
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        super::primary_text("Lobby", false, &asset_server, ui);
+                        for peer in &peers {
+                            super::primary_text(peer, false, &asset_server, ui);
+                        }
+                        super::primary_text(status, false, &asset_server, ui);
+                        super::primary_text(&network, false, &asset_server, ui);
+
+                        let mut rows = vec![
+                            (LobbyUIState::Side, format!("< Side: {side} >")),
+                            (
+                                LobbyUIState::Ready,
+                                if local.ready { "Ready!" } else { "Ready Up" }.to_string(),
+                            ),
+                            (LobbyUIState::Leave, "Leave".to_string()),
+                        ];
+                        if lobby.is_host() {
+                            rows.splice(
+                                1..1,
+                                [
+                                    (LobbyUIState::ScoreTarget, format!("< {score_target} >")),
+                                    (LobbyUIState::Countdown, format!("< {countdown} >")),
+                                    (LobbyUIState::FormationA, format!("< {formation_a} >")),
+                                    (LobbyUIState::FormationB, format!("< {formation_b} >")),
+                                ],
+                            );
+                        } else {
+                            for rule in [&score_target, &countdown, &formation_a, &formation_b] {
+                                super::primary_text(rule, false, &asset_server, ui);
+                            }
+                        }
+                        for (state, text) in rows {
+                            let response =
+                                super::primary_text(&text, self.state == state, &asset_server, ui);
+                            if response.hovered() && pointer_navigation {
+                                self.state = state;
+                            }
+                            if ctx.clicked_rect(response.rect) {
+                                self.state = state;
+                                output = self.select().or(self.change(1));
+                            }
+                        }
+                    });
+            });
+
+        output
+    }
+}
@@ -0,0 +1,289 @@
+use super::*;
+use bones_framework::networking::SyncingInfo;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the stats are shown and logged.
+pub const DIAGNOSTICS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The network stats of an online match over one [`DIAGNOSTICS_INTERVAL`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NetworkSample {
+    /// The seconds since the match started.
+    pub seconds: u64,
+    /// The round trip time to the slowest peer in milliseconds.
+    pub ping: u128,
+    /// How many frames this peer is behind the others, negative when ahead.
+    pub frames_behind: i32,
+    pub rollbacks: u32,
+    /// The most frames that were simulated again in one rollback.
+    pub max_rollback: i32,
+    /// The frames that were simulated with predicted inputs that turned out
+    /// wrong, which all had to be simulated again.
+    pub mispredicted_frames: u32,
+    pub frame: i32,
+    pub confirmed_frame: i32,
+}
+impl NetworkSample {
+    pub const CSV_HEADER: &'static str = "seconds,ping_ms,frames_behind,rollbacks,max_rollback,mispredicted_frames,frame,confirmed_frame";
+
+    pub fn csv_row(&self) -> String {
+        let Self {
+            seconds,
+            ping,
+            frames_behind,
+            rollbacks,
+            max_rollback,
+            mispredicted_frames,
+            frame,
+            confirmed_frame,
+        } = self;
+        format!("{seconds},{ping},{frames_behind},{rollbacks},{max_rollback},{mispredicted_frames},{frame},{confirmed_frame}")
+    }
+}
+
+/// Counts the rollbacks by watching for frames that were already simulated.
+#[derive(Clone, Debug, Default)]
+pub struct RollbackTracker {
+    last_frame: Option<i32>,
+    pub rollbacks: u32,
+    pub max_rollback: i32,
+    pub mispredicted_frames: u32,
+}
+impl RollbackTracker {
+    /// Records the frame that is being simulated.
+    pub fn simulate(&mut self, frame: i32) {
+        if let Some(last_frame) = self.last_frame.filter(|last| frame <= *last) {
+            let depth = last_frame - frame + 1;
+            self.rollbacks += 1;
+            self.max_rollback = self.max_rollback.max(depth);
+            self.mispredicted_frames += depth as u32;
+        }
+        self.last_frame = Some(frame);
+    }
+    /// Starts counting the next interval.
+    pub fn reset_counts(&mut self) {
+        *self = Self {
+            last_frame: self.last_frame,
+            ..default()
+        };
+    }
+}
+
+#[derive(Debug)]
+pub struct NetworkDiagnosticsState {
+    pub visible: bool,
+    pub tracker: RollbackTracker,
+    /// The last full interval, shown by the overlay.
+    pub sample: Option<NetworkSample>,
+    started: Instant,
+    next_sample: Instant,
+    log: Option<std::fs::File>,
+    /// Whether opening the log was attempted, which isn't retried.
+    log_opened: bool,
+}
+impl Default for NetworkDiagnosticsState {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            tracker: default(),
+            sample: None,
+            started: Instant::now(),
+            next_sample: Instant::now() + DIAGNOSTICS_INTERVAL,
+            log: None,
+            log_opened: false,
+        }
+    }
+}
+
+/// Shows the network stats of an online match, toggled with `F7`, and logs
+/// them to a csv in the `network_logs` of the data directory.
+///
+/// The state is shared between the snapshots of the play session so
+/// rollbacks don't restore it.
+#[derive(HasSchema, Clone, Default)]
+pub struct NetworkDiagnostics(pub Arc<Mutex<NetworkDiagnosticsState>>);
+impl SessionPlugin for NetworkDiagnostics {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+        session.add_system_to_stage(Last, record_network_diagnostics);
+    }
+}
+
+impl NetworkDiagnostics {
+    pub fn log_path() -> Option<std::path::PathBuf> {
+        let (qualifier, organization, application) = game::namespace();
+        let dirs = directories::ProjectDirs::from(qualifier, organization, application)?;
+        let started = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Some(
+            dirs.data_dir()
+                .join("network_logs")
+                .join(format!("match_{started}.csv")),
+        )
+    }
+    fn open_log() -> std::io::Result<std::fs::File> {
+        let path = Self::log_path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no data directory")
+        })?;
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        let mut file = std::fs::File::create(&path)?;
+        writeln!(file, "{}", NetworkSample::CSV_HEADER)?;
+        tracing::info!(?path, "logging network diagnostics");
+        Ok(file)
+    }
+}
+
+/// Toggles the overlay with `F7`.
+///
+/// This runs in the [`UI`] session, since the systems of the play session run
+/// again for every frame that is rolled back, which would toggle it again.
+pub fn toggle_network_diagnostics(ui: &World) {
+    let mut sessions = ui.resource_mut::<Sessions>();
+    let Some(diagnostics) = sessions
+        .get_world(session::PLAY)
+        .and_then(|play| play.get_resource::<NetworkDiagnostics>())
+    else {
+        return;
+    };
+    for input in ui.resource::<KeyboardInputs>().key_events.iter() {
+        if input.button_state == ButtonState::Pressed && input.key_code == Set(KeyCode::F7) {
+            let mut state = diagnostics.0.lock().unwrap();
+            state.visible = !state.visible;
+        }
+    }
+}
+
+pub fn record_network_diagnostics(
+    info: Option<Res<SyncingInfo>>,
+    diagnostics: Res<NetworkDiagnostics>,
+) {
+    let Some(SyncingInfo::Online {
+        current_frame,
+        last_confirmed_frame,
+        players_network_stats,
+        ..
+    }) = info.as_deref()
+    else {
+        return;
+    };
+    let mut state = diagnostics.0.lock().unwrap();
+    state.tracker.simulate(*current_frame);
+
+    let now = Instant::now();
+    if now < state.next_sample {
+        return;
+    }
+    state.next_sample = now + DIAGNOSTICS_INTERVAL;
+
+    let sample = NetworkSample {
+        seconds: now.duration_since(state.started).as_secs(),
+        ping: players_network_stats
+            .iter()
+            .map(|player| player.stats.ping)
+            .max()
+            .unwrap_or_default(),
+        frames_behind: players_network_stats
+            .iter()
+            .map(|player| player.stats.local_frames_behind)
+            .max()
+            .unwrap_or_default(),
+        rollbacks: state.tracker.rollbacks,
+        max_rollback: state.tracker.max_rollback,
+        mispredicted_frames: state.tracker.mispredicted_frames,
+        frame: *current_frame,
+        confirmed_frame: *last_confirmed_frame,
+    };
+    state.tracker.reset_counts();
+    state.sample = Some(sample);
+
+    if !state.log_opened {
+        state.log_opened = true;
+        state.log = NetworkDiagnostics::open_log()
+            .map_err(|err| tracing::warn!(%err, "failed to open the network log"))
+            .ok();
+    }
+    if let Some(file) = &mut state.log {
+        if let Err(err) = writeln!(file, "{}", sample.csv_row()) {
+            tracing::warn!(%err, "failed to write the network log");
+            state.log = None;
+        }
+    }
+}
+
+pub fn show(world: &World) {
+    let Some(diagnostics) = world.get_resource::<NetworkDiagnostics>() else {
+        return;
+    };
+    let state = diagnostics.0.lock().unwrap();
+    if !state.visible {
+        return;
+    }
+    let asset_server = world.resource::<AssetServer>();
+    let root = asset_server.root::<Data>();
+    let ctx = world.resource::<EguiCtx>();
+
+    let font = asset_server
+        .get(root.font.primary_inner)
+        .family_name
+        .clone();
+
+    let lines = match state.sample {
+        Some(sample) => vec![
+            format!("Ping: {}ms", sample.ping),
+            format!("Frames Behind: {}", sample.frames_behind),
+            format!("Rollbacks/s: {}", sample.rollbacks),
+            format!("Max Rollback: {}", sample.max_rollback),
+            format!("Mispredicted: {}", sample.mispredicted_frames),
+            format!("Frame: {}/{}", sample.frame, sample.confirmed_frame),
+        ],
+        None => vec!["Measuring...".to_string()],
+    };
+
+    use egui::*;
+
+    Area::new("network_diagnostics")
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_TOP, [-4., 4.])
+        .show(&ctx, |ui| {
+            ui.spacing_mut().item_spacing = vec2(3.0, 2.0);
+            for line in lines {
+                ui.label(RichText::new(line).color(Color32::WHITE).font(FontId {
+                    size: 7.0,
+                    family: FontFamily::Name(font.clone()),
+                }));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollback_tracker() {
+        let mut tracker = RollbackTracker::default();
+        for frame in 1..=10 {
+            tracker.simulate(frame);
+        }
+        assert_eq!(tracker.rollbacks, 0);
+
+        // Frames 7 to 10 are simulated again.
+        for frame in 7..=11 {
+            tracker.simulate(frame);
+        }
+        // Only frame 11 is simulated again.
+        tracker.simulate(11);
+        assert_eq!(tracker.rollbacks, 2);
+        assert_eq!(tracker.max_rollback, 4);
+        assert_eq!(tracker.mispredicted_frames, 5);
+
+        tracker.reset_counts();
+        tracker.simulate(12);
+        assert_eq!(tracker.rollbacks, 0);
+        assert_eq!(tracker.max_rollback, 0);
+    }
+}
@@ -0,0 +1,187 @@
+use super::*;
+
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NetworkGraceChoice {
+    TakeWin,
+    #[default]
+    Cpus,
+    Leave,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NetworkGraceOutput {
+    TakeWin,
+    Cpus,
+    Leave,
+}
+
+/// Shown while a network match waits for a dropped peer, see [`NetworkGrace`].
+///
+/// On LAN the peers try to find each other again while the countdown runs,
+/// by the host of the match hosting again under the same name. Once it runs
+/// out the remaining player picks how to go on. Online and direct connect
+/// matches can't find each other again, so the remaining player picks right away.
+#[derive(HasSchema, Clone, Default)]
+pub struct NetworkGraceUI {
+    pub visible: bool,
+    pub choice: NetworkGraceChoice,
+    /// Whether the local player is on the other team than the dropped one.
+    pub take_win: bool,
+    /// Whether the match was between two machines that found each other
+    /// through the LAN discovery, the only ones that can find each other again.
+    pub rejoin: bool,
+    /// Whether this peer hosted the match, which it hosts again to regroup.
+    pub host: bool,
+    /// The name the host of the match hosted with, which it hosts with again.
+    pub host_name: String,
+    /// Whether the peers are trying to find each other again.
+    pub regrouping: bool,
+    /// Where the match picks up from once the peers found each other.
+    pub resume: Option<MatchResume>,
+}
+impl ShowHide for NetworkGraceUI {
+    fn show(&mut self) {
+        self.visible = true;
+        self.choice = default();
+        self.regrouping = false;
+    }
+    fn hide(&mut self) {
+        self.visible = false
+    }
+}
+impl SessionPlugin for NetworkGraceUI {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+    }
+}
+
+impl NetworkGraceUI {
+    /// The grace of the current network match.
+    pub fn grace(world: &World) -> Option<NetworkGrace> {
+        world
+            .resource_mut::<Sessions>()
+            .get_session_resource::<NetworkGrace>(session::PLAY)
+            .as_deref()
+            .cloned()
+    }
+    /// Whether the dropped peer can come back, which only two machine LAN
+    /// matches found through the discovery try.
+    pub fn can_rejoin(&self, world: &World) -> bool {
+        self.rejoin && *world.resource::<Matchmaker>().online_status() != OnlineStatus::Ready
+    }
+    /// Whether the remaining player has to pick how to go on.
+    fn deciding(&self, world: &World) -> bool {
+        Self::grace(world).is_some_and(|grace| {
+            let state = grace.0.lock().unwrap();
+            state.is_frozen() && (state.timed_out() || !self.can_rejoin(world))
+        })
+    }
+    fn choices(&self, deciding: bool) -> Vec<(NetworkGraceChoice, &'static str)> {
+        let mut choices = vec![(NetworkGraceChoice::Leave, "Leave")];
+        if deciding {
+            choices.insert(0, (NetworkGraceChoice::Cpus, "Play With CPUs"));
+            if self.take_win {
+                choices.insert(0, (NetworkGraceChoice::TakeWin, "Take The Win"));
+            }
+        }
+        choices
+    }
+    fn select(&self) -> NetworkGraceOutput {
+        match self.choice {
+            NetworkGraceChoice::TakeWin => NetworkGraceOutput::TakeWin,
+            NetworkGraceChoice::Cpus => NetworkGraceOutput::Cpus,
+            NetworkGraceChoice::Leave => NetworkGraceOutput::Leave,
+        }
+    }
+
+    pub fn process_input(&mut self, world: &World) -> Option<NetworkGraceOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+        let choices = self.choices(self.deciding(world));
+        let mut position = choices
+            .iter()
+            .position(|(choice, _)| *choice == self.choice)
+            .unwrap_or(0) as isize;
+        let local_inputs = world.resource::<LocalInputs>();
+
+        for (_source, input) in local_inputs.iter() {
+            if input.menu_up.just_pressed() {
+                position -= 1;
+            }
+            if input.menu_down.just_pressed() {
+                position += 1;
+            }
+            self.choice = choices[position.rem_euclid(choices.len() as isize) as usize].0;
+            if input.menu_select.just_pressed() {
+                output = Some(self.select());
+            }
+        }
+        output
+    }
+
+    pub fn process_ui(&mut self, world: &World) -> Option<NetworkGraceOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+        let Some(grace) = Self::grace(world) else {
+            return output;
+        };
+        let deciding = self.deciding(world);
+        let remaining = grace.0.lock().unwrap().remaining();
+
+        let asset_server = world.resource::<AssetServer>();
+        let root = asset_server.root::<Data>();
+        let ctx = world.resource::<EguiCtx>();
+        let pointer_navigation = world.resource::<LocalInputs>().pointer_navigation();
+
+        let status = if deciding {
+            "The Other Player Is Gone".to_string()
+        } else {
+            format!(
+                "Waiting For The Other Player... {}",
+                remaining.as_secs() + 1
+            )
+        };
+
+        use egui::*;
+
+        Area::new("network_grace")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .order(Order::Foreground)
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        super::primary_text("Connection Lost", false, &asset_server, ui);
+                        super::primary_text(&status, false, &asset_server, ui);
+                        if !self.rejoin {
+                            super::primary_text(
+                                "Only LAN Matches Can Rejoin",
+                                false,
+                                &asset_server,
+                                ui,
+                            );
+                        }
+
+                        for (choice, text) in self.choices(deciding) {
+                            let response =
+                                super::primary_text(text, self.choice == choice, &asset_server, ui);
+                            if response.hovered() && pointer_navigation {
+                                self.choice = choice;
+                            }
+                            if ctx.clicked_rect(response.rect) {
+                                self.choice = choice;
+                                output = Some(self.select());
+                            }
+                        }
+                    });
+            });
+
+        output
+    }
+}
@@ -0,0 +1,165 @@
+use super::*;
+
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NetworkMatchDoneState {
+    #[default]
+    Rematch,
+    Leave,
+}
+impl NetworkMatchDoneState {
+    pub fn cycle(&mut self) {
+        *self = match self {
+            Self::Rematch => Self::Leave,
+            Self::Leave => Self::Rematch,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NetworkMatchDoneOutput {
+    Rematch,
+    Leave,
+}
+
+/// Shown to every peer once a network match is done, the match is played
+/// again on the same socket when all of them vote for a rematch.
+#[derive(HasSchema, Clone, Default)]
+pub struct NetworkMatchDone {
+    pub visible: bool,
+    pub state: NetworkMatchDoneState,
+    /// The peers that voted for a rematch, by player index.
+    pub rematch: [bool; MAX_PEERS],
+    /// Whether a peer left or disconnected, which rules out a rematch.
+    pub peer_left: bool,
+}
+impl ShowHide for NetworkMatchDone {
+    fn show(&mut self) {
+        *self = Self {
+            visible: true,
+            ..default()
+        }
+    }
+    fn hide(&mut self) {
+        self.visible = false
+    }
+}
+impl SessionPlugin for NetworkMatchDone {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+    }
+}
+
+impl NetworkMatchDone {
+    pub fn vote(&mut self, peer: u32) {
+        if let Some(vote) = self.rematch.get_mut(peer as usize) {
+            *vote = true;
+        }
+    }
+    pub fn voted(&self, peer: u32) -> bool {
+        self.rematch.get(peer as usize).copied().unwrap_or_default()
+    }
+    /// Whether the first `peers` peers all voted for a rematch.
+    pub fn all_voted(&self, peers: usize) -> bool {
+        !self.peer_left
+            && self.rematch[..peers.min(MAX_PEERS)]
+                .iter()
+                .all(|vote| *vote)
+    }
+    fn select(&mut self, local: u32) -> Option<NetworkMatchDoneOutput> {
+        match self.state {
+            NetworkMatchDoneState::Rematch => {
+                (!self.peer_left && !self.voted(local)).then_some(NetworkMatchDoneOutput::Rematch)
+            }
+            NetworkMatchDoneState::Leave => Some(NetworkMatchDoneOutput::Leave),
+        }
+    }
+
+    pub fn process_input(&mut self, world: &World) -> Option<NetworkMatchDoneOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+        let local = world
+            .resource::<Matchmaker>()
+            .network_match_socket()
+            .map_or(0, |socket| socket.player_idx());
+        let local_inputs = world.resource::<LocalInputs>();
+
+        for (_source, input) in local_inputs.iter() {
+            if input.menu_select.just_pressed() {
+                output = self.select(local);
+            }
+            if input.menu_up.just_pressed()
+                || input.menu_down.just_pressed()
+                || input.menu_left.just_pressed()
+                || input.menu_right.just_pressed()
+            {
+                self.state.cycle();
+            }
+        }
+        if self.peer_left {
+            self.state = NetworkMatchDoneState::Leave;
+        }
+        output
+    }
+
+    pub fn process_ui(&mut self, world: &World) -> Option<NetworkMatchDoneOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+        let socket = world.resource::<Matchmaker>().network_match_socket();
+        let local = socket.as_ref().map_or(0, |socket| socket.player_idx());
+        let peers = socket
+            .as_ref()
+            .map_or(0, |socket| (socket.player_count() as usize).min(MAX_PEERS));
+        let votes = self.rematch[..peers].iter().filter(|vote| **vote).count();
+
+        let asset_server = world.resource::<AssetServer>();
+        let root = asset_server.root::<Data>();
+        let ctx = world.resource::<EguiCtx>();
+        let pointer_navigation = world.resource::<LocalInputs>().pointer_navigation();
+
+        let status = if self.peer_left {
+            "A Player Left The Match".to_string()
+        } else if self.voted(local) {
+            format!("Waiting For Rematch ({votes}/{peers})")
+        } else {
+            format!("Rematch Votes: {votes}/{peers}")
+        };
+
+        use egui::*;
+
+        Area::new("network_match_done")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .order(Order::Foreground)
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        super::primary_text("Match Over", false, &asset_server, ui);
+                        super::primary_text(&status, false, &asset_server, ui);
+
+                        let mut rows = vec![(NetworkMatchDoneState::Leave, "Leave")];
+                        if !self.peer_left {
+                            rows.insert(0, (NetworkMatchDoneState::Rematch, "Rematch"));
+                        }
+                        for (state, text) in rows {
+                            let response =
+                                super::primary_text(text, self.state == state, &asset_server, ui);
+                            if response.hovered() && pointer_navigation {
+                                self.state = state;
+                            }
+                            if ctx.clicked_rect(response.rect) {
+                                self.state = state;
+                                output = self.select(local);
+                            }
+                        }
+                    });
+            });
+
+        output
+    }
+}
@@ -0,0 +1,244 @@
+use super::*;
+
+#[derive(HasSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnlineUIState {
+    #[default]
+    QuickMatch,
+    /// Focus is on the match code.
+    Code,
+    /// Changing the character of the match code at the index.
+    EditCode(usize),
+    PrivateMatch,
+}
+impl OnlineUIState {
+    pub fn cycle_down(&mut self) {
+        *self = match self {
+            Self::QuickMatch => Self::Code,
+            Self::Code => Self::PrivateMatch,
+            Self::PrivateMatch => Self::QuickMatch,
+            Self::EditCode(index) => Self::EditCode(*index),
+        }
+    }
+    pub fn cycle_up(&mut self) {
+        self.cycle_down();
+        self.cycle_down();
+    }
+}
+
+pub enum OnlineUIOutput {
+    Search(OnlineSearch),
+    /// Stops the search, or dismisses its error.
+    Cancel,
+    Exit,
+}
+
+/// Finds a match over the internet through the matchmaking server.
+///
+/// A quick match is against anyone else doing a quick match, while a
+/// private match is only against players that entered the same code.
+#[derive(HasSchema, Clone, Default)]
+pub struct OnlineUI {
+    pub visible: bool,
+    pub service: ServiceType,
+    pub state: OnlineUIState,
+    pub code: MatchCode,
+}
+impl ShowHide for OnlineUI {
+    fn show(&mut self) {
+        *self = Self {
+            visible: true,
+            service: self.service,
+            code: MatchCode::random(),
+            ..default()
+        }
+    }
+    fn hide(&mut self) {
+        self.visible = false
+    }
+}
+impl SessionPlugin for OnlineUI {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+    }
+}
+
+impl OnlineUI {
+    fn select(&mut self) -> Option<OnlineUIOutput> {
+        match self.state {
+            OnlineUIState::QuickMatch => Some(OnlineUIOutput::Search(OnlineSearch::QuickMatch)),
+            OnlineUIState::Code => {
+                self.state = OnlineUIState::EditCode(0);
+                None
+            }
+            OnlineUIState::EditCode(..) => {
+                self.state = OnlineUIState::Code;
+                None
+            }
+            OnlineUIState::PrivateMatch => {
+                Some(OnlineUIOutput::Search(OnlineSearch::Private(self.code)))
+            }
+        }
+    }
+
+    pub fn process_input(&mut self, world: &World) -> Option<OnlineUIOutput> {
+        let mut output = None;
+
+        let idle = *world.resource::<Matchmaker>().online_status() == OnlineStatus::Idle;
+        let local_inputs = world.resource::<LocalInputs>();
+
+        for (_source, input) in local_inputs.iter() {
+            if !idle {
+                if input.menu_select.just_pressed() || input.menu_back.just_pressed() {
+                    output = Some(OnlineUIOutput::Cancel);
+                }
+                continue;
+            }
+            if let OnlineUIState::EditCode(index) = &mut self.state {
+                if input.menu_up.just_pressed() {
+                    self.code.cycle(*index, 1);
+                }
+                if input.menu_down.just_pressed() {
+                    self.code.cycle(*index, -1);
+                }
+                if input.menu_left.just_pressed() {
+                    *index = index.checked_sub(1).unwrap_or(MatchCode::LEN - 1);
+                }
+                if input.menu_right.just_pressed() {
+                    *index = (*index + 1) % MatchCode::LEN;
+                }
+                if input.menu_select.just_pressed() || input.menu_back.just_pressed() {
+                    self.state = OnlineUIState::Code;
+                }
+                continue;
+            }
+            if input.menu_select.just_pressed() {
+                output = self.select();
+            }
+            if input.menu_back.just_pressed() {
+                output = Some(OnlineUIOutput::Exit);
+            }
+            if input.menu_up.just_pressed() {
+                self.state.cycle_up();
+            }
+            if input.menu_down.just_pressed() {
+                self.state.cycle_down();
+            }
+        }
+        output
+    }
+
+    pub fn process_ui(&mut self, world: &World) -> Option<OnlineUIOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+
+        let asset_server = world.resource::<AssetServer>();
+        let root = asset_server.root::<Data>();
+        let textures = world.resource::<EguiTextures>();
+        let ctx = world.resource::<EguiCtx>();
+        let pointer_navigation = world.resource::<LocalInputs>().pointer_navigation();
+        let status = world.resource::<Matchmaker>().online_status().clone();
+        let searching = world.resource::<Matchmaker>().online_searching();
+
+        let service = match self.service {
+            ServiceType::OnePlayer(..) => "ONLINE - SOLO",
+            ServiceType::TwoPlayer(..) => "ONLINE - DOUBLES",
+            ServiceType::Single(..) => "ONLINE - SINGLE",
+        };
+        let code = match self.state {
+            OnlineUIState::EditCode(index) => self
+                .code
+                .to_string()
+                .chars()
+                .enumerate()
+                .map(|(i, char)| {
+                    if i == index {
+                        format!("[{char}]")
+                    } else {
+                        char.to_string()
+                    }
+                })
+                .collect::<String>(),
+            _ => self.code.to_string(),
+        };
+
+        use egui::*;
+
+        Area::new("online_bg")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show(&ctx, |ui| {
+                ui.image(load::SizedTexture::new(
+                    textures.get(root.menu.splash.bg),
+                    root.screen_size.to_array(),
+                ));
+            });
+
+        Area::new("online")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .order(Order::Foreground)
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        super::primary_text(service, false, &asset_server, ui);
+
+                        let lines = match &status {
+                            OnlineStatus::Idle => {
+                                let rows = [
+                                    (OnlineUIState::QuickMatch, "Quick Match".to_string()),
+                                    (OnlineUIState::Code, format!("Code: {code}")),
+                                    (OnlineUIState::PrivateMatch, "Private Match".to_string()),
+                                ];
+                                for (state, text) in rows {
+                                    let selected = self.state == state
+                                        || (state == OnlineUIState::Code
+                                            && matches!(self.state, OnlineUIState::EditCode(..)));
+                                    let response =
+                                        super::primary_text(&text, selected, &asset_server, ui);
+                                    if response.hovered()
+                                        && pointer_navigation
+                                        && !matches!(self.state, OnlineUIState::EditCode(..))
+                                    {
+                                        self.state = state;
+                                    }
+                                    if ctx.clicked_rect(response.rect) {
+                                        output = self.select();
+                                    }
+                                }
+                                return;
+                            }
+                            OnlineStatus::Waiting { players } => {
+                                let mut lines = vec!["Searching...".to_string()];
+                                if let Some(OnlineSearch::Private(code)) = searching {
+                                    lines.push(format!("Code: {code}"));
+                                }
+                                if *players > 0 {
+                                    lines.push(format!("Players: {players}"));
+                                }
+                                lines.push("Cancel".to_string());
+                                lines
+                            }
+                            OnlineStatus::Ready => vec!["Starting...".to_string()],
+                            OnlineStatus::Failed(err) => vec![
+                                "Matchmaking Failed".to_string(),
+                                err.clone(),
+                                "Back".to_string(),
+                            ],
+                        };
+                        // The last line cancels, unless the match is already starting.
+                        let last = (status != OnlineStatus::Ready).then_some(lines.len() - 1);
+                        for (i, line) in lines.iter().enumerate() {
+                            let response =
+                                super::primary_text(line, Some(i) == last, &asset_server, ui);
+                            if Some(i) == last && ctx.clicked_rect(response.rect) {
+                                output = Some(OnlineUIOutput::Cancel);
+                            }
+                        }
+                    });
+            });
+
+        output
+    }
+}
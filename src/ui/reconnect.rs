@@ -0,0 +1,161 @@
+use super::*;
+
+#[derive(HasSchema, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct GamepadRemap {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// Pauses an offline match when the gamepad of a local player disconnects,
+/// until a gamepad is pressed to take its place.
+///
+/// The same gamepad can be reconnected, or any gamepad that isn't already
+/// playing can be used instead.
+#[derive(HasSchema, Clone, Default)]
+pub struct ReconnectPrompt {
+    /// The disconnected gamepads that are still assigned to players.
+    pub missing: SVec<u32>,
+    /// The gamepads that took the place of others, which are applied again
+    /// when the match restarts.
+    pub remaps: SVec<GamepadRemap>,
+    /// The pause menu as it was when the prompt was shown, which is put back
+    /// once the prompt closes.
+    pub pause: Pause,
+}
+impl SessionPlugin for ReconnectPrompt {
+    fn install(self, session: &mut SessionBuilder) {
+        session.insert_resource(self);
+    }
+}
+
+pub enum ReconnectOutput {
+    /// The first gamepad disconnected.
+    Show,
+    /// A gamepad took the place of a disconnected one.
+    Reassign(GamepadRemap),
+}
+
+impl ReconnectPrompt {
+    pub fn visible(&self) -> bool {
+        !self.missing.is_empty()
+    }
+
+    /// The offline [`PlayMode`] of the match.
+    fn play_mode(world: &World) -> Option<PlayMode> {
+        let mode = world
+            .resource_mut::<Sessions>()
+            .get_session_resource::<PlayMode>(session::PLAY)
+            .as_deref()
+            .cloned();
+        mode.filter(|mode| matches!(mode, PlayMode::Offline(..)))
+    }
+
+    /// The players using the gamepad.
+    fn gamepad_players(mode: &PlayMode, gamepad: u32) -> Vec<PlayerInfo> {
+        PlayerSlot::variants()
+            .into_iter()
+            .map(|slot| mode.player_info(slot))
+            .filter(|info| {
+                matches!(
+                    info,
+                    PlayerInfo::Local { source, .. } if *source == SingleSource::Gamepad(gamepad)
+                )
+            })
+            .collect()
+    }
+
+    pub fn process_input(&mut self, world: &World) -> Option<ReconnectOutput> {
+        let mut output = None;
+
+        let mode = Self::play_mode(world)?;
+
+        for event in &world.resource::<GamepadInputs>().gamepad_events {
+            let GamepadEvent::Connection(GamepadConnectionEvent {
+                gamepad,
+                event: GamepadConnectionEventKind::Disconnected,
+                ..
+            }) = event
+            else {
+                continue;
+            };
+            if Self::gamepad_players(&mode, *gamepad).is_empty()
+                || self.missing.iter().any(|missing| missing == gamepad)
+            {
+                continue;
+            }
+            tracing::info!(gamepad, "gamepad disconnected during the match");
+            if !self.visible() {
+                output = Some(ReconnectOutput::Show);
+            }
+            self.missing.push(*gamepad);
+        }
+
+        let Some(from) = self.missing.iter().next().copied() else {
+            return output;
+        };
+        for (source, input) in world.resource::<LocalInputs>().iter() {
+            let SingleSource::Gamepad(to) = *source else {
+                continue;
+            };
+            if !(input.start.just_pressed() || input.menu_select.just_pressed()) {
+                continue;
+            }
+            // Gamepads that are already playing can't take another player's place.
+            if to != from && !Self::gamepad_players(&mode, to).is_empty() {
+                continue;
+            }
+            tracing::info!(from, to, "gamepad reassigned");
+
+            let remap = GamepadRemap { from, to };
+            if from != to {
+                self.remaps.push(remap);
+            }
+            self.missing = self.missing.iter().copied().skip(1).collect();
+            output = Some(ReconnectOutput::Reassign(remap));
+            break;
+        }
+        output
+    }
+
+    pub fn process_ui(&mut self, world: &World) {
+        let Some(gamepad) = self.missing.iter().next().copied() else {
+            return;
+        };
+        let Some(mode) = Self::play_mode(world) else {
+            return;
+        };
+        let mut players = Self::gamepad_players(&mode, gamepad)
+            .into_iter()
+            .filter_map(|info| match info {
+                PlayerInfo::Local { number, .. } => Some(format!("P{}", number + 1)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        players.dedup();
+        let players = players.join(" & ");
+
+        let asset_server = world.resource::<AssetServer>();
+        let root = asset_server.root::<Data>();
+        let ctx = world.resource::<EguiCtx>();
+
+        use egui::*;
+
+        Area::new("reconnect_prompt")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .order(Order::Foreground)
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        super::primary_text("Controller Disconnected", false, &asset_server, ui);
+                        super::primary_text(
+                            &format!("{players}: Press Start On A Controller"),
+                            true,
+                            &asset_server,
+                            ui,
+                        );
+                    });
+            });
+    }
+}
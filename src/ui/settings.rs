@@ -1,10 +1,13 @@
 use super::*;
+use std::time::Duration;
 
 #[derive(HasSchema, Clone, Default)]
 #[repr(C)]
 pub struct Settings {
     pub sfx_volume: VolumeSetting,
     pub music_volume: VolumeSetting,
+    pub input_delay: InputDelaySetting,
+    pub max_prediction: PredictionSetting,
 }
 
 #[derive(HasSchema, Clone, Copy, Deref, DerefMut)]
@@ -23,6 +26,94 @@ impl VolumeSetting {
     }
 }
 
+/// The frames the local inputs of a network match are delayed by, which
+/// gives them time to reach the other peers before they're needed.
+#[derive(HasSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct InputDelaySetting {
+    /// Whether the delay is picked from the round trip time to the other peers.
+    pub auto: bool,
+    /// The delay when it isn't picked automatically.
+    pub frames: u8,
+}
+impl Default for InputDelaySetting {
+    fn default() -> Self {
+        Self {
+            auto: true,
+            frames: 2,
+        }
+    }
+}
+impl InputDelaySetting {
+    pub const MAX_FRAMES: u8 = 8;
+    /// How long a frame of a network match lasts.
+    pub const FRAME: Duration = Duration::from_micros(16_667);
+
+    /// Cycles through the automatic delay followed by every fixed delay.
+    pub fn cycle(&mut self, direction: i8) {
+        let steps = Self::MAX_FRAMES as isize + 2;
+        let position = if self.auto {
+            0
+        } else {
+            self.frames as isize + 1
+        };
+        match (position + direction as isize).rem_euclid(steps) {
+            0 => self.auto = true,
+            position => {
+                self.auto = false;
+                self.frames = position as u8 - 1;
+            }
+        }
+    }
+    /// The delay that covers the time the inputs take to reach the other
+    /// peers, which is half of the round trip.
+    pub fn from_rtt(rtt: Duration) -> u8 {
+        let frames = (rtt / 2).as_micros().div_ceil(Self::FRAME.as_micros());
+        frames.clamp(1, Self::MAX_FRAMES as u128) as u8
+    }
+    /// The delay to play with, given the round trip time to the slowest peer
+    /// if it has been measured.
+    pub fn frames(&self, rtt: Option<Duration>) -> u8 {
+        match rtt {
+            Some(rtt) if self.auto => Self::from_rtt(rtt),
+            _ if self.auto => Self::default().frames,
+            _ => self.frames,
+        }
+    }
+}
+impl std::fmt::Display for InputDelaySetting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.auto {
+            write!(f, "Auto")
+        } else {
+            write!(f, "{}", self.frames)
+        }
+    }
+}
+
+/// The most frames the inputs of the other peers of a network match are
+/// predicted for before the match waits for them.
+#[derive(HasSchema, Clone, Copy, Debug, Deref, DerefMut)]
+#[repr(C)]
+pub struct PredictionSetting(pub u8);
+
+impl Default for PredictionSetting {
+    fn default() -> Self {
+        Self(7)
+    }
+}
+impl PredictionSetting {
+    pub const MIN: u8 = 1;
+    pub const MAX: u8 = 12;
+
+    pub fn change(&mut self, change: i8) {
+        self.0 = self
+            .0
+            .saturating_add_signed(change)
+            .clamp(Self::MIN, Self::MAX);
+    }
+}
+
 #[derive(HasSchema, Clone, Default)]
 #[repr(C)]
 pub struct SettingsAssets {
@@ -45,12 +136,16 @@ pub enum SettingsState {
     #[default]
     SFX,
     Music,
+    Controls,
+    Sticks,
 }
 impl SettingsState {
     pub fn cycle(&mut self) {
         *self = match self {
             SettingsState::SFX => SettingsState::Music,
-            SettingsState::Music => SettingsState::SFX,
+            SettingsState::Music => SettingsState::Controls,
+            SettingsState::Controls => SettingsState::Sticks,
+            SettingsState::Sticks => SettingsState::SFX,
         }
     }
 }
@@ -60,13 +155,17 @@ pub struct SettingsUi {
     #[deref]
     pub state: SettingsState,
     pub visible: bool,
+    pub controls: ControlsUi,
+    pub sticks: SticksUi,
 }
 impl ShowHide for SettingsUi {
     fn show(&mut self) {
         self.visible = true
     }
     fn hide(&mut self) {
-        self.visible = false
+        self.visible = false;
+        self.controls.hide();
+        self.sticks.hide();
     }
 }
 
@@ -86,6 +185,19 @@ impl SettingsUi {
     pub fn process_input(&mut self, world: &World) -> Option<SettingsOutput> {
         let mut output = None;
 
+        if self.controls.visible {
+            if self.controls.process_input(world).is_some() {
+                self.controls.hide();
+            }
+            return output;
+        }
+        if self.sticks.visible {
+            if self.sticks.process_input(world).is_some() {
+                self.sticks.hide();
+            }
+            return output;
+        }
+
         let local_inputs = world.resource::<LocalInputs>();
 
         let mut storage = world.resource_mut::<Storage>();
@@ -94,6 +206,7 @@ impl SettingsUi {
         let Settings {
             sfx_volume,
             music_volume,
+            ..
         } = storage.get_or_insert_default_mut::<Settings>();
 
         let asset_server = world.asset_server();
@@ -103,9 +216,20 @@ impl SettingsUi {
             if input.menu_back.just_pressed() {
                 output = Some(SettingsOutput)
             }
-            if input.menu_down.just_pressed() || input.menu_up.just_pressed() {
+            if input.menu_down.just_pressed() {
+                self.cycle();
+            }
+            if input.menu_up.just_pressed() {
+                self.cycle();
                 self.cycle();
             }
+            if input.menu_select.just_pressed() {
+                match self.state {
+                    SettingsState::Controls => self.controls.show(),
+                    SettingsState::Sticks => self.sticks.show(),
+                    SettingsState::SFX | SettingsState::Music => {}
+                }
+            }
             if input.menu_left.just_pressed() {
                 match self.state {
                     SettingsState::SFX => {
@@ -132,6 +256,7 @@ impl SettingsUi {
                             sfx_volume.scale(),
                         );
                     }
+                    SettingsState::Controls | SettingsState::Sticks => {}
                 }
             }
             if input.menu_right.just_pressed() {
@@ -159,6 +284,7 @@ impl SettingsUi {
                             sfx_volume.scale(),
                         );
                     }
+                    SettingsState::Controls | SettingsState::Sticks => {}
                 }
             }
         }
@@ -175,6 +301,14 @@ impl SettingsUi {
         if !self.visible {
             return output;
         }
+        if self.controls.visible {
+            self.controls.process_ui(world);
+            return output;
+        }
+        if self.sticks.visible {
+            self.sticks.process_ui(world);
+            return output;
+        }
 
         let local_inputs = world.resource::<LocalInputs>();
         let asset_server = world.resource::<AssetServer>();
@@ -233,6 +367,39 @@ impl SettingsUi {
                     .offset(music_volume_label_offset.to_array().into())
                     .paint(&painter, &textures);
             }
+            SettingsState::Controls | SettingsState::Sticks => {}
+        }
+
+        let [controls_response, sticks_response] = Area::new("settings_controls")
+            .order(Order::Foreground)
+            .pivot(Align2::CENTER_TOP)
+            .fixed_pos(settings_rect.center_bottom() + vec2(0.0, 4.0))
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        [
+                            ("CONTROLS", SettingsState::Controls),
+                            ("STICKS", SettingsState::Sticks),
+                        ]
+                        .map(|(text, state)| {
+                            super::primary_text(text, self.state == state, &asset_server, ui)
+                        })
+                    })
+            })
+            .inner
+            .inner;
+        if controls_response.hovered() && pointer_navigation {
+            self.state = SettingsState::Controls;
+        }
+        if sticks_response.hovered() && pointer_navigation {
+            self.state = SettingsState::Sticks;
+        }
+        if ctx.clicked_rect(controls_response.rect) {
+            self.controls.show();
+        }
+        if ctx.clicked_rect(sticks_response.rect) {
+            self.sticks.show();
         }
 
         // Naming scheme
@@ -0,0 +1,205 @@
+use super::*;
+
+/// The stick settings screen shown from the settings.
+///
+/// The first row picks the gamepad being edited, `None` being the defaults
+/// used by every gamepad without its own settings. Left and right change the
+/// selected value, which is saved right away.
+#[derive(HasSchema, Clone, Default)]
+pub struct SticksUi {
+    pub visible: bool,
+    pub row: usize,
+    pub gamepad: Option<u32>,
+}
+impl ShowHide for SticksUi {
+    fn show(&mut self) {
+        *self = Self {
+            visible: true,
+            ..default()
+        }
+    }
+    fn hide(&mut self) {
+        self.visible = false
+    }
+}
+
+pub struct SticksOutput;
+
+impl SticksUi {
+    pub const GAMEPAD_ROW: usize = 0;
+    pub const INNER_ROW: usize = 1;
+    pub const OUTER_ROW: usize = 2;
+    pub const SHAPE_ROW: usize = 3;
+    pub const CURVE_ROW: usize = 4;
+    pub const RESET_ROW: usize = 5;
+
+    /// The gamepads that have sent any input, in order.
+    fn gamepads(world: &World) -> Vec<u32> {
+        let mut gamepads = world
+            .resource::<LocalInputs>()
+            .keys()
+            .filter_map(|source| match source {
+                SingleSource::Gamepad(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        gamepads.sort();
+        gamepads
+    }
+
+    pub fn process_input(&mut self, world: &World) -> Option<SticksOutput> {
+        let mut output = None;
+
+        if !self.visible {
+            return output;
+        }
+
+        let local_inputs = world.resource::<LocalInputs>();
+
+        for (_source, input) in local_inputs.iter() {
+            if input.menu_back.just_pressed() {
+                output = Some(SticksOutput);
+            }
+            if input.menu_up.just_pressed() {
+                self.row = self.row.checked_sub(1).unwrap_or(Self::RESET_ROW);
+            }
+            if input.menu_down.just_pressed() {
+                self.row = (self.row + 1) % (Self::RESET_ROW + 1);
+            }
+            if input.menu_left.just_pressed() {
+                self.change(world, -1);
+            }
+            if input.menu_right.just_pressed() {
+                self.change(world, 1);
+            }
+            if input.menu_select.just_pressed() {
+                self.select(world);
+            }
+        }
+        output
+    }
+
+    /// Steps the value on the selected row.
+    fn change(&mut self, world: &World, direction: i8) {
+        if self.row == Self::GAMEPAD_ROW {
+            let choices = std::iter::once(None)
+                .chain(Self::gamepads(world).into_iter().map(Some))
+                .collect::<Vec<_>>();
+            let index = choices
+                .iter()
+                .position(|gamepad| *gamepad == self.gamepad)
+                .unwrap_or(0);
+            let index = (index as isize + direction as isize).rem_euclid(choices.len() as isize);
+            self.gamepad = choices[index as usize];
+            return;
+        }
+        if self.row == Self::RESET_ROW {
+            return;
+        }
+
+        let mut sticks = world.resource::<InputSettings>().sticks.clone();
+        let settings = sticks.get_mut(self.gamepad);
+        let step = StickSettings::STEP * direction as f32;
+
+        match self.row {
+            Self::INNER_ROW => settings.set_inner_dead_zone(settings.inner_dead_zone + step),
+            Self::OUTER_ROW => settings.set_outer_dead_zone(settings.outer_dead_zone + step),
+            Self::SHAPE_ROW => settings.shape.cycle(),
+            Self::CURVE_ROW if direction > 0 => settings.curve.cycle(),
+            Self::CURVE_ROW => {
+                settings.curve.cycle();
+                settings.curve.cycle();
+            }
+            _ => {}
+        }
+        InputSettings::save_sticks(world, sticks);
+        tracing::info!("storage saved");
+    }
+
+    fn select(&mut self, world: &World) {
+        if self.row == Self::RESET_ROW {
+            let mut sticks = world.resource::<InputSettings>().sticks.clone();
+            sticks.reset(self.gamepad);
+            InputSettings::save_sticks(world, sticks);
+            tracing::info!("stick settings reset");
+        } else {
+            self.change(world, 1);
+        }
+    }
+
+    pub fn process_ui(&mut self, world: &World) {
+        if !self.visible {
+            return;
+        }
+
+        let asset_server = world.resource::<AssetServer>();
+        let root = asset_server.root::<Data>();
+        let textures = world.resource::<EguiTextures>();
+        let ctx = world.resource::<EguiCtx>();
+        let local_inputs = world.resource::<LocalInputs>();
+        let pointer_navigation = local_inputs.pointer_navigation();
+        let settings = world.resource::<InputSettings>().sticks.get(self.gamepad);
+
+        let gamepad = match self.gamepad {
+            Some(id) => format!("Gamepad: {id}"),
+            None => "Gamepad: All".to_string(),
+        };
+        let rows = [
+            gamepad,
+            format!("Inner Dead Zone: {:.2}", settings.inner_dead_zone),
+            format!("Outer Dead Zone: {:.2}", settings.outer_dead_zone),
+            format!("Dead Zone: {}", settings.shape.name()),
+            format!("Response: {}", settings.curve.name()),
+            "Reset Defaults".to_string(),
+        ];
+        // Shows what the stick is doing so that drift can be tuned out.
+        let preview = self.gamepad.and_then(|id| {
+            let input = local_inputs.get(&SingleSource::Gamepad(id))?;
+            Some(format!(
+                "Stick: {:.2} -> {:.2}",
+                input.raw_left_stick.length(),
+                settings.apply(input.raw_left_stick).length()
+            ))
+        });
+
+        use egui::*;
+
+        Area::new("sticks_bg")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show(&ctx, |ui| {
+                ui.image(load::SizedTexture::new(
+                    textures.get(root.menu.splash.bg),
+                    root.screen_size.to_array(),
+                ));
+            });
+
+        let mut clicked = false;
+
+        Area::new("sticks")
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .order(Order::Foreground)
+            .show(&ctx, |ui| {
+                BorderedFrame::new(&root.menu.bframe)
+                    .padding(Margin::same(6.0))
+                    .show(ui, |ui| {
+                        for (row, text) in rows.iter().enumerate() {
+                            let response =
+                                super::primary_text(text, self.row == row, &asset_server, ui);
+                            if response.hovered() && pointer_navigation {
+                                self.row = row;
+                            }
+                            if ctx.clicked_rect(response.rect) {
+                                clicked = true;
+                            }
+                        }
+                        if let Some(preview) = &preview {
+                            super::primary_text(preview, false, &asset_server, ui);
+                        }
+                    });
+            });
+
+        if clicked {
+            self.select(world);
+        }
+    }
+}
@@ -35,6 +35,14 @@ impl TeamSelect {
                     return Some(TeamSelectOutput::PlayersInfo(players_info.clone()));
                 }
             }
+            if self.contains_source(*source) {
+                if input.left_bump.just_pressed() {
+                    self.tactics.team_a.cycle(1);
+                }
+                if input.right_bump.just_pressed() {
+                    self.tactics.team_b.cycle(1);
+                }
+            }
             if input.start.just_pressed()
                 || input.north.just_pressed()
                 || input.east.just_pressed()
@@ -213,6 +221,49 @@ impl TeamSelect {
             default(),
         );
 
+        for formation in [
+            Formation::Aggressive,
+            Formation::Balanced,
+            Formation::Defensive,
+        ] {
+            for family in [&small_inner_font, &small_outer_font] {
+                painter.text(
+                    default(),
+                    Align2::LEFT_CENTER,
+                    format!("Cpus: {}", formation.name()),
+                    FontId {
+                        size: 7.0,
+                        family: FontFamily::Name(family.clone()),
+                    },
+                    default(),
+                );
+            }
+        }
+
+        // Formations
+        let mut clicked_formation = false;
+        for team in [Team::A, Team::B] {
+            let builder =
+                TextPainter::new(format!("Cpus: {}", self.tactics.formation(team).name()))
+                    .size(7.0)
+                    .pos(origin + slots.formation_pos(team).to_array().into())
+                    .align2(Align2::CENTER_CENTER);
+            let rect = builder
+                .clone()
+                .family(small_inner_font.clone())
+                .color(Color32::WHITE)
+                .paint(&painter);
+            builder
+                .clone()
+                .family(small_outer_font.clone())
+                .color(Color32::BLACK)
+                .paint(&painter);
+            if ctx.clicked_rect(rect) {
+                self.tactics.formation_mut(team).cycle(1);
+                clicked_formation = true;
+            }
+        }
+
         for player_slot in [
             PlayerSlot::A1,
             PlayerSlot::A2,
@@ -378,7 +429,7 @@ impl TeamSelect {
                     if self.is_player_slot_set(player_slot)
                         && !self.is_player_slot_double(player_slot)
                         && !self.is_player_slot_hovered(player_slot.partner())
-                        && !join.is_source(SingleSource::KeyboardMouse)
+                        && join.get_source().is_some_and(|s| s.can_twin_stick())
                     {
                         let pos = origin + partner_slot.to_array().into();
 
@@ -460,7 +511,7 @@ impl TeamSelect {
                 let player_icon_offset = slots.number_icon_offset.to_array().into();
                 let source_icon = match join.get_source().unwrap() {
                     SingleSource::Gamepad(..) => controller_icon,
-                    SingleSource::KeyboardMouse => keyboard_icon,
+                    SingleSource::KeyboardMouse | SingleSource::Keyboard2 => keyboard_icon,
                     SingleSource::CPU(..) => unreachable!(),
                 };
 
@@ -586,15 +637,15 @@ impl TeamSelect {
                 });
         }
 
-        if !clicking_back_btn && output.is_none() {
+        if !clicking_back_btn && !clicked_formation && output.is_none() {
             if clicked_a {
-                self.keyboard_join_a();
+                self.keyboard_join_a(SingleSource::KeyboardMouse);
             }
             if clicked_center {
-                self.keyboard_join_center();
+                self.keyboard_join_center(SingleSource::KeyboardMouse);
             }
             if clicked_b {
-                self.keyboard_join_b();
+                self.keyboard_join_b(SingleSource::KeyboardMouse);
             }
             if clicked_anywhere
                 && self
@@ -58,6 +58,8 @@ pub struct TeamSelectSlots {
     pub number_icon_offset: Vec2,
     pub ready_text_offset: Vec2,
     pub ready_btn_offset: Vec2,
+    pub formation_a: Vec2,
+    pub formation_b: Vec2,
 
     pub start_offset: Vec2,
     pub back_btn_offset: Vec2,
@@ -74,6 +76,12 @@ impl TeamSelectSlots {
             PlayerSlot::B2 => self.b2,
         }
     }
+    pub fn formation_pos(&self, team: Team) -> Vec2 {
+        match team {
+            Team::A => self.formation_a,
+            Team::B => self.formation_b,
+        }
+    }
     pub fn player_slots(&self) -> [&Vec2; 4] {
         [&self.a1, &self.a2, &self.b1, &self.b2]
     }
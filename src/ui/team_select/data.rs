@@ -187,11 +187,14 @@ impl Join {
 pub struct TeamSelect {
     pub visible: bool,
     pub joins: [Join; 4],
+    /// The formations of the cpus, cycled with the bumpers or by clicking them.
+    pub tactics: CpuTactics,
 }
 impl ShowHide for TeamSelect {
     fn show(&mut self) {
         *self = Self {
             visible: true,
+            tactics: self.tactics,
             ..Default::default()
         };
     }
@@ -260,7 +263,7 @@ impl TeamSelect {
             } else if join.is_single()
                 && !join.is_double()
                 && twin_stick_able
-                && !join.is_source(SingleSource::KeyboardMouse)
+                && join.get_source().is_some_and(|s| s.can_twin_stick())
             {
                 join.double();
             }
@@ -361,7 +364,7 @@ impl TeamSelect {
                 else {
                     unreachable!()
                 };
-                if *source != SingleSource::KeyboardMouse {
+                if source.can_twin_stick() {
                     partner_setting.cycle();
                 }
             }
@@ -397,71 +400,61 @@ impl TeamSelect {
                 else {
                     unreachable!()
                 };
-                if *source != SingleSource::KeyboardMouse {
+                if source.can_twin_stick() {
                     partner_setting.cycle();
                 }
             }
         }
     }
-    pub fn keyboard_join_a(&mut self) {
+    pub fn keyboard_join_a(&mut self, source: SingleSource) {
         if let Some(slot) = self.next_slot_a() {
-            if let Some(i) = self.joins.iter().enumerate().find_map(|(i, join)| {
-                join.get_source()
-                    .is_some_and(|s| s == SingleSource::KeyboardMouse)
-                    .then_some(i)
-            }) {
+            if let Some(i) =
+                self.joins.iter().enumerate().find_map(|(i, join)| {
+                    join.get_source().is_some_and(|s| s == source).then_some(i)
+                })
+            {
                 if self.joins[i]
                     .get_player_slot()
                     .is_some_and(|s| s.team() == Team::A)
                 {
-                    self.joins[i] = Join::Joined {
-                        source: SingleSource::KeyboardMouse,
-                    };
+                    self.joins[i] = Join::Joined { source };
                 } else {
                     self.joins[i] = Join::Single {
                         partner_setting: PartnerSetting::CPU,
-                        source: SingleSource::KeyboardMouse,
+                        source,
                         slot,
                     };
                 }
             }
         }
     }
-    pub fn keyboard_join_b(&mut self) {
+    pub fn keyboard_join_b(&mut self, source: SingleSource) {
         if let Some(slot) = self.next_slot_b() {
-            if let Some(i) = self.joins.iter().enumerate().find_map(|(i, join)| {
-                join.get_source()
-                    .is_some_and(|s| s == SingleSource::KeyboardMouse)
-                    .then_some(i)
-            }) {
+            if let Some(i) =
+                self.joins.iter().enumerate().find_map(|(i, join)| {
+                    join.get_source().is_some_and(|s| s == source).then_some(i)
+                })
+            {
                 if self.joins[i]
                     .get_player_slot()
                     .is_some_and(|s| s.team() == Team::B)
                 {
-                    self.joins[i] = Join::Joined {
-                        source: SingleSource::KeyboardMouse,
-                    };
+                    self.joins[i] = Join::Joined { source };
                 } else {
                     self.joins[i] = Join::Single {
                         partner_setting: PartnerSetting::CPU,
-                        source: SingleSource::KeyboardMouse,
+                        source,
                         slot,
                     };
                 }
             }
         }
     }
-    pub fn keyboard_join_center(&mut self) {
+    pub fn keyboard_join_center(&mut self, source: SingleSource) {
         if let Some(i) = self.joins.iter().enumerate().find_map(|(i, join)| {
-            (join
-                .get_source()
-                .is_some_and(|s| s == SingleSource::KeyboardMouse)
-                && join.is_hovered())
-            .then_some(i)
+            (join.get_source().is_some_and(|s| s == source) && join.is_hovered()).then_some(i)
         }) {
-            self.joins[i] = Join::Joined {
-                source: SingleSource::KeyboardMouse,
-            };
+            self.joins[i] = Join::Joined { source };
         }
     }
     pub fn is_double(&self, source: SingleSource) -> bool {
@@ -509,7 +502,7 @@ impl TeamSelect {
             match join {
                 Join::Single { source, slot, .. } => {
                     if self.get_join_from_slot(slot.partner()).is_none() {
-                        if let SingleSource::KeyboardMouse = source {
+                        if source.is_keyboard() {
                             builder.insert(
                                 *slot,
                                 PlayerInfo::Local {
@@ -518,7 +511,7 @@ impl TeamSelect {
                                     twin_stick: false,
                                 },
                             );
-                            builder.insert(slot.partner(), PlayerInfo::CPU);
+                            builder.insert(slot.partner(), PlayerInfo::cpu());
                         } else {
                             return None;
                         }
@@ -547,7 +540,7 @@ impl TeamSelect {
                                 twin_stick: false,
                             },
                         );
-                        builder.insert(slot.partner(), PlayerInfo::CPU);
+                        builder.insert(slot.partner(), PlayerInfo::cpu());
                     }
                     PartnerSetting::TwinStick => {
                         builder.insert(
@@ -581,7 +574,7 @@ impl TeamSelect {
             PlayerSlot::B2,
         ] {
             if !builder.contains_key(&slot) {
-                builder.insert(slot, PlayerInfo::CPU);
+                builder.insert(slot, PlayerInfo::cpu());
             }
         }
         Some(PlayersInfo {
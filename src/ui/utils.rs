@@ -10,13 +10,17 @@ pub fn show_all(world: &World) {
 
     fade::show(world);
 
-    if let Some(world) = world.resource_mut::<Sessions>().get_world(session::PLAY) {
+    let ui = world;
+    if let Some(world) = ui.resource_mut::<Sessions>().get_world(session::PLAY) {
         world.resource_mut::<MatchDone>().process_input(world);
         world.resource_mut::<MatchDone>().process_ui(world);
         fade::show(world);
         countdown::show(world);
         score_display::show(world);
         winner::show(world);
+        input_display::show(ui, world);
+        #[cfg(not(target_arch = "wasm32"))]
+        network_diagnostics::show(world);
     }
 }
 
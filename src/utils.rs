@@ -11,6 +11,7 @@ mod follow;
 mod input;
 mod lifetime;
 mod path2d;
+mod rng;
 mod state;
 
 pub use egui::*;
@@ -19,6 +20,7 @@ pub use follow::*;
 pub use input::*;
 pub use lifetime::*;
 pub use path2d::*;
+pub use rng::*;
 pub use state::*;
 
 use bones_framework::prelude::*;
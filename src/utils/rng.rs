@@ -0,0 +1,44 @@
+/// A small deterministic random number generator, so that anything seeded
+/// with it doesn't depend on anything but the seed.
+#[derive(Clone, Debug)]
+pub struct SplitMix64(pub u64);
+impl SplitMix64 {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+    /// A number in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+    /// A normally distributed number with a mean of `0.0` and a deviation of `1.0`.
+    pub fn next_gaussian(&mut self) -> f32 {
+        let u1 = self.next_f32().max(f32::MIN_POSITIVE);
+        let u2 = self.next_f32();
+        (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded() {
+        let mut a = SplitMix64(7);
+        let mut b = SplitMix64(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(SplitMix64(7).next_u64(), SplitMix64(8).next_u64());
+
+        let mut rng = SplitMix64(0);
+        let samples = (0..10_000).map(|_| rng.next_f32()).collect::<Vec<_>>();
+        assert!(samples.iter().all(|sample| (0.0..1.0).contains(sample)));
+        let mean = (0..10_000).map(|_| rng.next_gaussian()).sum::<f32>() / 10_000.0;
+        assert!(mean.abs() < 0.05);
+    }
+}
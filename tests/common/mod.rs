@@ -0,0 +1,72 @@
+//! The stand-in matchmaker that the LAN and online matchmaking are tested
+//! against.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use striker_ball::MatchmakingEvent;
+
+/// Queues the events of every client and hands out a socket id to every
+/// player of a full match, with the state of the matchmaker being stood in
+/// for.
+pub struct StandInServer<T, E> {
+    pub state: T,
+    events: HashMap<usize, VecDeque<MatchmakingEvent<u32, E>>>,
+    next_client: usize,
+    next_socket: u32,
+}
+impl<T, E> StandInServer<T, E> {
+    pub fn send(&mut self, client: usize, event: MatchmakingEvent<u32, E>) {
+        self.events.entry(client).or_default().push_back(event);
+    }
+    /// The socket of a match that just filled up.
+    pub fn next_socket(&mut self) -> u32 {
+        self.next_socket += 1;
+        self.next_socket
+    }
+}
+
+/// A player of the [`StandInServer`], which the services are implemented for.
+pub struct StandInClient<T, E> {
+    pub id: usize,
+    server: Arc<Mutex<StandInServer<T, E>>>,
+}
+impl<T, E> StandInClient<T, E> {
+    pub fn server(&self) -> MutexGuard<'_, StandInServer<T, E>> {
+        self.server.lock().unwrap()
+    }
+    /// The next event sent to the client.
+    pub fn poll(&self) -> Option<MatchmakingEvent<u32, E>> {
+        self.server().events.get_mut(&self.id)?.pop_front()
+    }
+}
+
+pub struct StandIn<T, E>(Arc<Mutex<StandInServer<T, E>>>);
+impl<T: Default, E> Default for StandIn<T, E> {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(StandInServer {
+            state: T::default(),
+            events: HashMap::new(),
+            next_client: 0,
+            next_socket: 0,
+        })))
+    }
+}
+impl<T, E> StandIn<T, E> {
+    /// A new player of the stand-in.
+    pub fn client(&self) -> StandInClient<T, E> {
+        let mut server = self.0.lock().unwrap();
+        server.next_client += 1;
+        StandInClient {
+            id: server.next_client,
+            server: self.0.clone(),
+        }
+    }
+    pub fn server(&self) -> MutexGuard<'_, StandInServer<T, E>> {
+        self.0.lock().unwrap()
+    }
+}
+
+pub fn default<T: Default>() -> T {
+    T::default()
+}
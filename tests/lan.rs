@@ -0,0 +1,318 @@
+//! LAN matchmaking against a local stand-in for the LAN matchmaker.
+
+mod common;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use bones_framework::networking::lan::ServerInfo;
+use common::default;
+use striker_ball::{
+    direct_server_info, server_players, with_players, DirectHost, DirectJoin, LanError, LanEvent,
+    LanMatchmaking, LanService, LanStatus, HOST_TIMEOUT, JOIN_TIMEOUT, LAN_RETRIES,
+    NODE_ADDR_PROPERTY,
+};
+
+const SERVICE_TYPE: &str = "_striker_ball._udp.local.";
+
+fn address(port: u16) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], port))
+}
+
+/// The server the client with the id hosts on its port.
+fn server(id: usize) -> ServerInfo {
+    let properties = HashMap::from([(NODE_ADDR_PROPERTY.to_string(), format!("{id:02x}"))]);
+    direct_server_info(SERVICE_TYPE, address(7000 + id as u16), properties).unwrap()
+}
+
+struct StandInHost {
+    player_count: u32,
+    /// The clients in the match, the host first.
+    joined: Vec<usize>,
+}
+
+/// Hosts matches on a port for every client.
+#[derive(Default)]
+struct StandInLan {
+    hosts: HashMap<u16, StandInHost>,
+    /// How many times each client hosted or joined.
+    attempts: HashMap<usize, usize>,
+}
+
+type StandIn = common::StandIn<StandInLan, LanError>;
+type StandInClient = common::StandInClient<StandInLan, LanError>;
+
+fn port(id: usize) -> u16 {
+    7000 + id as u16
+}
+
+impl LanService<u32> for StandInClient {
+    fn host(&self, _service_type: &str, _host_name: &str, player_count: u32) -> Result<(), String> {
+        let mut server = self.server();
+        *server.state.attempts.entry(self.id).or_default() += 1;
+        server.state.hosts.insert(
+            port(self.id),
+            StandInHost {
+                player_count,
+                joined: vec![self.id],
+            },
+        );
+        server.send(self.id, LanEvent::PlayerCount(1));
+        Ok(())
+    }
+    fn join(&self, info: &ServerInfo) -> Result<(), String> {
+        let mut server = self.server();
+        *server.state.attempts.entry(self.id).or_default() += 1;
+        let Some(host) = server.state.hosts.get_mut(&info.service.get_port()) else {
+            return Err("no server on the port".to_string());
+        };
+        // The server filled up after it was found.
+        if host.joined.len() == host.player_count as usize {
+            server.send(self.id, LanEvent::Error(LanError::ServerFull));
+            return Ok(());
+        }
+        host.joined.push(self.id);
+        let joined = host.joined.clone();
+        let full = joined.len() == host.player_count as usize;
+
+        server.send(joined[0], LanEvent::PlayerCount(joined.len()));
+        if full {
+            let socket = server.next_socket();
+            for client in joined {
+                server.send(client, LanEvent::Ready(socket));
+            }
+        }
+        Ok(())
+    }
+    fn cancel(&self) {
+        let mut server = self.server();
+        if let Some(host) = server.state.hosts.remove(&port(self.id)) {
+            for client in host.joined.into_iter().skip(1) {
+                server.send(client, LanEvent::Error(LanError::HostLeft));
+            }
+            return;
+        }
+        let mut left = Vec::new();
+        for host in server.state.hosts.values_mut() {
+            if host.joined.contains(&self.id) {
+                host.joined.retain(|client| *client != self.id);
+                left.push((host.joined[0], host.joined.len()));
+            }
+        }
+        for (host, players) in left {
+            server.send(host, LanEvent::PlayerCount(players));
+        }
+    }
+    fn poll(&self) -> Option<LanEvent<u32>> {
+        StandInClient::poll(self)
+    }
+    fn hosted_server(&self) -> Option<ServerInfo> {
+        advertised(&self.server().state, self.id)
+    }
+}
+
+/// The server of the client as it is advertised, with its players.
+fn advertised(lan: &StandInLan, id: usize) -> Option<ServerInfo> {
+    let host = lan.hosts.get(&port(id))?;
+    Some(with_players(&server(id), host.joined.len(), host.player_count as usize).unwrap())
+}
+
+/// A player that hosts and joins through the stand-in.
+fn player(stand_in: &StandIn) -> (usize, LanMatchmaking<u32>) {
+    let client = stand_in.client();
+    (client.id, LanMatchmaking::new(Arc::new(client)))
+}
+
+fn attempts(stand_in: &StandIn, id: usize) -> usize {
+    stand_in
+        .server()
+        .state
+        .attempts
+        .get(&id)
+        .copied()
+        .unwrap_or_default()
+}
+
+fn update(players: &mut [&mut LanMatchmaking<u32>], delta: std::time::Duration) {
+    for player in players {
+        player.update(delta);
+    }
+}
+
+#[test]
+fn host_and_join() {
+    let stand_in = StandIn::default();
+    let (host_id, mut host) = player(&stand_in);
+    let (_, mut guest) = player(&stand_in);
+
+    host.host(SERVICE_TYPE, "host", 2);
+    update(&mut [&mut host], default());
+    assert_eq!(host.joined_players(), Some(1));
+    assert_eq!(
+        host.hosted_server().map(|server| server.service.get_port()),
+        Some(7000 + host_id as u16)
+    );
+
+    guest.join(&server(host_id));
+    assert!(guest.is_joining());
+    update(&mut [&mut host, &mut guest], default());
+    assert_eq!(*host.status(), LanStatus::Ready);
+    assert_eq!(*guest.status(), LanStatus::Ready);
+    assert!(host.socket().is_some());
+    assert_eq!(host.socket(), guest.socket());
+
+    // Cancelling after the match forgets its socket.
+    guest.cancel();
+    assert!(guest.socket().is_none());
+    assert_eq!(*guest.status(), LanStatus::Idle);
+}
+
+/// Asks the direct host of the player for its server with the code.
+fn join_direct(
+    direct: &mut DirectHost,
+    host: &LanMatchmaking<u32>,
+    code: u16,
+) -> Result<ServerInfo, LanError> {
+    let mut join = DirectJoin::connect(address(direct.port().unwrap()), code);
+    loop {
+        direct.update(&host.hosted_server().unwrap());
+        if let Some(server) = join.poll(SERVICE_TYPE, default())? {
+            return Ok(server);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn join_by_address() {
+    let stand_in = StandIn::default();
+    let (_, mut host) = player(&stand_in);
+    let (_, mut guest) = player(&stand_in);
+    let mut direct = DirectHost::with_code(1234).unwrap();
+
+    host.host(SERVICE_TYPE, "host", 2);
+    assert_eq!(
+        join_direct(&mut direct, &host, 4321).err(),
+        Some(LanError::WrongCode)
+    );
+    let server = join_direct(&mut direct, &host, 1234).unwrap();
+
+    guest.join(&server);
+    update(&mut [&mut host, &mut guest], default());
+    assert_eq!(*host.status(), LanStatus::Ready);
+    assert_eq!(*guest.status(), LanStatus::Ready);
+    assert_eq!(host.socket(), guest.socket());
+}
+
+#[test]
+fn server_full() {
+    let stand_in = StandIn::default();
+    let (host_id, mut host) = player(&stand_in);
+    let (_, mut guest) = player(&stand_in);
+    let (late_id, mut late) = player(&stand_in);
+
+    host.host(SERVICE_TYPE, "host", 2);
+    let found = host.hosted_server().unwrap();
+    assert_eq!(server_players(&found), Some((1, 2)));
+    guest.join(&found);
+    update(&mut [&mut host, &mut guest], default());
+    assert_eq!(*guest.status(), LanStatus::Ready);
+
+    // The server advertises that it's full, so it isn't joined at all.
+    let full = advertised(&stand_in.server().state, host_id).unwrap();
+    assert_eq!(server_players(&full), Some((2, 2)));
+    late.join(&full);
+    assert_eq!(*late.status(), LanStatus::Failed(LanError::ServerFull));
+    assert_eq!(attempts(&stand_in, late_id), 0);
+    assert!(late.socket().is_none());
+
+    // The error is only shown once.
+    assert_eq!(late.take_error(), Some(LanError::ServerFull));
+    assert_eq!(late.take_error(), None);
+    assert_eq!(*late.status(), LanStatus::Idle);
+
+    // A server that filled up after it was found refuses the join.
+    late.join(&found);
+    update(&mut [&mut late], default());
+    assert_eq!(*late.status(), LanStatus::Failed(LanError::ServerFull));
+    assert_eq!(attempts(&stand_in, late_id), 1);
+}
+
+#[test]
+fn host_left() {
+    let stand_in = StandIn::default();
+    let (host_id, mut host) = player(&stand_in);
+    let (_, mut guest) = player(&stand_in);
+
+    host.host(SERVICE_TYPE, "host", 3);
+    guest.join(&server(host_id));
+    update(&mut [&mut host, &mut guest], default());
+    assert_eq!(host.joined_players(), Some(2));
+    assert!(guest.is_joining());
+
+    host.cancel();
+    update(&mut [&mut host, &mut guest], default());
+    assert_eq!(*host.status(), LanStatus::Idle);
+    assert_eq!(*guest.status(), LanStatus::Failed(LanError::HostLeft));
+}
+
+#[test]
+fn unknown_server() {
+    let stand_in = StandIn::default();
+    let (_, mut guest) = player(&stand_in);
+
+    guest.join(&server(42));
+    assert_eq!(*guest.status(), LanStatus::Failed(LanError::Failed));
+}
+
+#[test]
+fn join_timeout() {
+    let stand_in = StandIn::default();
+    let (host_id, mut host) = player(&stand_in);
+    let (guest_id, mut guest) = player(&stand_in);
+
+    host.host(SERVICE_TYPE, "host", 3);
+    guest.join(&server(host_id));
+    update(&mut [&mut host, &mut guest], JOIN_TIMEOUT / 2);
+    assert!(guest.is_joining());
+    assert_eq!(attempts(&stand_in, guest_id), 1);
+
+    // Joining is tried again, without the guest being in the match twice.
+    for retry in 1..=LAN_RETRIES {
+        update(&mut [&mut host, &mut guest], JOIN_TIMEOUT);
+        assert!(guest.is_joining());
+        assert_eq!(attempts(&stand_in, guest_id), 1 + retry as usize);
+        assert_eq!(host.joined_players(), Some(2));
+    }
+
+    update(&mut [&mut host, &mut guest], JOIN_TIMEOUT);
+    assert_eq!(*guest.status(), LanStatus::Failed(LanError::TimedOut));
+    update(&mut [&mut host], default());
+    assert_eq!(host.joined_players(), Some(1));
+}
+
+#[test]
+fn host_timeout() {
+    let stand_in = StandIn::default();
+    let (host_id, mut host) = player(&stand_in);
+    let (_, mut guest) = player(&stand_in);
+
+    host.host(SERVICE_TYPE, "host", 3);
+    for retry in 1..=LAN_RETRIES {
+        update(&mut [&mut host], HOST_TIMEOUT);
+        assert!(host.is_hosting());
+        assert_eq!(attempts(&stand_in, host_id), 1 + retry as usize);
+    }
+    update(&mut [&mut host], HOST_TIMEOUT);
+    assert_eq!(*host.status(), LanStatus::Failed(LanError::TimedOut));
+    assert!(host.hosted_server().is_none());
+
+    // The server isn't advertised again once someone joined it.
+    host.host(SERVICE_TYPE, "host", 3);
+    guest.join(&server(host_id));
+    for _ in 0..=LAN_RETRIES {
+        update(&mut [&mut host], HOST_TIMEOUT);
+    }
+    assert_eq!(host.joined_players(), Some(2));
+}
@@ -0,0 +1,169 @@
+//! Online matchmaking against a local stand-in for the matchmaking server.
+
+mod common;
+
+use std::sync::Arc;
+
+use common::default;
+use striker_ball::{
+    MatchCode, OnlineEvent, OnlineMatchmaking, OnlineSearch, OnlineService, OnlineStatus,
+};
+
+const SERVER: &str = "stand-in";
+
+/// The players waiting for a match, with their player count and match data.
+type StandInOnline = Vec<(usize, u32, Vec<u8>)>;
+
+type StandIn = common::StandIn<StandInOnline, String>;
+type StandInClient = common::StandInClient<StandInOnline, String>;
+
+impl OnlineService<u32> for StandInClient {
+    fn search(&self, server: &str, player_count: u32, match_data: Vec<u8>) -> Result<(), String> {
+        if server != SERVER {
+            return Err(format!("unknown matchmaking server: {server}"));
+        }
+        let mut server = self.server();
+        server
+            .state
+            .push((self.id, player_count, match_data.clone()));
+
+        let players = server
+            .state
+            .iter()
+            .filter(|(_, count, data)| *count == player_count && *data == match_data)
+            .map(|(id, ..)| *id)
+            .collect::<Vec<_>>();
+        let event = if players.len() == player_count as usize {
+            server.state.retain(|(id, ..)| !players.contains(id));
+            OnlineEvent::Ready(server.next_socket())
+        } else {
+            OnlineEvent::PlayerCount(players.len())
+        };
+        for id in players {
+            server.send(id, event.clone());
+        }
+        Ok(())
+    }
+    fn cancel(&self, _server: &str) {
+        self.server().state.retain(|(id, ..)| *id != self.id);
+    }
+    fn poll(&self) -> Option<OnlineEvent<u32>> {
+        StandInClient::poll(self)
+    }
+}
+
+/// A player that finds matches of two through the stand-in.
+fn player(stand_in: &StandIn) -> OnlineMatchmaking<u32> {
+    OnlineMatchmaking::new(Arc::new(stand_in.client()), 2)
+}
+
+fn update(players: &mut [&mut OnlineMatchmaking<u32>]) {
+    for player in players {
+        player.update(default());
+    }
+}
+
+#[test]
+fn quick_match() {
+    let stand_in = StandIn::default();
+    let mut a = player(&stand_in);
+    let mut b = player(&stand_in);
+
+    a.search(SERVER, OnlineSearch::QuickMatch);
+    update(&mut [&mut a]);
+    assert_eq!(*a.status(), OnlineStatus::Waiting { players: 1 });
+    assert_eq!(a.searching(), Some(OnlineSearch::QuickMatch));
+    assert!(a.socket().is_none());
+
+    b.search(SERVER, OnlineSearch::QuickMatch);
+    update(&mut [&mut a, &mut b]);
+    assert_eq!(*a.status(), OnlineStatus::Ready);
+    assert_eq!(*b.status(), OnlineStatus::Ready);
+    assert!(a.socket().is_some());
+    assert_eq!(a.socket(), b.socket());
+}
+
+#[test]
+fn private_match_codes() {
+    let stand_in = StandIn::default();
+    let code = MatchCode::from_seed(1);
+    let other_code = MatchCode::from_seed(2);
+    assert_ne!(code, other_code);
+
+    let mut host = player(&stand_in);
+    let mut stranger = player(&stand_in);
+    let mut wrong_code = player(&stand_in);
+    let mut friend = player(&stand_in);
+
+    host.search(SERVER, OnlineSearch::Private(code));
+    stranger.search(SERVER, OnlineSearch::QuickMatch);
+    wrong_code.search(SERVER, OnlineSearch::Private(other_code));
+    update(&mut [&mut host, &mut stranger, &mut wrong_code]);
+    for player in [&host, &stranger, &wrong_code] {
+        assert!(player.is_searching());
+        assert!(player.socket().is_none());
+    }
+
+    friend.search(SERVER, OnlineSearch::Private(code));
+    update(&mut [&mut host, &mut stranger, &mut wrong_code, &mut friend]);
+    assert_eq!(*host.status(), OnlineStatus::Ready);
+    assert_eq!(host.socket(), friend.socket());
+    assert!(stranger.is_searching());
+    assert!(wrong_code.is_searching());
+}
+
+#[test]
+fn cancel_leaves_the_queue() {
+    let stand_in = StandIn::default();
+    let mut a = player(&stand_in);
+    let mut b = player(&stand_in);
+    let mut c = player(&stand_in);
+
+    a.search(SERVER, OnlineSearch::QuickMatch);
+    a.cancel();
+    assert_eq!(*a.status(), OnlineStatus::Idle);
+    assert!(stand_in.server().state.is_empty());
+
+    b.search(SERVER, OnlineSearch::QuickMatch);
+    update(&mut [&mut a, &mut b]);
+    assert_eq!(*a.status(), OnlineStatus::Idle);
+    assert!(b.is_searching());
+
+    c.search(SERVER, OnlineSearch::QuickMatch);
+    update(&mut [&mut a, &mut b, &mut c]);
+    assert_eq!(*a.status(), OnlineStatus::Idle);
+    assert_eq!(b.socket(), c.socket());
+    assert!(b.socket().is_some());
+
+    // Cancelling after the match forgets its socket.
+    b.cancel();
+    assert!(b.socket().is_none());
+}
+
+#[test]
+fn unknown_server() {
+    let stand_in = StandIn::default();
+    let mut a = player(&stand_in);
+
+    a.search("elsewhere", OnlineSearch::QuickMatch);
+    assert!(matches!(a.status(), OnlineStatus::Failed(..)));
+
+    a.cancel();
+    assert_eq!(*a.status(), OnlineStatus::Idle);
+}
+
+#[test]
+fn match_code_cycle() {
+    let mut code = MatchCode::default();
+    assert_eq!(code.to_string(), "AAAA");
+
+    code.cycle(0, -1);
+    code.cycle(1, 1);
+    code.cycle(MatchCode::LEN, 1);
+    assert_eq!(code.to_string(), "9BAA");
+
+    for _ in 0..MatchCode::ALPHABET.len() {
+        code.cycle(2, 1);
+    }
+    assert_eq!(code.to_string(), "9BAA");
+}